@@ -0,0 +1,176 @@
+//! Mapping between account names and security identifiers.
+//!
+//! Principals name their account either way, e.g. `NT AUTHORITY\SYSTEM` or
+//! `S-1-5-18`. An [`AccountResolver`] turns one into the other: the
+//! Windows implementation asks the local security authority, while
+//! [`WellKnownAccounts`] answers from a fixed table and works anywhere.
+//! [`FallbackAccounts`] combines two, e.g. to fall back on the table when
+//! the system lookup fails.
+
+mod sid;
+mod well_known;
+#[cfg(all(windows, feature = "windows-backend"))]
+mod windows;
+
+use std::fmt;
+
+pub use sid::Sid;
+pub use well_known::WellKnownAccounts;
+#[cfg(all(windows, feature = "windows-backend"))]
+pub use windows::WindowsAccounts;
+
+use crate::error::ComError;
+
+/// An account with its domain, name and SID.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Account {
+    /// Domain or computer holding the account, e.g. `NT AUTHORITY`,
+    /// `BUILTIN` or `CORP`. Empty for accounts such as `Everyone`.
+    pub domain: String,
+    pub name: String,
+    pub sid: Sid,
+}
+
+impl Account {
+    /// The name as `DOMAIN\name`, or just `name` without a domain.
+    pub fn qualified_name(&self) -> String {
+        if self.domain.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}\\{}", self.domain, self.name)
+        }
+    }
+}
+
+/// Looks up accounts by name or by SID.
+pub trait AccountResolver {
+    /// Finds the account called `name`, given as `name` or `DOMAIN\name`.
+    fn lookup_name(&self, name: &str) -> Result<Account, AccountError>;
+
+    /// Finds the account `sid` belongs to.
+    fn lookup_sid(&self, sid: &Sid) -> Result<Account, AccountError>;
+
+    /// Finds the account a principal's user or group id names, which may
+    /// be a SID string or a name.
+    fn resolve(&self, id: &str) -> Result<Account, AccountError> {
+        match id.parse::<Sid>() {
+            Ok(sid) => self.lookup_sid(&sid),
+            Err(_) => self.lookup_name(id),
+        }
+    }
+}
+
+/// Asks `primary` first and `fallback` only when `primary` fails. If both
+/// fail, the error from `primary` is returned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FallbackAccounts<P, F> {
+    pub primary: P,
+    pub fallback: F,
+}
+
+impl<P: AccountResolver, F: AccountResolver> FallbackAccounts<P, F> {
+    pub fn new(primary: P, fallback: F) -> Self {
+        Self { primary, fallback }
+    }
+}
+
+impl<P: AccountResolver, F: AccountResolver> AccountResolver for FallbackAccounts<P, F> {
+    fn lookup_name(&self, name: &str) -> Result<Account, AccountError> {
+        self.primary.lookup_name(name).or_else(|e| self.fallback.lookup_name(name).map_err(|_| e))
+    }
+
+    fn lookup_sid(&self, sid: &Sid) -> Result<Account, AccountError> {
+        self.primary.lookup_sid(sid).or_else(|e| self.fallback.lookup_sid(sid).map_err(|_| e))
+    }
+}
+
+/// Resolves accounts with [`WindowsAccounts`], falling back on
+/// [`WellKnownAccounts`] when the system lookup fails.
+#[cfg(all(windows, feature = "windows-backend"))]
+pub fn system_accounts() -> FallbackAccounts<WindowsAccounts, WellKnownAccounts> {
+    FallbackAccounts::new(WindowsAccounts, WellKnownAccounts)
+}
+
+/// Why an account could not be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountError {
+    /// A SID string or structure that is malformed.
+    InvalidSid(String),
+    /// No account has the given name or SID.
+    NotFound(String),
+    /// The system lookup failed.
+    Lookup(ComError),
+}
+
+impl fmt::Display for AccountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountError::InvalidSid(sid) => write!(f, "{:?} is not a valid SID", sid),
+            AccountError::NotFound(account) => write!(f, "no account matches {:?}", account),
+            AccountError::Lookup(e) => write!(f, "account lookup failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AccountError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AccountError::Lookup(e) => Some(e),
+            AccountError::InvalidSid(_) | AccountError::NotFound(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    /// Records each lookup and answers only for the accounts it was given.
+    struct FakeAccounts<'a> {
+        label: &'static str,
+        accounts: Vec<Account>,
+        error: AccountError,
+        calls: &'a RefCell<Vec<String>>,
+    }
+
+    impl AccountResolver for FakeAccounts<'_> {
+        fn lookup_name(&self, name: &str) -> Result<Account, AccountError> {
+            self.calls.borrow_mut().push(format!("{} {}", self.label, name));
+            self.accounts.iter().find(|a| a.qualified_name() == name).cloned().ok_or_else(|| self.error.clone())
+        }
+
+        fn lookup_sid(&self, sid: &Sid) -> Result<Account, AccountError> {
+            self.calls.borrow_mut().push(format!("{} {}", self.label, sid));
+            self.accounts.iter().find(|a| a.sid == *sid).cloned().ok_or_else(|| self.error.clone())
+        }
+    }
+
+    #[test]
+    fn falls_back_only_when_the_primary_lookup_fails() {
+        let calls = RefCell::new(Vec::new());
+        let alice = Account { domain: "CORP".to_string(), name: "alice".to_string(), sid: "S-1-5-21-1-2-3-1001".parse().unwrap() };
+        let primary = FakeAccounts {
+            label: "primary",
+            accounts: vec![alice.clone()],
+            error: AccountError::Lookup(ComError::new(crate::HResult::E_FAIL, "Cannot look up the SID", "Unspecified error")),
+            calls: &calls,
+        };
+        let fallback = FakeAccounts {
+            label: "fallback",
+            accounts: vec![WellKnownAccounts.lookup_name("SYSTEM").unwrap()],
+            error: AccountError::NotFound("fallback".to_string()),
+            calls: &calls,
+        };
+        let accounts = FallbackAccounts::new(primary, fallback);
+
+        assert_eq!(accounts.resolve("CORP\\alice"), Ok(alice));
+        assert_eq!(accounts.resolve("S-1-5-18").unwrap().qualified_name(), "NT AUTHORITY\\SYSTEM");
+        assert_eq!(accounts.resolve("CORP\\bob"), Err(accounts.primary.error.clone()));
+        assert_eq!(
+            *calls.borrow(),
+            ["primary CORP\\alice", "primary S-1-5-18", "fallback S-1-5-18", "primary CORP\\bob", "fallback CORP\\bob"]
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::AccountError;
+
+/// Largest identifier authority, which is a 48-bit number.
+const MAX_AUTHORITY: u64 = (1 << 48) - 1;
+
+/// A Windows security identifier, such as `S-1-5-18` for `SYSTEM`.
+///
+/// [`Display`](fmt::Display) and [`FromStr`] write and read the string form
+/// used by `ConvertSidToStringSid`; [`Sid::to_bytes`] and
+/// [`Sid::from_bytes`] convert to and from the binary `SID` structure.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Sid {
+    authority: u64,
+    sub_authorities: Vec<u32>,
+}
+
+impl Sid {
+    /// The only SID revision Windows defines.
+    pub const REVISION: u8 = 1;
+    /// Most sub-authorities a SID can have.
+    pub const MAX_SUB_AUTHORITIES: usize = 15;
+
+    /// Creates a SID from its identifier authority, e.g. 5 for
+    /// `NT AUTHORITY`, and its sub-authorities.
+    pub fn new(authority: u64, sub_authorities: &[u32]) -> Result<Sid, AccountError> {
+        if authority > MAX_AUTHORITY || sub_authorities.len() > Self::MAX_SUB_AUTHORITIES {
+            let sid = Sid { authority, sub_authorities: sub_authorities.to_vec() };
+            return Err(AccountError::InvalidSid(sid.to_string()));
+        }
+        Ok(Sid { authority, sub_authorities: sub_authorities.to_vec() })
+    }
+
+    pub fn authority(&self) -> u64 {
+        self.authority
+    }
+
+    pub fn sub_authorities(&self) -> &[u32] {
+        &self.sub_authorities
+    }
+
+    /// The last sub-authority, which names the account within its domain.
+    pub fn rid(&self) -> Option<u32> {
+        self.sub_authorities.last().copied()
+    }
+
+    /// The binary `SID` structure: revision, sub-authority count, the
+    /// authority as six big-endian bytes, then each sub-authority as four
+    /// little-endian bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![Self::REVISION, self.sub_authorities.len() as u8];
+        bytes.extend_from_slice(&self.authority.to_be_bytes()[2..]);
+        for sub_authority in &self.sub_authorities {
+            bytes.extend_from_slice(&sub_authority.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Reads a binary `SID` structure, ignoring any bytes after it.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Sid, AccountError> {
+        let invalid = || AccountError::InvalidSid(format!("{:02X?}", bytes));
+        let (&[revision, count], rest) = bytes.split_first_chunk::<2>().ok_or_else(invalid)?;
+        let count = count as usize;
+        if revision != Self::REVISION || count > Self::MAX_SUB_AUTHORITIES || rest.len() < 6 + 4 * count {
+            return Err(invalid());
+        }
+
+        let mut authority = [0; 8];
+        authority[2..].copy_from_slice(&rest[..6]);
+        let sub_authorities = rest[6..6 + 4 * count]
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        Ok(Sid { authority: u64::from_be_bytes(authority), sub_authorities })
+    }
+}
+
+impl fmt::Display for Sid {
+    /// Writes `S-1-` and the authority, in decimal below 2^32 and in hex
+    /// above, followed by each sub-authority.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "S-{}-", Self::REVISION)?;
+        if self.authority >> 32 == 0 {
+            write!(f, "{}", self.authority)?;
+        } else {
+            write!(f, "0x{:012X}", self.authority)?;
+        }
+        for sub_authority in &self.sub_authorities {
+            write!(f, "-{}", sub_authority)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Sid {
+    type Err = AccountError;
+
+    /// Parses a SID string such as `S-1-5-32-544`. The authority may also
+    /// be written in hex, as `0x` and twelve digits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || AccountError::InvalidSid(s.to_string());
+        let mut parts = s.split('-');
+
+        if !parts.next().is_some_and(|prefix| prefix.eq_ignore_ascii_case("S")) || parts.next() != Some("1") {
+            return Err(invalid());
+        }
+        let authority = parts.next().ok_or_else(invalid)?;
+        let authority = match authority.strip_prefix("0x").or_else(|| authority.strip_prefix("0X")) {
+            Some(hex) if hex.len() == 12 => u64::from_str_radix(hex, 16).ok(),
+            Some(_) => None,
+            None => decimal(authority),
+        }
+        .ok_or_else(invalid)?;
+        let sub_authorities = parts
+            .map(|part| decimal(part).and_then(|value| u32::try_from(value).ok()))
+            .collect::<Option<Vec<u32>>>()
+            .ok_or_else(invalid)?;
+
+        Sid::new(authority, &sub_authorities).map_err(|_| invalid())
+    }
+}
+
+/// Parses a run of ASCII digits, without the sign `u64::from_str` allows.
+fn decimal(s: &str) -> Option<u64> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Sid, AccountError> {
+        s.parse()
+    }
+
+    #[test]
+    fn formats_and_parses_sid_strings() {
+        let cases = [
+            (Sid::new(5, &[18]).unwrap(), "S-1-5-18"),
+            (Sid::new(5, &[32, 544]).unwrap(), "S-1-5-32-544"),
+            (Sid::new(1, &[0]).unwrap(), "S-1-1-0"),
+            (Sid::new(5, &[21, 3623811015, 3361044348, 30300820, 1013]).unwrap(), "S-1-5-21-3623811015-3361044348-30300820-1013"),
+            (Sid::new(0x1234_5678_9ABC, &[1]).unwrap(), "S-1-0x123456789ABC-1"),
+        ];
+        for (sid, text) in cases {
+            assert_eq!(sid.to_string(), text);
+            assert_eq!(parse(text), Ok(sid));
+        }
+        assert_eq!(parse("s-1-5-18"), Sid::new(5, &[18]));
+        assert_eq!(Sid::new(5, &[32, 544]).unwrap().rid(), Some(544));
+    }
+
+    #[test]
+    fn rejects_malformed_sid_strings() {
+        for text in [
+            "", "S", "S-1", "S-2-5-18", "X-1-5-18", "S-1--18", "S-1-5-", "S-1-5-+18", "S-1-5-4294967296",
+            "S-1-0x1-1", "S-1-281474976710656", "S-1-5-1-2-3-4-5-6-7-8-9-10-11-12-13-14-15-16",
+        ] {
+            assert_eq!(parse(text), Err(AccountError::InvalidSid(text.to_string())), "{}", text);
+        }
+    }
+
+    #[test]
+    fn converts_to_and_from_the_binary_form() {
+        let sid = Sid::new(5, &[32, 544]).unwrap();
+        let bytes = [1, 2, 0, 0, 0, 0, 0, 5, 32, 0, 0, 0, 0x20, 2, 0, 0];
+
+        assert_eq!(sid.to_bytes(), bytes);
+        assert_eq!(Sid::from_bytes(&bytes), Ok(sid));
+        assert!(Sid::from_bytes(&bytes[..15]).is_err());
+        assert!(Sid::from_bytes(&[2, 0, 0, 0, 0, 0, 0, 5]).is_err());
+    }
+}
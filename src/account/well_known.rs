@@ -0,0 +1,96 @@
+use super::{Account, AccountError, AccountResolver, Sid};
+
+/// NT authority, the identifier authority of the accounts below.
+const NT_AUTHORITY: u64 = 5;
+
+/// Domain, name and sub-authorities of each account the table knows.
+const ACCOUNTS: &[(&str, &str, &[u32])] = &[
+    ("NT AUTHORITY", "SYSTEM", &[18]),
+    ("NT AUTHORITY", "LOCAL SERVICE", &[19]),
+    ("NT AUTHORITY", "NETWORK SERVICE", &[20]),
+    ("BUILTIN", "Administrators", &[32, 544]),
+    ("BUILTIN", "Users", &[32, 545]),
+];
+
+/// Resolves the well-known accounts every Windows installation has under
+/// the same name and SID, without asking the system:
+///
+/// | Account                        | SID            |
+/// |--------------------------------|----------------|
+/// | `NT AUTHORITY\SYSTEM`          | `S-1-5-18`     |
+/// | `NT AUTHORITY\LOCAL SERVICE`   | `S-1-5-19`     |
+/// | `NT AUTHORITY\NETWORK SERVICE` | `S-1-5-20`     |
+/// | `BUILTIN\Administrators`       | `S-1-5-32-544` |
+/// | `BUILTIN\Users`                | `S-1-5-32-545` |
+///
+/// Names match without regard to case, with or without their domain.
+/// These are the English names; localized systems may use others.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WellKnownAccounts;
+
+impl WellKnownAccounts {
+    fn accounts() -> impl Iterator<Item = Account> {
+        ACCOUNTS.iter().map(|&(domain, name, sub_authorities)| Account {
+            domain: domain.to_string(),
+            name: name.to_string(),
+            sid: Sid::new(NT_AUTHORITY, sub_authorities).expect("well-known SIDs are valid"),
+        })
+    }
+}
+
+impl AccountResolver for WellKnownAccounts {
+    fn lookup_name(&self, name: &str) -> Result<Account, AccountError> {
+        let (domain, account_name) = match name.split_once('\\') {
+            Some((domain, account_name)) => (Some(domain), account_name),
+            None => (None, name),
+        };
+        Self::accounts()
+            .find(|account| {
+                account.name.eq_ignore_ascii_case(account_name)
+                    && domain.is_none_or(|domain| account.domain.eq_ignore_ascii_case(domain))
+            })
+            .ok_or_else(|| AccountError::NotFound(name.to_string()))
+    }
+
+    fn lookup_sid(&self, sid: &Sid) -> Result<Account, AccountError> {
+        Self::accounts()
+            .find(|account| account.sid == *sid)
+            .ok_or_else(|| AccountError::NotFound(sid.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_names_to_sids_and_back() {
+        for (name, sid) in [
+            ("NT AUTHORITY\\SYSTEM", "S-1-5-18"),
+            ("LOCAL SERVICE", "S-1-5-19"),
+            ("nt authority\\network service", "S-1-5-20"),
+            ("BUILTIN\\Administrators", "S-1-5-32-544"),
+            ("users", "S-1-5-32-545"),
+        ] {
+            let account = WellKnownAccounts.lookup_name(name).unwrap();
+            assert_eq!(account.sid.to_string(), sid);
+            assert_eq!(WellKnownAccounts.resolve(sid), Ok(account));
+        }
+        assert_eq!(
+            WellKnownAccounts.resolve("S-1-5-32-544").unwrap().qualified_name(),
+            "BUILTIN\\Administrators"
+        );
+    }
+
+    #[test]
+    fn reports_unknown_accounts() {
+        assert_eq!(
+            WellKnownAccounts.lookup_name("CORP\\SYSTEM"),
+            Err(AccountError::NotFound("CORP\\SYSTEM".to_string()))
+        );
+        assert_eq!(
+            WellKnownAccounts.resolve("S-1-5-21-1-2-3-500"),
+            Err(AccountError::NotFound("S-1-5-21-1-2-3-500".to_string()))
+        );
+    }
+}
@@ -0,0 +1,165 @@
+// Account lookup through the local security authority.
+// https://learn.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-lookupaccountnamew
+
+use std::ptr;
+
+use winapi::shared::minwindef::{DWORD, FALSE};
+use winapi::shared::winerror::{ERROR_INSUFFICIENT_BUFFER, ERROR_NONE_MAPPED};
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::processthreadsapi::{GetCurrentProcess, OpenProcessToken};
+use winapi::um::securitybaseapi::{GetLengthSid, GetTokenInformation};
+use winapi::um::winbase::{LookupAccountNameW, LookupAccountSidW};
+use winapi::um::winnt::{TokenUser, HANDLE, PSID, SID_NAME_USE, TOKEN_QUERY, TOKEN_USER};
+
+use super::{Account, AccountError, AccountResolver, Sid};
+use crate::com::{decode, to_wide};
+use crate::error::ComError;
+use crate::hresult::HResult;
+
+/// Resolves accounts with `LookupAccountName` and `LookupAccountSid` on
+/// the local computer, which also asks its domain controllers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WindowsAccounts;
+
+impl WindowsAccounts {
+    /// The account the calling process runs as, read from its token. Unlike
+    /// `USERDOMAIN` and `USERNAME`, this is right for services too.
+    pub fn current_user(&self) -> Result<Account, AccountError> {
+        self.lookup_sid(&self.current_user_sid()?)
+    }
+
+    /// The SID of the account the calling process runs as. Reading it
+    /// needs no lookup, so it works while the domain is unreachable.
+    pub fn current_user_sid(&self) -> Result<Sid, AccountError> {
+        let mut token: HANDLE = ptr::null_mut();
+        if unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) } == FALSE {
+            return Err(last_error("", "Cannot open the process token"));
+        }
+        let sid = token_user(token);
+        unsafe { CloseHandle(token) };
+        sid
+    }
+}
+
+/// Reads the user SID from an access token.
+fn token_user(token: HANDLE) -> Result<Sid, AccountError> {
+    let mut len: DWORD = 0;
+    unsafe { GetTokenInformation(token, TokenUser, ptr::null_mut(), 0, &mut len) };
+
+    // A u64 buffer keeps the TOKEN_USER aligned
+    let mut buffer = vec![0u64; (len as usize).div_ceil(8)];
+    let ok = unsafe { GetTokenInformation(token, TokenUser, buffer.as_mut_ptr().cast(), len, &mut len) };
+    if ok == FALSE {
+        return Err(last_error("", "Cannot read the token user"));
+    }
+
+    let user = unsafe { &*(buffer.as_ptr() as *const TOKEN_USER) };
+    let sid = user.User.Sid;
+    let sid_len = unsafe { GetLengthSid(sid) } as usize;
+    Sid::from_bytes(unsafe { std::slice::from_raw_parts(sid as *const u8, sid_len) })
+}
+
+impl AccountResolver for WindowsAccounts {
+    fn lookup_name(&self, name: &str) -> Result<Account, AccountError> {
+        let name_wide = to_wide(name);
+        let mut sid_len: DWORD = 0;
+        let mut domain_len: DWORD = 0;
+        let mut sid_use: SID_NAME_USE = 0;
+
+        // The first call only reports the buffer sizes
+        let ok = unsafe {
+            LookupAccountNameW(
+                ptr::null(),
+                name_wide.as_ptr(),
+                ptr::null_mut(),
+                &mut sid_len,
+                ptr::null_mut(),
+                &mut domain_len,
+                &mut sid_use,
+            )
+        };
+        if ok == FALSE && unsafe { GetLastError() } != ERROR_INSUFFICIENT_BUFFER {
+            return Err(last_error(name, "Cannot look up the account name"));
+        }
+
+        let mut sid = vec![0u8; sid_len as usize];
+        let mut domain = vec![0u16; domain_len as usize];
+        let ok = unsafe {
+            LookupAccountNameW(
+                ptr::null(),
+                name_wide.as_ptr(),
+                sid.as_mut_ptr() as PSID,
+                &mut sid_len,
+                domain.as_mut_ptr(),
+                &mut domain_len,
+                &mut sid_use,
+            )
+        };
+        if ok == FALSE {
+            return Err(last_error(name, "Cannot look up the account name"));
+        }
+
+        // Look the SID up again for the account's own spelling of its name
+        self.lookup_sid(&Sid::from_bytes(&sid)?)
+    }
+
+    fn lookup_sid(&self, sid: &Sid) -> Result<Account, AccountError> {
+        let mut sid_bytes = sid.to_bytes();
+        let mut name_len: DWORD = 0;
+        let mut domain_len: DWORD = 0;
+        let mut sid_use: SID_NAME_USE = 0;
+        let sid_text = sid.to_string();
+
+        // The first call only reports the buffer sizes
+        let ok = unsafe {
+            LookupAccountSidW(
+                ptr::null(),
+                sid_bytes.as_mut_ptr() as PSID,
+                ptr::null_mut(),
+                &mut name_len,
+                ptr::null_mut(),
+                &mut domain_len,
+                &mut sid_use,
+            )
+        };
+        if ok == FALSE && unsafe { GetLastError() } != ERROR_INSUFFICIENT_BUFFER {
+            return Err(last_error(&sid_text, "Cannot look up the SID"));
+        }
+
+        let mut name = vec![0u16; name_len as usize];
+        let mut domain = vec![0u16; domain_len as usize];
+        let ok = unsafe {
+            LookupAccountSidW(
+                ptr::null(),
+                sid_bytes.as_mut_ptr() as PSID,
+                name.as_mut_ptr(),
+                &mut name_len,
+                domain.as_mut_ptr(),
+                &mut domain_len,
+                &mut sid_use,
+            )
+        };
+        if ok == FALSE {
+            return Err(last_error(&sid_text, "Cannot look up the SID"));
+        }
+
+        // On success the lengths no longer count the terminating NUL
+        Ok(Account {
+            domain: String::from_utf16_lossy(&domain[..domain_len as usize]),
+            name: String::from_utf16_lossy(&name[..name_len as usize]),
+            sid: sid.clone(),
+        })
+    }
+}
+
+/// Builds the error for the failed call that set the thread's last error.
+/// An unmapped name or SID is reported as not found.
+fn last_error(account: &str, context: &str) -> AccountError {
+    let code = unsafe { GetLastError() };
+    if code == ERROR_NONE_MAPPED {
+        return AccountError::NotFound(account.to_string());
+    }
+    let hr = HResult::from_win32(code);
+    AccountError::Lookup(ComError::new(hr, context, &decode(hr)))
+}
@@ -0,0 +1,570 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{
+    config_home,
+    delete_path_folder,
+    error,
+    file_error,
+    path_folder,
+    same_path,
+    split_task_path,
+    unsupported,
+    RegisterMode,
+    RegisteredTask,
+    SchedulerBackend,
+    TaskFolder,
+    TaskState,
+};
+use crate::builder::TaskBuilder;
+use crate::error::SchtaskError;
+use crate::hresult::HResult;
+use crate::model::{
+    split_args,
+    Action,
+    ActionKind,
+    LogonType,
+    Principal,
+    RegisteredTaskInfo,
+    Task,
+    TaskDefinition,
+    TaskDuration,
+    TaskSettings,
+    Trigger,
+    TriggerKind,
+};
+
+const BACKEND: &str = "XDG autostart";
+
+/// Key holding the path of the task an entry was written for. Entries
+/// without it belong to someone else and are left alone.
+const PATH_KEY: &str = "X-Schtask-Path";
+
+/// Starts tasks when the user logs on to their desktop, through desktop
+/// entries in the XDG autostart directory, `<config dir>/autostart`.
+///
+/// A task becomes one entry: its exec action is the entry's `Exec` line,
+/// its working directory the `Path`, and the delay of its logon trigger
+/// the `X-GNOME-Autostart-Delay`. A disabled task is written with
+/// `Hidden=true`, which tells the desktop to skip it. Tasks must have a
+/// single exec action and only logon triggers, and run as the user whose
+/// desktop starts them: principals naming another account, a group or a
+/// non-interactive logon, and logon triggers for a given user or with a
+/// start boundary, fail with [`SchtaskError::Unsupported`] like anything
+/// else entries cannot express.
+///
+/// A task at `\Vendor\Backup` is kept in `Vendor-Backup.desktop`, and
+/// found again by the path its entry records, without regard to case, so
+/// registering `\Vendor\BACKUP` later replaces the same entry. Reading
+/// an entry back gives a task with what the entry records: its name,
+/// description, action, logon delay and whether it is enabled. Desktops
+/// run autostart entries themselves, so [`run`](SchedulerBackend::run)
+/// and [`stop`](SchedulerBackend::stop) fail with
+/// [`SchtaskError::Unsupported`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutostartBackend {
+    dir: PathBuf,
+}
+
+impl AutostartBackend {
+    /// Keeps entries in the `autostart` directory of `config_dir`, such as
+    /// `~/.config`.
+    pub fn new(config_dir: impl AsRef<Path>) -> Self {
+        Self { dir: config_dir.as_ref().join("autostart") }
+    }
+
+    /// The directory the entries are written to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The entry for the task at `path`, and the file it is in, if one was
+    /// written for that task.
+    fn read(&self, path: &str) -> Result<Option<(PathBuf, RegisteredTask)>, SchtaskError> {
+        Ok(self.entries()?.into_iter().find(|(_, task)| same_path(&task.path, path)))
+    }
+
+    fn task(&self, path: &str, context: &str) -> Result<(PathBuf, RegisteredTask), SchtaskError> {
+        self.read(path)?.ok_or_else(|| error(SchtaskError::Task, HResult::ERROR_FILE_NOT_FOUND, context, path))
+    }
+
+    /// Every task with an entry in the directory.
+    fn all(&self) -> Result<Vec<RegisteredTask>, SchtaskError> {
+        let mut tasks: Vec<RegisteredTask> = self.entries()?.into_iter().map(|(_, task)| task).collect();
+        tasks.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(tasks)
+    }
+
+    /// The entries in the directory that were written for tasks, with the
+    /// files they are in. Entries that are not UTF-8 were not written by
+    /// this backend and are skipped like other entries of someone else.
+    fn entries(&self) -> Result<Vec<(PathBuf, RegisteredTask)>, SchtaskError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(file_error(&self.dir, "Cannot read", e)),
+        };
+        let mut tasks = Vec::new();
+        for entry in entries.flatten() {
+            let file = entry.path();
+            if file.extension().is_none_or(|extension| extension != "desktop") {
+                continue;
+            }
+            let contents = match fs::read_to_string(&file) {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => continue,
+                Err(e) => return Err(file_error(&file, "Cannot read", e)),
+            };
+            if let Some(task) = parse_entry(&contents) {
+                tasks.push((file, task));
+            }
+        }
+        Ok(tasks)
+    }
+
+    fn write(&self, file: &Path, contents: &str) -> Result<(), SchtaskError> {
+        fs::create_dir_all(&self.dir).map_err(|e| file_error(&self.dir, "Cannot create", e))?;
+        fs::write(file, contents).map_err(|e| file_error(file, "Cannot write", e))
+    }
+}
+
+impl SchedulerBackend for AutostartBackend {
+    /// Uses `$XDG_CONFIG_HOME/autostart`.
+    fn connect() -> Result<Self, SchtaskError> {
+        Ok(Self::new(config_home()?))
+    }
+
+    fn get_folder(&self, path: &str) -> Result<TaskFolder, SchtaskError> {
+        path_folder(path)
+    }
+
+    fn create_folder(&mut self, path: &str) -> Result<TaskFolder, SchtaskError> {
+        path_folder(path)
+    }
+
+    fn delete_folder(&mut self, path: &str) -> Result<(), SchtaskError> {
+        delete_path_folder(path, || Ok(self.entries()?.into_iter().map(|(_, task)| task.path).collect()))
+    }
+
+    fn register(&mut self, task: &Task, mode: RegisterMode) -> Result<RegisteredTaskInfo, SchtaskError> {
+        task.validate()?;
+        let path = task.path();
+        let contents = render_entry(task)?;
+
+        let existing = self.read(&path)?;
+        let exists = existing.is_some();
+        let file = match existing {
+            Some((file, _)) => file,
+            // Never overwrite an entry that was not written for this task
+            None if self.dir.join(file_name(&path)).exists() => {
+                return Err(error(SchtaskError::Register, HResult::ERROR_ALREADY_EXISTS, "Cannot register the task", &path));
+            }
+            None => self.dir.join(file_name(&path)),
+        };
+        match (mode, exists) {
+            (RegisterMode::Create, true) => {
+                return Err(error(SchtaskError::Register, HResult::ERROR_ALREADY_EXISTS, "Cannot register the task", &path));
+            }
+            (RegisterMode::Update, false) => {
+                return Err(error(SchtaskError::Register, HResult::ERROR_FILE_NOT_FOUND, "Cannot register the task", &path));
+            }
+            _ => {}
+        }
+        self.write(&file, &contents)?;
+
+        Ok(RegisteredTaskInfo { name: task.name.clone(), path })
+    }
+
+    fn get(&self, path: &str) -> Result<RegisteredTask, SchtaskError> {
+        self.task(path, "Cannot open the task").map(|(_, task)| task)
+    }
+
+    fn list(&self, folder: &str) -> Result<Vec<RegisteredTask>, SchtaskError> {
+        let tasks = self.all()?;
+        Ok(tasks.into_iter().filter(|task| same_path(split_task_path(&task.path).0, folder)).collect())
+    }
+
+    fn run(&mut self, _path: &str) -> Result<(), SchtaskError> {
+        Err(unsupported(BACKEND, "running tasks on demand"))
+    }
+
+    fn stop(&mut self, _path: &str) -> Result<(), SchtaskError> {
+        Err(unsupported(BACKEND, "stopping running tasks"))
+    }
+
+    fn set_enabled(&mut self, path: &str, enabled: bool) -> Result<(), SchtaskError> {
+        let (file, registered) = self.task(path, "Cannot enable or disable the task")?;
+        let (folder, _) = split_task_path(&registered.path);
+        let mut task = Task { name: registered.name, folder: folder.to_string(), definition: registered.definition? };
+        task.definition.settings.enabled = enabled;
+        self.write(&file, &render_entry(&task)?)
+    }
+
+    fn delete(&mut self, path: &str) -> Result<(), SchtaskError> {
+        let (file, _) = self.task(path, "Cannot delete the task")?;
+        fs::remove_file(&file).map_err(|e| file_error(&file, "Cannot remove", e))
+    }
+}
+
+/// The name of the entry of the task at `path`: its folders and name
+/// joined with `-`. A `-` in a name is escaped as `\x2d`, as
+/// `systemd-escape` does, so that `\Vendor\Backup` and `\Vendor-Backup`
+/// get different entries; names cannot hold the `\` that starts the
+/// escape.
+fn file_name(path: &str) -> String {
+    let parts: Vec<String> = path.trim_start_matches('\\').split('\\').map(|part| part.replace('-', "\\x2d")).collect();
+    format!("{}.desktop", parts.join("-"))
+}
+
+/// The desktop entry of `task`.
+fn render_entry(task: &Task) -> Result<String, SchtaskError> {
+    let definition = &task.definition;
+    check_principal(&definition.principal)?;
+    let mut logon = None;
+    for trigger in &definition.triggers {
+        let TriggerKind::Logon(logon_trigger) = &trigger.kind else {
+            return Err(unsupported(BACKEND, &format!("{} triggers", trigger.kind.name())));
+        };
+        // The desktop runs the entry for whoever logs on to it
+        if logon_trigger.user_id.is_some() {
+            return Err(unsupported(BACKEND, "logon triggers for a specific user"));
+        }
+        if trigger.start_boundary.is_some() {
+            return Err(unsupported(BACKEND, "start boundaries on logon triggers"));
+        }
+        if trigger.repetition.is_some() || trigger.end_boundary.is_some() || trigger.execution_time_limit.is_some() {
+            return Err(unsupported(BACKEND, "repetition, end boundaries or time limits on logon triggers"));
+        }
+        if logon.replace(trigger).is_some() {
+            return Err(unsupported(BACKEND, "more than one logon trigger"));
+        }
+    }
+    let logon = logon.ok_or_else(|| unsupported(BACKEND, "tasks without a logon trigger"))?;
+    let exec = match definition.actions.as_slice() {
+        [Action { kind: ActionKind::Exec(exec), .. }] => exec,
+        [action] => return Err(unsupported(BACKEND, &format!("{} actions", action.kind.name()))),
+        _ => return Err(unsupported(BACKEND, "more than one action")),
+    };
+
+    let mut args = vec![exec.path.clone()];
+    args.extend(exec.arguments.as_deref().map(split_args).unwrap_or_default());
+    let mut entry = String::from("[Desktop Entry]\nType=Application\n");
+    entry.push_str(&format!("Name={}\n", escape_value(&task.name)));
+    if let Some(description) = &definition.registration_info.description {
+        entry.push_str(&format!("Comment={}\n", escape_value(description)));
+    }
+    entry.push_str(&format!("Exec={}\n", escape_value(&quote_exec(&args))));
+    if let Some(dir) = &exec.working_directory {
+        entry.push_str(&format!("Path={}\n", escape_value(dir)));
+    }
+    if let Some(delay) = logon.delay {
+        entry.push_str(&format!("X-GNOME-Autostart-Delay={}\n", delay.as_duration().as_secs()));
+    }
+    if !definition.settings.enabled || !logon.enabled {
+        entry.push_str("Hidden=true\n");
+    }
+    entry.push_str(&format!("{}={}\n", PATH_KEY, escape_value(&task.path())));
+    Ok(entry)
+}
+
+/// Rejects principals other than the current user, logged on
+/// interactively, which is who the desktop runs entries as.
+fn check_principal(principal: &Principal) -> Result<(), SchtaskError> {
+    let interactive = matches!(
+        principal.effective_logon_type(),
+        LogonType::InteractiveToken | LogonType::InteractiveTokenOrPassword
+    );
+    if !interactive || principal.user_id.is_some() || principal.group_id.is_some() {
+        return Err(unsupported(BACKEND, "principals other than the interactive current user"));
+    }
+    Ok(())
+}
+
+/// Reads back a desktop entry written by [`render_entry`]. Entries
+/// without a task path, or that cannot be read, are not tasks.
+fn parse_entry(contents: &str) -> Option<RegisteredTask> {
+    let value = |key: &str| {
+        contents
+            .lines()
+            .scan(false, |in_group, line| {
+                if line.starts_with('[') {
+                    *in_group = line == "[Desktop Entry]";
+                }
+                Some((*in_group, line))
+            })
+            .filter(|(in_group, _)| *in_group)
+            .find_map(|(_, line)| line.split_once('=').filter(|(name, _)| name.trim_end() == key))
+            .map(|(_, value)| unescape_value(value.trim_start()))
+    };
+
+    let path = value(PATH_KEY)?;
+    let (folder, name) = split_task_path(&path);
+    let args = split_exec(&value("Exec")?)?;
+    let (program, args) = args.split_first()?;
+    let mut action = Action::exec_args(program, args);
+    if let ActionKind::Exec(exec) = &mut action.kind {
+        exec.working_directory = value("Path");
+    }
+
+    let mut trigger = Trigger::logon(None);
+    trigger.delay = value("X-GNOME-Autostart-Delay").and_then(|secs| secs.parse().ok()).map(TaskDuration::from_secs);
+    let enabled = value("Hidden").is_none_or(|hidden| hidden != "true");
+    let mut builder = TaskBuilder::new(name)
+        .folder(folder)
+        .settings(TaskSettings { enabled, ..TaskSettings::default() })
+        .trigger(trigger)
+        .action(action);
+    if let Some(description) = value("Comment") {
+        builder = builder.description(&description);
+    }
+    let definition: TaskDefinition = builder.build().ok()?.definition;
+
+    Some(RegisteredTask {
+        name: name.to_string(),
+        path: path.clone(),
+        state: if enabled { TaskState::Ready } else { TaskState::Disabled },
+        definition: Ok(definition),
+    })
+}
+
+/// Characters that make an `Exec` argument need quotes.
+const RESERVED: &[char] = &[' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(', ')', '`'];
+
+/// Joins a program and its arguments into an `Exec` value, quoting the
+/// way the Desktop Entry Specification asks. A literal `%` is written as
+/// `%%`, since a single one starts a field code.
+fn quote_exec(args: &[String]) -> String {
+    let words: Vec<String> = args
+        .iter()
+        .map(|arg| {
+            let arg = arg.replace('%', "%%");
+            if !arg.is_empty() && !arg.contains(RESERVED) {
+                return arg;
+            }
+            let mut quoted = String::from("\"");
+            for c in arg.chars() {
+                if matches!(c, '"' | '`' | '$' | '\\') {
+                    quoted.push('\\');
+                }
+                quoted.push(c);
+            }
+            quoted.push('"');
+            quoted
+        })
+        .collect();
+    words.join(" ")
+}
+
+/// Splits an `Exec` value written by [`quote_exec`] back into its words.
+fn split_exec(exec: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut chars = exec.chars().peekable();
+    loop {
+        while chars.next_if(|c| *c == ' ').is_some() {}
+        let Some(&first) = chars.peek() else { break };
+        let mut word = String::new();
+        if first == '"' {
+            chars.next();
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => word.push(chars.next()?),
+                    c => word.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| *c != ' ') {
+                word.push(c);
+            }
+        }
+        words.push(word.replace("%%", "%"));
+    }
+    Some(words)
+}
+
+/// Escapes a string value for a desktop entry.
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Undoes [`escape_value`], also reading `\s` as a space.
+fn unescape_value(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::tests::{scratch_path, unsupported_feature};
+    use crate::model::Timestamp;
+
+    fn task(name: &str, delay: Option<TaskDuration>) -> Task {
+        TaskBuilder::new(name)
+            .folder("\\Vendor")
+            .description("Syncs the notes")
+            .trigger(Trigger { delay, ..Trigger::logon(None) })
+            .action(Action::exec_args("/opt/My Notes/sync", &["--profile", "work $HOME", "100%", "C:\\notes"]))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn renders_a_desktop_entry() {
+        let mut task = task("Sync", Some(TaskDuration::from_secs(30)));
+        task.definition.settings.enabled = false;
+
+        assert_eq!(
+            render_entry(&task).unwrap(),
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Sync\n\
+             Comment=Syncs the notes\n\
+             Exec=\"/opt/My Notes/sync\" --profile \"work \\\\$HOME\" 100%% \"C:\\\\\\\\notes\"\n\
+             X-GNOME-Autostart-Delay=30\n\
+             Hidden=true\n\
+             X-Schtask-Path=\\\\Vendor\\\\Sync\n"
+        );
+    }
+
+    #[test]
+    fn reads_back_what_it_writes() {
+        let task = task("Sync", Some(TaskDuration::from_secs(30)));
+        let registered = parse_entry(&render_entry(&task).unwrap()).unwrap();
+
+        assert_eq!(registered.path, "\\Vendor\\Sync");
+        assert_eq!(registered.state, TaskState::Ready);
+        assert_eq!(registered.definition, Ok(task.definition));
+        assert_eq!(split_exec("a \"b \\\" c\" %%d"), Some(vec!["a".to_string(), "b \" c".to_string(), "%d".to_string()]));
+    }
+
+    #[test]
+    fn rejects_tasks_that_do_not_start_at_logon() {
+        let feature = |task: Task| unsupported_feature(render_entry(&task));
+
+        let mut boot = task("Sync", None);
+        boot.definition.triggers.push(Trigger::boot());
+        assert_eq!(feature(boot), "Boot triggers");
+        let mut two_actions = task("Sync", None);
+        two_actions.definition.actions.push(Action::exec("/bin/true", None));
+        assert_eq!(feature(two_actions), "more than one action");
+
+        let mut alice = task("Sync", None);
+        alice.definition.triggers = vec![Trigger::logon(Some("alice"))];
+        assert_eq!(feature(alice), "logon triggers for a specific user");
+        let mut later = task("Sync", None);
+        later.definition.triggers[0].start_boundary = Some(Timestamp::new(2031, 1, 1, 9, 0, 0).unwrap());
+        assert_eq!(feature(later), "start boundaries on logon triggers");
+        for principal in [
+            Principal::system(),
+            Principal::group("BUILTIN\\Users"),
+            Principal::user("CORP\\bob", LogonType::InteractiveToken),
+            Principal { logon_type: Some(LogonType::S4U), ..Principal::default() },
+        ] {
+            let mut other = task("Sync", None);
+            other.definition.principal = principal;
+            assert_eq!(feature(other), "principals other than the interactive current user");
+        }
+    }
+
+    #[test]
+    fn registers_lists_and_deletes_entries() {
+        let config = scratch_path("autostart-register");
+        let mut backend = AutostartBackend::new(&config);
+        let hresult = |result: Result<RegisteredTaskInfo, SchtaskError>| result.unwrap_err().hresult().unwrap();
+
+        assert_eq!(hresult(backend.register(&task("Sync", None), RegisterMode::Update)), HResult::ERROR_FILE_NOT_FOUND);
+        backend.register(&task("Sync", None), RegisterMode::Create).unwrap();
+        backend.register(&task("Backup", Some(TaskDuration::from_mins(2))), RegisterMode::Create).unwrap();
+        assert!(config.join("autostart/Vendor-Sync.desktop").is_file());
+        assert_eq!(hresult(backend.register(&task("Sync", None), RegisterMode::Create)), HResult::ERROR_ALREADY_EXISTS);
+
+        // Entries someone else wrote are neither listed nor replaced
+        fs::write(config.join("autostart/Vendor-Mail.desktop"), "[Desktop Entry]\nType=Application\nExec=mail\n").unwrap();
+        fs::write(config.join("autostart/latin1.desktop"), b"[Desktop Entry]\nName=Caf\xe9\n").unwrap();
+        assert_eq!(
+            hresult(backend.register(&task("Mail", None), RegisterMode::CreateOrUpdate)),
+            HResult::ERROR_ALREADY_EXISTS
+        );
+        let paths: Vec<String> = backend.list("\\vendor").unwrap().into_iter().map(|task| task.path).collect();
+        assert_eq!(paths, ["\\Vendor\\Backup", "\\Vendor\\Sync"]);
+        assert_eq!(backend.list("\\").unwrap(), []);
+
+        backend.set_enabled("\\Vendor\\Sync", false).unwrap();
+        let sync = backend.get("\\Vendor\\Sync").unwrap();
+        assert_eq!(sync.state, TaskState::Disabled);
+        assert_eq!(sync.definition.unwrap().triggers[0].delay, None);
+        let backup = backend.get("\\Vendor\\Backup").unwrap();
+        assert_eq!(backup.definition.unwrap().triggers[0].delay, Some(TaskDuration::from_mins(2)));
+
+        backend.delete("\\Vendor\\Sync").unwrap();
+        assert_eq!(backend.get("\\Vendor\\Sync").unwrap_err().hresult(), Some(HResult::ERROR_FILE_NOT_FOUND));
+        assert_eq!(backend.delete_folder("\\Vendor").unwrap_err().hresult(), Some(HResult::ERROR_DIR_NOT_EMPTY));
+        backend.delete("\\Vendor\\Backup").unwrap();
+        backend.delete_folder("\\Vendor").unwrap();
+        assert!(config.join("autostart/Vendor-Mail.desktop").is_file());
+
+        // A dash in a name does not make it collide with a folder
+        let dashed = TaskBuilder::new("Vendor-Backup").trigger(Trigger::logon(None)).action(Action::exec("/usr/bin/backup", None));
+        backend.register(&task("Backup", None), RegisterMode::Create).unwrap();
+        backend.register(&dashed.build().unwrap(), RegisterMode::Create).unwrap();
+        assert!(config.join("autostart/Vendor\\x2dBackup.desktop").is_file());
+        assert_eq!(backend.get("\\Vendor-Backup").unwrap().path, "\\Vendor-Backup");
+
+        fs::remove_dir_all(&config).unwrap();
+    }
+
+    #[test]
+    fn finds_entries_without_regard_to_case() {
+        let config = scratch_path("autostart-case");
+        let mut backend = AutostartBackend::new(&config);
+
+        backend.register(&task("Sync", None), RegisterMode::Create).unwrap();
+        let upper = TaskBuilder::new("SYNC")
+            .folder("\\vendor")
+            .trigger(Trigger::logon(None))
+            .action(Action::exec("/usr/bin/sync", None))
+            .build()
+            .unwrap();
+        assert_eq!(
+            backend.register(&upper, RegisterMode::Create).unwrap_err().hresult(),
+            Some(HResult::ERROR_ALREADY_EXISTS)
+        );
+        backend.register(&upper, RegisterMode::Update).unwrap();
+
+        let files: Vec<PathBuf> = fs::read_dir(backend.dir()).unwrap().map(|entry| entry.unwrap().path()).collect();
+        assert_eq!(files, [backend.dir().join("Vendor-Sync.desktop")]);
+        assert_eq!(backend.get("\\Vendor\\sync").unwrap().path, "\\vendor\\SYNC");
+        backend.set_enabled("\\VENDOR\\Sync", false).unwrap();
+        backend.delete("\\Vendor\\SYNC").unwrap();
+        assert_eq!(backend.list("\\Vendor").unwrap(), []);
+
+        fs::remove_dir_all(&config).unwrap();
+    }
+}
@@ -0,0 +1,625 @@
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use super::{
+    delete_path_folder,
+    error,
+    file_error,
+    path_folder,
+    same_path,
+    unsupported,
+    RegisterMode,
+    RegisteredTask,
+    SchedulerBackend,
+    TaskFolder,
+};
+use crate::error::SchtaskError;
+use crate::hresult::HResult;
+use crate::model::{
+    split_args,
+    ActionKind,
+    DaysOfWeek,
+    LogonType,
+    Months,
+    Principal,
+    RegisteredTaskInfo,
+    ServiceAccount,
+    Task,
+    TimeZone,
+    Timestamp,
+    Trigger,
+    TriggerKind,
+};
+
+const BACKEND: &str = "cron";
+
+/// Start of the block holding a task's lines, followed by the task path.
+const BEGIN: &str = "# BEGIN schtask ";
+/// End of the block holding a task's lines, followed by the task path.
+const END: &str = "# END schtask ";
+
+/// Prefix that comments out the lines of a disabled task.
+const DISABLED: &str = "# ";
+
+/// Which kind of crontab a [`CrontabBackend`] edits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CrontabFormat {
+    /// A user's crontab, whose commands run as its owner.
+    User,
+    /// `/etc/crontab` or a file in `/etc/cron.d`, whose lines name the
+    /// user each command runs as.
+    System,
+}
+
+/// Keeps tasks in a crontab file, editing it in place.
+///
+/// Each task's lines sit in a block marked with its path:
+///
+/// ```text
+/// # BEGIN schtask \Vendor\Backup
+/// 30 9 * * 1,5 /usr/bin/backup --full
+/// # END schtask \Vendor\Backup
+/// ```
+///
+/// Registering a task replaces its block where it stands, or appends one;
+/// every line outside the blocks is left alone. Disabling a task comments
+/// out its lines.
+///
+/// Daily and weekly triggers that fire every day or week, monthly triggers
+/// on given days, and boot triggers (as `@reboot`) are written as crontab
+/// lines. The hour and minute of a trigger's start boundary set the time
+/// of day; cron has no start dates, so the lines fire from the day they
+/// are written on, and triggers that start after today (in UTC) are
+/// rejected. Repetition without a duration of a daily trigger is written
+/// out as the minutes or hours it fires at, if its interval divides an
+/// hour or a day. Anything else cron has no way to express, such as
+/// repetition durations, repetition of weekly or monthly triggers or
+/// event triggers, fails with [`SchtaskError::Unsupported`].
+///
+/// Lines cannot be read back into task definitions, and running or
+/// stopping tasks is up to cron, so [`get`](SchedulerBackend::get),
+/// [`list`](SchedulerBackend::list), [`run`](SchedulerBackend::run) and
+/// [`stop`](SchedulerBackend::stop) fail with
+/// [`SchtaskError::Unsupported`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrontabBackend {
+    path: PathBuf,
+    format: CrontabFormat,
+}
+
+impl CrontabBackend {
+    /// Edits the crontab at `path`, which is created when the first task is
+    /// registered.
+    pub fn new(path: impl Into<PathBuf>, format: CrontabFormat) -> Self {
+        Self { path: path.into(), format }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The crontab lines of `task`, without the markers of its block.
+    pub fn lines(&self, task: &Task) -> Result<Vec<String>, SchtaskError> {
+        let user = match self.format {
+            CrontabFormat::User => {
+                check_user_principal(&task.definition.principal)?;
+                None
+            }
+            CrontabFormat::System => Some(system_user(&task.definition.principal)?),
+        };
+        let command = command(task)?;
+        let today = Timestamp::now_utc().date();
+
+        let mut lines = Vec::new();
+        for trigger in task.definition.triggers.iter().filter(|trigger| trigger.enabled) {
+            let mut line = schedule(trigger, today)?;
+            if let Some(user) = &user {
+                line.push(' ');
+                line.push_str(user);
+            }
+            line.push(' ');
+            line.push_str(&command);
+            if !task.definition.settings.enabled {
+                line.insert_str(0, DISABLED);
+            }
+            lines.push(line);
+        }
+        Ok(lines)
+    }
+
+    fn read(&self) -> Result<Crontab, SchtaskError> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(Crontab { lines: contents.lines().map(str::to_string).collect() }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Crontab { lines: Vec::new() }),
+            Err(e) => Err(file_error(&self.path, "Cannot read", e)),
+        }
+    }
+
+    /// Replaces the crontab with `crontab`. The lines go to a file next to
+    /// it that is then renamed over it, so that a failed write leaves the
+    /// old crontab whole.
+    fn write(&self, crontab: &Crontab) -> Result<(), SchtaskError> {
+        // cron ignores a last line without a newline
+        let mut contents = crontab.lines.join("\n");
+        contents.push('\n');
+
+        let mut temp_name = self.path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".schtask-new");
+        let temp = self.path.with_file_name(temp_name);
+        let written = fs::write(&temp, contents).and_then(|()| match fs::metadata(&self.path) {
+            Ok(metadata) => fs::set_permissions(&temp, metadata.permissions()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        });
+        if let Err(e) = written {
+            let _ = fs::remove_file(&temp);
+            return Err(file_error(&temp, "Cannot write", e));
+        }
+        fs::rename(&temp, &self.path).map_err(|e| {
+            let _ = fs::remove_file(&temp);
+            file_error(&self.path, "Cannot replace", e)
+        })
+    }
+
+    /// Reads the crontab and finds the block of the task at `path`, which
+    /// must exist.
+    fn task_block(&self, path: &str, context: &str) -> Result<(Crontab, RangeInclusive<usize>), SchtaskError> {
+        let crontab = self.read()?;
+        match crontab.block(path).map_err(|e| file_error(&self.path, "Cannot read", e))? {
+            Some(block) => Ok((crontab, block)),
+            None => Err(error(SchtaskError::Task, HResult::ERROR_FILE_NOT_FOUND, context, path)),
+        }
+    }
+}
+
+impl SchedulerBackend for CrontabBackend {
+    /// Edits the system crontab, `/etc/crontab`.
+    fn connect() -> Result<Self, SchtaskError> {
+        Ok(Self::new("/etc/crontab", CrontabFormat::System))
+    }
+
+    fn get_folder(&self, path: &str) -> Result<TaskFolder, SchtaskError> {
+        path_folder(path)
+    }
+
+    fn create_folder(&mut self, path: &str) -> Result<TaskFolder, SchtaskError> {
+        path_folder(path)
+    }
+
+    fn delete_folder(&mut self, path: &str) -> Result<(), SchtaskError> {
+        delete_path_folder(path, || {
+            Ok(self.read()?.lines.iter().filter_map(|line| line.strip_prefix(BEGIN)).map(str::to_string).collect())
+        })
+    }
+
+    fn register(&mut self, task: &Task, mode: RegisterMode) -> Result<RegisteredTaskInfo, SchtaskError> {
+        task.validate()?;
+        let path = task.path();
+        let mut block = vec![format!("{}{}", BEGIN, path)];
+        block.extend(self.lines(task)?);
+        block.push(format!("{}{}", END, path));
+
+        let mut crontab = self.read()?;
+        let existing = crontab.block(&path).map_err(|e| file_error(&self.path, "Cannot read", e))?;
+        match (mode, existing) {
+            (RegisterMode::Create, Some(_)) => {
+                return Err(error(SchtaskError::Register, HResult::ERROR_ALREADY_EXISTS, "Cannot register the task", &path));
+            }
+            (RegisterMode::Update, None) => {
+                return Err(error(SchtaskError::Register, HResult::ERROR_FILE_NOT_FOUND, "Cannot register the task", &path));
+            }
+            (_, Some(existing)) => {
+                crontab.lines.splice(existing, block);
+            }
+            (_, None) => crontab.lines.extend(block),
+        }
+        self.write(&crontab)?;
+
+        Ok(RegisteredTaskInfo { name: task.name.clone(), path })
+    }
+
+    fn get(&self, _path: &str) -> Result<RegisteredTask, SchtaskError> {
+        Err(unsupported(BACKEND, "reading tasks back from crontab lines"))
+    }
+
+    fn list(&self, _folder: &str) -> Result<Vec<RegisteredTask>, SchtaskError> {
+        Err(unsupported(BACKEND, "reading tasks back from crontab lines"))
+    }
+
+    fn run(&mut self, _path: &str) -> Result<(), SchtaskError> {
+        Err(unsupported(BACKEND, "running tasks on demand"))
+    }
+
+    fn stop(&mut self, _path: &str) -> Result<(), SchtaskError> {
+        Err(unsupported(BACKEND, "stopping running tasks"))
+    }
+
+    fn set_enabled(&mut self, path: &str, enabled: bool) -> Result<(), SchtaskError> {
+        let (mut crontab, block) = self.task_block(path, "Cannot enable or disable the task")?;
+        // The lines between the markers
+        for line in &mut crontab.lines[block.start() + 1..*block.end()] {
+            match (enabled, line.strip_prefix(DISABLED)) {
+                (true, Some(enabled_line)) => *line = enabled_line.to_string(),
+                (false, None) => line.insert_str(0, DISABLED),
+                _ => {}
+            }
+        }
+        self.write(&crontab)
+    }
+
+    fn delete(&mut self, path: &str) -> Result<(), SchtaskError> {
+        let (mut crontab, block) = self.task_block(path, "Cannot delete the task")?;
+        crontab.lines.drain(block);
+        self.write(&crontab)
+    }
+}
+
+/// The lines of a crontab file.
+struct Crontab {
+    lines: Vec<String>,
+}
+
+impl Crontab {
+    /// The lines of the block of the task at `path`, markers included.
+    /// Paths compare without regard to case, as in Task Scheduler.
+    fn block(&self, path: &str) -> io::Result<Option<RangeInclusive<usize>>> {
+        let is_marker = |line: &str, marker: &str| {
+            line.strip_prefix(marker).is_some_and(|marked| same_path(marked, path))
+        };
+        let Some(begin) = self.lines.iter().position(|line| is_marker(line, BEGIN)) else {
+            return Ok(None);
+        };
+        match self.lines[begin..].iter().position(|line| is_marker(line, END)) {
+            Some(length) => Ok(Some(begin..=begin + length)),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("the block of {} has no end marker", path),
+            )),
+        }
+    }
+}
+
+/// Rejects principals that would need the task to run as someone other
+/// than the owner of a user crontab.
+fn check_user_principal(principal: &Principal) -> Result<(), SchtaskError> {
+    match principal.effective_logon_type() {
+        LogonType::InteractiveToken | LogonType::InteractiveTokenOrPassword => Ok(()),
+        LogonType::Group => Err(unsupported(BACKEND, "group principals")),
+        LogonType::Password | LogonType::S4U | LogonType::ServiceAccount => {
+            Err(unsupported(BACKEND, "tasks that run as another account in a user crontab"))
+        }
+    }
+}
+
+/// The user column of a system crontab line.
+fn system_user(principal: &Principal) -> Result<String, SchtaskError> {
+    if principal.effective_logon_type() == LogonType::Group {
+        return Err(unsupported(BACKEND, "group principals"));
+    }
+    let user_id = principal.user_id.as_deref().ok_or_else(|| unsupported(BACKEND, "tasks without a user in a system crontab"))?;
+    Ok(match ServiceAccount::from_user_id(user_id) {
+        Some(ServiceAccount::System) => "root".to_string(),
+        Some(ServiceAccount::LocalService | ServiceAccount::NetworkService) => "nobody".to_string(),
+        None => user_id.rsplit_once('\\').map_or(user_id, |(_, name)| name).to_string(),
+    })
+}
+
+/// The time fields of a trigger's crontab line, or `@reboot`. `today` is
+/// the date the line is written on.
+fn schedule(trigger: &Trigger, today: (u16, u8, u8)) -> Result<String, SchtaskError> {
+    if trigger.start_boundary.is_some_and(|start| start.date() > today) {
+        return Err(unsupported(BACKEND, "start dates after today"));
+    }
+    if trigger.end_boundary.is_some() {
+        return Err(unsupported(BACKEND, "trigger end boundaries"));
+    }
+    if trigger.delay.is_some() || trigger.random_delay.is_some() {
+        return Err(unsupported(BACKEND, "trigger delays"));
+    }
+    if trigger.execution_time_limit.is_some() {
+        return Err(unsupported(BACKEND, "trigger execution time limits"));
+    }
+    if trigger.repetition.as_ref().is_some_and(|repetition| repetition.duration.is_some()) {
+        return Err(unsupported(BACKEND, "repetition durations"));
+    }
+
+    let (days_of_month, months, days_of_week) = match &trigger.kind {
+        TriggerKind::Boot if trigger.repetition.is_some() => return Err(unsupported(BACKEND, "repeating boot triggers")),
+        TriggerKind::Boot => return Ok("@reboot".to_string()),
+        TriggerKind::Daily(daily) if daily.days_interval == 1 => ("*".to_string(), "*".to_string(), "*".to_string()),
+        TriggerKind::Weekly(weekly) if weekly.weeks_interval == 1 => {
+            ("*".to_string(), "*".to_string(), weekdays(weekly.days_of_week))
+        }
+        TriggerKind::Monthly(monthly) if !monthly.run_on_last_day_of_month => {
+            let days: Vec<String> = monthly.days_of_month.days().map(|day| day.to_string()).collect();
+            (days.join(","), months_field(monthly.months), "*".to_string())
+        }
+        TriggerKind::Daily(_) => return Err(unsupported(BACKEND, "daily triggers with an interval of more than one day")),
+        TriggerKind::Weekly(_) => return Err(unsupported(BACKEND, "weekly triggers with an interval of more than one week")),
+        TriggerKind::Monthly(_) => return Err(unsupported(BACKEND, "runs on the last day of the month")),
+        TriggerKind::MonthlyDayOfWeek(_) => return Err(unsupported(BACKEND, "monthly day-of-week triggers")),
+        TriggerKind::Time => return Err(unsupported(BACKEND, "one-time triggers")),
+        TriggerKind::Logon(_) => return Err(unsupported(BACKEND, "logon triggers")),
+        TriggerKind::Idle => return Err(unsupported(BACKEND, "idle triggers")),
+        TriggerKind::Registration => return Err(unsupported(BACKEND, "registration triggers")),
+        TriggerKind::SessionStateChange(_) => return Err(unsupported(BACKEND, "session state change triggers")),
+        TriggerKind::Event(_) => return Err(unsupported(BACKEND, "event triggers")),
+    };
+
+    // Validation makes sure scheduled triggers have a start boundary
+    let start = trigger.start_boundary.expect("scheduled trigger without a start boundary");
+    if start.zone() != TimeZone::Local {
+        return Err(unsupported(BACKEND, "times outside local time"));
+    }
+    let (hour, minute, second) = start.time();
+    if second != 0 {
+        return Err(unsupported(BACKEND, "start times with seconds"));
+    }
+
+    let Some(repetition) = &trigger.repetition else {
+        return Ok(format!("{} {} {} {} {}", minute, hour, days_of_month, months, days_of_week));
+    };
+    // The lines below fire every day, which only a daily trigger does
+    if !matches!(trigger.kind, TriggerKind::Daily(_)) {
+        return Err(unsupported(BACKEND, "repetition of weekly or monthly triggers"));
+    }
+    // Repeating indefinitely keeps the task running every interval, on
+    // every day, from the first start on
+    let interval = repetition.interval.as_duration().as_secs();
+    if interval % 60 == 0 && 3600 % interval == 0 {
+        Ok(format!("{} * * * *", steps(minute, interval / 60, 60)))
+    } else if interval % 3600 == 0 && 86_400 % interval == 0 {
+        Ok(format!("{} {} * * *", minute, steps(hour, interval / 3600, 24)))
+    } else {
+        Err(unsupported(BACKEND, "repetition intervals that do not divide an hour or a day"))
+    }
+}
+
+/// The values of a field from `start` on, every `step`, wrapping at
+/// `limit`, in ascending order: `steps(10, 20, 60)` is `10,30,50`.
+fn steps(start: u8, step: u64, limit: u64) -> String {
+    let first = u64::from(start) % step;
+    let values: Vec<String> = (first..limit).step_by(step as usize).map(|value| value.to_string()).collect();
+    values.join(",")
+}
+
+/// A day-of-week field, where 0 is Sunday.
+fn weekdays(days: DaysOfWeek) -> String {
+    let numbers: Vec<String> = DaysOfWeek::NAMED
+        .iter()
+        .enumerate()
+        .filter(|(_, (day, _))| days.contains(*day))
+        .map(|(index, _)| index.to_string())
+        .collect();
+    numbers.join(",")
+}
+
+/// A month field, e.g. `1,7` or `*`.
+fn months_field(months: Months) -> String {
+    if months == Months::ALL {
+        return "*".to_string();
+    }
+    let numbers: Vec<String> = Months::NAMED
+        .iter()
+        .enumerate()
+        .filter(|(_, (month, _))| months.contains(*month))
+        .map(|(index, _)| (index + 1).to_string())
+        .collect();
+    numbers.join(",")
+}
+
+/// The shell command that runs the task's actions one after another,
+/// stopping at the first that fails.
+fn command(task: &Task) -> Result<String, SchtaskError> {
+    let mut commands = Vec::new();
+    for action in &task.definition.actions {
+        let ActionKind::Exec(exec) = &action.kind else {
+            return Err(unsupported(BACKEND, &format!("{} actions", action.kind.name())));
+        };
+        let mut words = vec![quote_sh(&exec.path, "exec paths")?];
+        for arg in exec.arguments.as_deref().map(split_args).unwrap_or_default() {
+            words.push(quote_sh(&arg, "exec arguments")?);
+        }
+        let mut command = words.join(" ");
+        if let Some(dir) = &exec.working_directory {
+            command = format!("cd {} && {}", quote_sh(dir, "working directories")?, command);
+        }
+        commands.push(command);
+    }
+    // cron turns an unescaped % into a newline
+    Ok(commands.join(" && ").replace('%', "\\%"))
+}
+
+/// Quotes a word for `/bin/sh`, leaving words of safe characters alone.
+/// A line break would end the crontab line even inside quotes, so words
+/// with control characters are rejected; `what` names them in the error.
+fn quote_sh(word: &str, what: &str) -> Result<String, SchtaskError> {
+    if word.contains(|c: char| c.is_control()) {
+        return Err(unsupported(BACKEND, &format!("{} with control characters", what)));
+    }
+    let safe = !word.is_empty()
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '@' | '%' | '+' | '=' | ':' | ',' | '.' | '/' | '-'));
+    Ok(if safe { word.to_string() } else { format!("'{}'", word.replace('\'', "'\\''")) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::tests::{scratch_path, unsupported_feature};
+    use crate::builder::TaskBuilder;
+    use crate::model::{Action, DaysOfMonth, ExecAction, Repetition, TaskDuration, ValidationError};
+
+    fn start() -> Timestamp {
+        Timestamp::new(2024, 6, 1, 9, 30, 0).unwrap()
+    }
+
+    fn task(name: &str, trigger: Trigger) -> Task {
+        TaskBuilder::new(name)
+            .folder("\\Vendor")
+            .trigger(trigger)
+            .action(Action::exec("/usr/bin/backup", Some("--label \"it's 100%\"")))
+            .build()
+            .unwrap()
+    }
+
+    fn user_crontab() -> CrontabBackend {
+        CrontabBackend::new("crontab", CrontabFormat::User)
+    }
+
+    fn schedule_of(trigger: Trigger) -> Result<String, SchtaskError> {
+        let line = user_crontab().lines(&task("Backup", trigger))?.remove(0);
+        Ok(line.trim_end_matches(" /usr/bin/backup --label 'it'\\''s 100\\%'").to_string())
+    }
+
+    #[test]
+    fn renders_calendar_and_boot_triggers() {
+        assert_eq!(schedule_of(Trigger::daily(start(), 1)).unwrap(), "30 9 * * *");
+        assert_eq!(
+            schedule_of(Trigger::weekly(start(), DaysOfWeek::SUNDAY | DaysOfWeek::FRIDAY, 1)).unwrap(),
+            "30 9 * * 0,5"
+        );
+        let days = DaysOfMonth::from_days(&[1, 15]).unwrap();
+        assert_eq!(
+            schedule_of(Trigger::monthly(start(), days, Months::JANUARY | Months::JULY)).unwrap(),
+            "30 9 1,15 1,7 *"
+        );
+        assert_eq!(schedule_of(Trigger::boot()).unwrap(), "@reboot");
+
+        let every = |interval| Trigger {
+            repetition: Some(Repetition::new(interval, None)),
+            ..Trigger::daily(start(), 1)
+        };
+        assert_eq!(schedule_of(every(TaskDuration::from_mins(20))).unwrap(), "10,30,50 * * * *");
+        assert_eq!(schedule_of(every(TaskDuration::from_hours(6))).unwrap(), "30 3,9,15,21 * * *");
+    }
+
+    #[test]
+    fn rejects_what_cron_cannot_express() {
+        let feature = |trigger| unsupported_feature(schedule_of(trigger));
+
+        let bounded = Trigger {
+            repetition: Some(Repetition::new(TaskDuration::from_mins(5), Some(TaskDuration::from_hours(1)))),
+            ..Trigger::daily(start(), 1)
+        };
+        assert_eq!(feature(bounded), "repetition durations");
+        assert_eq!(feature(Trigger::event("<QueryList/>")), "event triggers");
+        assert_eq!(feature(Trigger::logon(None)), "logon triggers");
+        assert_eq!(feature(Trigger::daily(start(), 3)), "daily triggers with an interval of more than one day");
+        let odd = Trigger {
+            repetition: Some(Repetition::new(TaskDuration::from_mins(7), None)),
+            ..Trigger::daily(start(), 1)
+        };
+        assert_eq!(feature(odd), "repetition intervals that do not divide an hour or a day");
+        let weekly = Trigger {
+            repetition: Some(Repetition::new(TaskDuration::from_mins(20), None)),
+            ..Trigger::weekly(start(), DaysOfWeek::MONDAY, 1)
+        };
+        assert_eq!(feature(weekly), "repetition of weekly or monthly triggers");
+        let later = Timestamp::new(9999, 1, 1, 9, 30, 0).unwrap();
+        assert_eq!(feature(Trigger::daily(later, 1)), "start dates after today");
+        assert_eq!(feature(Trigger { start_boundary: Some(later), ..Trigger::boot() }), "start dates after today");
+
+        let mut system = task("Backup", Trigger::boot());
+        system.definition.principal = Principal::system();
+        assert!(matches!(user_crontab().lines(&system), Err(SchtaskError::Unsupported(_))));
+        let system_crontab = CrontabBackend::new("crontab", CrontabFormat::System);
+        assert_eq!(
+            system_crontab.lines(&system).unwrap(),
+            ["@reboot root /usr/bin/backup --label 'it'\\''s 100\\%'"]
+        );
+    }
+
+    #[test]
+    fn edits_managed_blocks_in_place() {
+        let path = scratch_path("cron-blocks");
+        fs::write(&path, "MAILTO=ops@example.com\n0 * * * * /usr/bin/hourly\n").unwrap();
+        let mut crontab = CrontabBackend::new(&path, CrontabFormat::User);
+        let read = || fs::read_to_string(&path).unwrap();
+
+        crontab.register(&task("Backup", Trigger::daily(start(), 1)), RegisterMode::Create).unwrap();
+        crontab.register(&task("Report", Trigger::boot()), RegisterMode::Create).unwrap();
+        fs::write(&path, read() + "# a line of the user's own\n").unwrap();
+        let err = crontab.register(&task("backup", Trigger::boot()), RegisterMode::Create).unwrap_err();
+        assert_eq!(err.hresult(), Some(HResult::ERROR_ALREADY_EXISTS));
+
+        // Updating keeps the block where it is
+        let weekly = Trigger::weekly(start(), DaysOfWeek::MONDAY, 1);
+        crontab.register(&task("Backup", weekly), RegisterMode::Update).unwrap();
+        crontab.set_enabled("\\Vendor\\Report", false).unwrap();
+        assert_eq!(
+            read(),
+            "MAILTO=ops@example.com\n\
+             0 * * * * /usr/bin/hourly\n\
+             # BEGIN schtask \\Vendor\\Backup\n\
+             30 9 * * 1 /usr/bin/backup --label 'it'\\''s 100\\%'\n\
+             # END schtask \\Vendor\\Backup\n\
+             # BEGIN schtask \\Vendor\\Report\n\
+             # @reboot /usr/bin/backup --label 'it'\\''s 100\\%'\n\
+             # END schtask \\Vendor\\Report\n\
+             # a line of the user's own\n"
+        );
+        assert_eq!(crontab.delete_folder("\\Vendor").unwrap_err().hresult(), Some(HResult::ERROR_DIR_NOT_EMPTY));
+
+        crontab.set_enabled("\\Vendor\\Report", true).unwrap();
+        assert!(read().contains("\n@reboot /usr/bin/backup"));
+        crontab.delete("\\Vendor\\Backup").unwrap();
+        crontab.delete("\\Vendor\\Report").unwrap();
+        assert_eq!(read(), "MAILTO=ops@example.com\n0 * * * * /usr/bin/hourly\n# a line of the user's own\n");
+        assert_eq!(crontab.delete("\\Vendor\\Report").unwrap_err().hresult(), Some(HResult::ERROR_FILE_NOT_FOUND));
+        crontab.delete_folder("\\Vendor").unwrap();
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn replaces_the_crontab_keeping_its_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = scratch_path("cron-replace");
+        fs::write(&path, "MAILTO=ops@example.com\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        let mut crontab = CrontabBackend::new(&path, CrontabFormat::User);
+
+        crontab.register(&task("Backup", Trigger::boot()), RegisterMode::Create).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert!(fs::read_to_string(&path).unwrap().starts_with("MAILTO=ops@example.com\n# BEGIN schtask"));
+        let mut temp = path.clone().into_os_string();
+        temp.push(".schtask-new");
+        assert!(!PathBuf::from(temp).exists());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_names_that_would_break_the_markers() {
+        let path = scratch_path("cron-names");
+        let mut crontab = CrontabBackend::new(&path, CrontabFormat::User);
+        let forged = Task { name: "Backup\n* * * * * /bin/evil".to_string(), ..task("Backup", Trigger::boot()) };
+
+        let err = crontab.register(&forged, RegisterMode::Create).unwrap_err();
+        assert_eq!(err, SchtaskError::Invalid(ValidationError::InvalidTaskName(forged.name.clone())));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn rejects_commands_that_would_break_the_line() {
+        let path = scratch_path("cron-commands");
+        let mut crontab = CrontabBackend::new(&path, CrontabFormat::User);
+        let mut register = |action: Action| {
+            let mut task = task("Backup", Trigger::boot());
+            task.definition.actions = vec![action];
+            unsupported_feature(crontab.register(&task, RegisterMode::Create))
+        };
+
+        // Quotes do not stop a line break from starting a new crontab line
+        let forged = Action::exec("/bin/true", Some("\"a\n* * * * * /bin/evil #\""));
+        assert_eq!(register(forged), "exec arguments with control characters");
+        assert_eq!(register(Action::exec("/bin/true\r", None)), "exec paths with control characters");
+        let exec = ExecAction { path: "/bin/true".to_string(), arguments: None, working_directory: Some("/tmp\n@reboot /bin/evil".to_string()) };
+        assert_eq!(register(Action::new(ActionKind::Exec(exec))), "working directories with control characters");
+        assert!(!path.exists());
+    }
+}
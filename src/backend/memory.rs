@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+
+use super::{error, fold_case, is_inside, join_path, split_task_path, task_folder, RegisterMode, RegisteredTask, SchedulerBackend, TaskFolder, TaskState};
+use crate::error::SchtaskError;
+use crate::hresult::HResult;
+use crate::model::{validate_folder, RegisteredTaskInfo, Task, TaskDefinition};
+
+/// A scheduler that keeps its folders and tasks in memory.
+///
+/// It follows Task Scheduler's rules for what it is asked to do: names are
+/// case-insensitive, a task and a folder cannot share a name, registering
+/// honors [`RegisterMode`], and disabled tasks or tasks that do not allow
+/// it cannot be run on demand. Nothing is ever actually run; a started
+/// task stays [`TaskState::Running`] until it is stopped.
+#[derive(Debug, Clone)]
+pub struct MemoryBackend {
+    /// Folders by path with its case folded. The root folder always exists.
+    folders: BTreeMap<String, MemoryFolder>,
+}
+
+#[derive(Debug, Clone)]
+struct MemoryFolder {
+    path: String,
+    /// Tasks by name with its case folded.
+    tasks: BTreeMap<String, MemoryTask>,
+}
+
+/// A registered task, whose definition always parses.
+#[derive(Debug, Clone)]
+struct MemoryTask {
+    name: String,
+    path: String,
+    state: TaskState,
+    definition: TaskDefinition,
+}
+
+impl MemoryTask {
+    fn registered(&self) -> RegisteredTask {
+        RegisteredTask {
+            name: self.name.clone(),
+            path: self.path.clone(),
+            state: self.state,
+            definition: Ok(self.definition.clone()),
+        }
+    }
+}
+
+impl MemoryBackend {
+    /// Creates a scheduler holding only the empty root folder.
+    pub fn new() -> Self {
+        let root = MemoryFolder { path: "\\".to_string(), tasks: BTreeMap::new() };
+        Self { folders: BTreeMap::from([("\\".to_string(), root)]) }
+    }
+
+    fn folder(&self, path: &str) -> Result<&MemoryFolder, SchtaskError> {
+        self.folders
+            .get(&fold_case(path))
+            .ok_or_else(|| error(SchtaskError::Folder, HResult::ERROR_FILE_NOT_FOUND, "Cannot open the folder", path))
+    }
+
+    fn folder_mut(&mut self, path: &str) -> Result<&mut MemoryFolder, SchtaskError> {
+        self.folders
+            .get_mut(&fold_case(path))
+            .ok_or_else(|| error(SchtaskError::Folder, HResult::ERROR_FILE_NOT_FOUND, "Cannot open the folder", path))
+    }
+
+    fn task_mut(&mut self, path: &str) -> Result<&mut MemoryTask, SchtaskError> {
+        let (folder, name) = split_task_path(path);
+        self.folder_mut(folder)?
+            .tasks
+            .get_mut(&fold_case(name))
+            .ok_or_else(|| error(SchtaskError::Task, HResult::ERROR_FILE_NOT_FOUND, "Cannot open the task", path))
+    }
+
+    /// Whether `path` names a subfolder, which a task may not share a name
+    /// with.
+    fn is_folder(&self, path: &str) -> bool {
+        self.folders.contains_key(&fold_case(path))
+    }
+
+    fn has_task(&self, path: &str) -> bool {
+        let (folder, name) = split_task_path(path);
+        self.folder(folder).is_ok_and(|folder| folder.tasks.contains_key(&fold_case(name)))
+    }
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SchedulerBackend for MemoryBackend {
+    fn connect() -> Result<Self, SchtaskError> {
+        Ok(Self::new())
+    }
+
+    fn get_folder(&self, path: &str) -> Result<TaskFolder, SchtaskError> {
+        let folder = self.folder(path)?;
+        Ok(task_folder(&folder.path))
+    }
+
+    fn create_folder(&mut self, path: &str) -> Result<TaskFolder, SchtaskError> {
+        validate_folder(path)?;
+        if path == "\\" || self.is_folder(path) || self.has_task(path) {
+            return Err(error(SchtaskError::Folder, HResult::ERROR_ALREADY_EXISTS, "Cannot create the folder", path));
+        }
+
+        let mut current = String::new();
+        for part in path[1..].split('\\') {
+            current.push('\\');
+            current.push_str(part);
+            if self.has_task(&current) {
+                return Err(error(SchtaskError::Folder, HResult::ERROR_ALREADY_EXISTS, "Cannot create the folder", &current));
+            }
+            self.folders
+                .entry(fold_case(&current))
+                .or_insert_with(|| MemoryFolder { path: current.clone(), tasks: BTreeMap::new() });
+        }
+        Ok(task_folder(path))
+    }
+
+    fn delete_folder(&mut self, path: &str) -> Result<(), SchtaskError> {
+        if path == "\\" {
+            return Err(error(SchtaskError::Folder, HResult::E_INVALIDARG, "Cannot delete the root folder", path));
+        }
+        let key = fold_case(path);
+        let folder = self.folder(path)?;
+        if !folder.tasks.is_empty() || self.folders.keys().any(|other| is_inside(other, path)) {
+            return Err(error(SchtaskError::Folder, HResult::ERROR_DIR_NOT_EMPTY, "Cannot delete the folder", path));
+        }
+        self.folders.remove(&key);
+        Ok(())
+    }
+
+    fn register(&mut self, task: &Task, mode: RegisterMode) -> Result<RegisteredTaskInfo, SchtaskError> {
+        task.validate()?;
+        let path = task.path();
+        if self.is_folder(&path) {
+            return Err(error(SchtaskError::Register, HResult::ERROR_ALREADY_EXISTS, "Cannot register the task", &path));
+        }
+
+        let folder = self.folder_mut(&task.folder)?;
+        let key = fold_case(&task.name);
+        let existing = folder.tasks.get(&key);
+        match (mode, existing) {
+            (RegisterMode::Create, Some(_)) => {
+                return Err(error(SchtaskError::Register, HResult::ERROR_ALREADY_EXISTS, "Cannot register the task", &path));
+            }
+            (RegisterMode::Update, None) => {
+                return Err(error(SchtaskError::Register, HResult::ERROR_FILE_NOT_FOUND, "Cannot register the task", &path));
+            }
+            _ => {}
+        }
+
+        // An updated task keeps the name it was first registered under
+        let name = existing.map_or_else(|| task.name.clone(), |existing| existing.name.clone());
+        let path = join_path(&folder.path, &name);
+        let state = if task.definition.settings.enabled { TaskState::Ready } else { TaskState::Disabled };
+        folder.tasks.insert(
+            key,
+            MemoryTask { name: name.clone(), path: path.clone(), state, definition: task.definition.clone() },
+        );
+        Ok(RegisteredTaskInfo { name, path })
+    }
+
+    fn get(&self, path: &str) -> Result<RegisteredTask, SchtaskError> {
+        let (folder, name) = split_task_path(path);
+        self.folder(folder)?
+            .tasks
+            .get(&fold_case(name))
+            .map(MemoryTask::registered)
+            .ok_or_else(|| error(SchtaskError::Task, HResult::ERROR_FILE_NOT_FOUND, "Cannot open the task", path))
+    }
+
+    fn list(&self, folder: &str) -> Result<Vec<RegisteredTask>, SchtaskError> {
+        Ok(self.folder(folder)?.tasks.values().map(MemoryTask::registered).collect())
+    }
+
+    fn run(&mut self, path: &str) -> Result<(), SchtaskError> {
+        let task = self.task_mut(path)?;
+        let settings = &task.definition.settings;
+        if !settings.enabled {
+            return Err(error(SchtaskError::Task, HResult::SCHED_E_TASK_DISABLED, "Cannot run the task", path));
+        }
+        if !settings.allow_demand_start {
+            return Err(error(SchtaskError::Task, HResult::SCHED_E_START_ON_DEMAND, "Cannot run the task", path));
+        }
+        task.state = TaskState::Running;
+        Ok(())
+    }
+
+    fn stop(&mut self, path: &str) -> Result<(), SchtaskError> {
+        let task = self.task_mut(path)?;
+        if task.state == TaskState::Running {
+            task.state = if task.definition.settings.enabled { TaskState::Ready } else { TaskState::Disabled };
+        }
+        Ok(())
+    }
+
+    fn set_enabled(&mut self, path: &str, enabled: bool) -> Result<(), SchtaskError> {
+        let task = self.task_mut(path)?;
+        task.definition.settings.enabled = enabled;
+        if task.state != TaskState::Running {
+            task.state = if enabled { TaskState::Ready } else { TaskState::Disabled };
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, path: &str) -> Result<(), SchtaskError> {
+        let (folder, name) = split_task_path(path);
+        self.folder_mut(folder)?
+            .tasks
+            .remove(&fold_case(name))
+            .map(|_| ())
+            .ok_or_else(|| error(SchtaskError::Task, HResult::ERROR_FILE_NOT_FOUND, "Cannot delete the task", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::TaskBuilder;
+    use crate::model::{Action, TaskSettings};
+
+    fn task(folder: &str, name: &str) -> Task {
+        TaskBuilder::new(name).folder(folder).action(Action::exec("C:\\app.exe", None)).build().unwrap()
+    }
+
+    fn hresult(result: Result<impl std::fmt::Debug, SchtaskError>) -> HResult {
+        result.unwrap_err().hresult().expect("a scheduler error")
+    }
+
+    #[test]
+    fn registers_according_to_the_mode() {
+        let mut backend = MemoryBackend::new();
+
+        assert_eq!(hresult(backend.register(&task("\\", "Backup"), RegisterMode::Update)), HResult::ERROR_FILE_NOT_FOUND);
+        let info = backend.register(&task("\\", "Backup"), RegisterMode::Create).unwrap();
+        assert_eq!(info, RegisteredTaskInfo { name: "Backup".to_string(), path: "\\Backup".to_string() });
+        assert_eq!(hresult(backend.register(&task("\\", "BACKUP"), RegisterMode::Create)), HResult::ERROR_ALREADY_EXISTS);
+
+        let mut update = task("\\", "backup");
+        update.definition.settings.priority = 4;
+        assert_eq!(backend.register(&update, RegisterMode::Update).unwrap().path, "\\Backup");
+        assert_eq!(backend.get("\\BACKUP").unwrap().definition.unwrap().settings.priority, 4);
+        backend.register(&task("\\", "Other"), RegisterMode::CreateOrUpdate).unwrap();
+        backend.register(&task("\\", "Other"), RegisterMode::CreateOrUpdate).unwrap();
+
+        let names: Vec<String> = backend.list("\\").unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(names, ["Backup", "Other"]);
+    }
+
+    #[test]
+    fn manages_folders() {
+        let mut backend = MemoryBackend::new();
+
+        assert_eq!(hresult(backend.register(&task("\\Vendor", "Backup"), RegisterMode::Create)), HResult::ERROR_FILE_NOT_FOUND);
+        assert_eq!(backend.create_folder("\\Vendor\\App").unwrap(), TaskFolder { name: "App".to_string(), path: "\\Vendor\\App".to_string() });
+        assert_eq!(backend.get_folder("\\vendor").unwrap().path, "\\Vendor");
+        assert_eq!(hresult(backend.create_folder("\\Vendor")), HResult::ERROR_ALREADY_EXISTS);
+
+        backend.register(&task("\\Vendor", "Backup"), RegisterMode::Create).unwrap();
+        assert_eq!(hresult(backend.register(&task("\\", "Vendor"), RegisterMode::Create)), HResult::ERROR_ALREADY_EXISTS);
+        assert_eq!(hresult(backend.create_folder("\\Vendor\\Backup")), HResult::ERROR_ALREADY_EXISTS);
+
+        assert_eq!(hresult(backend.delete_folder("\\Vendor")), HResult::ERROR_DIR_NOT_EMPTY);
+        backend.delete_folder("\\Vendor\\App").unwrap();
+        backend.delete("\\Vendor\\Backup").unwrap();
+        backend.delete_folder("\\Vendor").unwrap();
+        assert_eq!(hresult(backend.get_folder("\\Vendor")), HResult::ERROR_FILE_NOT_FOUND);
+        assert_eq!(hresult(backend.delete_folder("\\")), HResult::E_INVALIDARG);
+    }
+
+    #[test]
+    fn runs_stops_and_disables_tasks() {
+        let mut backend = MemoryBackend::new();
+        backend.register(&task("\\", "Backup"), RegisterMode::Create).unwrap();
+        let state = |backend: &MemoryBackend| backend.get("\\Backup").unwrap().state;
+
+        backend.run("\\Backup").unwrap();
+        assert_eq!(state(&backend), TaskState::Running);
+        backend.stop("\\Backup").unwrap();
+        assert_eq!(state(&backend), TaskState::Ready);
+
+        backend.set_enabled("\\Backup", false).unwrap();
+        assert_eq!(state(&backend), TaskState::Disabled);
+        assert!(!backend.get("\\Backup").unwrap().definition.unwrap().settings.enabled);
+        assert_eq!(hresult(backend.run("\\Backup")), HResult::SCHED_E_TASK_DISABLED);
+        backend.set_enabled("\\Backup", true).unwrap();
+
+        let mut manual_only = task("\\", "NoDemand");
+        manual_only.definition.settings = TaskSettings { allow_demand_start: false, ..TaskSettings::default() };
+        backend.register(&manual_only, RegisterMode::Create).unwrap();
+        assert_eq!(hresult(backend.run("\\NoDemand")), HResult::SCHED_E_START_ON_DEMAND);
+
+        backend.delete("\\Backup").unwrap();
+        assert_eq!(hresult(backend.get("\\Backup")), HResult::ERROR_FILE_NOT_FOUND);
+        assert_eq!(hresult(backend.delete("\\Backup")), HResult::ERROR_FILE_NOT_FOUND);
+    }
+}
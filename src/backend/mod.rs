@@ -0,0 +1,298 @@
+//! Scheduler backends: the services a task definition is registered with.
+//!
+//! [`SchedulerBackend`] is the interface every backend implements. On
+//! Windows with the `windows-backend` feature, `ComBackend` talks to Task
+//! Scheduler through `ITaskService`;
+//! [`MemoryBackend`] keeps tasks in memory and follows the same rules, so
+//! code that manages tasks can be tested on any platform. On Unix,
+//! [`SystemdBackend`] writes tasks out as systemd timer and service units;
+//! [`CrontabBackend`] keeps them in a crontab, for hosts without systemd;
+//! and [`AutostartBackend`] turns logon tasks into XDG autostart entries.
+//!
+//! The crontab, systemd and autostart backends have no folders of their
+//! own: a folder only exists as part of the paths of the tasks in it, so
+//! any folder can be opened, and one cannot be deleted while tasks remain
+//! in it.
+
+#[cfg(unix)]
+mod autostart;
+mod cron;
+mod memory;
+#[cfg(unix)]
+mod systemd;
+
+#[cfg(unix)]
+pub use autostart::AutostartBackend;
+pub use cron::{CrontabBackend, CrontabFormat};
+pub use memory::MemoryBackend;
+#[cfg(unix)]
+pub use systemd::{SystemdBackend, SystemdUnits, UnitScope};
+#[cfg(all(windows, feature = "windows-backend"))]
+pub use crate::com::ComBackend;
+
+#[cfg(unix)]
+use std::env;
+use std::io;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::path::Path;
+
+use crate::error::{ComError, FileError, SchtaskError, Unsupported};
+use crate::hresult::HResult;
+use crate::model::{validate_folder, RegisteredTaskInfo, Task, TaskDefinition};
+use crate::xml::XmlError;
+
+/// What registering a task does when one of the same name exists
+/// (`TASK_CREATION`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RegisterMode {
+    /// Fail if the task exists (`TASK_CREATE`).
+    Create,
+    /// Fail unless the task exists, and replace it (`TASK_UPDATE`).
+    Update,
+    /// Replace the task if it exists (`TASK_CREATE_OR_UPDATE`).
+    #[default]
+    CreateOrUpdate,
+}
+
+/// Where a registered task stands (`TASK_STATE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskState {
+    /// The scheduler did not report a state, or reported one this crate
+    /// does not know.
+    Unknown,
+    /// The task is registered but disabled.
+    Disabled,
+    /// An instance of the task is waiting to run.
+    Queued,
+    /// The task is enabled and not running.
+    Ready,
+    /// An instance of the task is running.
+    Running,
+}
+
+/// A task folder, such as `\` or `\Vendor\App`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskFolder {
+    /// Name of the folder within its parent; `\` for the root folder.
+    pub name: String,
+    /// Full path of the folder.
+    pub path: String,
+}
+
+/// A task as the scheduler holds it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisteredTask {
+    /// Name of the task within its folder.
+    pub name: String,
+    /// Full path of the task, e.g. `\Vendor\Backup`.
+    pub path: String,
+    /// Whether the task is disabled, ready, queued or running, as of the
+    /// lookup.
+    pub state: TaskState,
+    /// The definition the task was registered with. Enabling or disabling
+    /// the task changes its `settings.enabled`.
+    ///
+    /// Tasks registered by other programs may use Task XML this crate
+    /// cannot read; their definition is the error, and the task is still
+    /// listed.
+    pub definition: Result<TaskDefinition, XmlError>,
+}
+
+impl RegisteredTask {
+    /// A task whose definition is read from its Task XML, keeping the
+    /// error if `xml` does not parse.
+    #[cfg(any(test, all(windows, feature = "windows-backend")))]
+    pub(crate) fn from_xml(name: String, path: String, state: TaskState, xml: &str) -> Self {
+        RegisteredTask { name, path, state, definition: TaskDefinition::from_xml(xml) }
+    }
+}
+
+/// A service tasks are registered with.
+///
+/// Paths are absolute and use backslashes: folders are written like `\` or
+/// `\Vendor\App` and tasks like `\Vendor\App\Backup`. Names compare
+/// without regard to case, as in Task Scheduler. Failures carry the
+/// HRESULT Task Scheduler returns for the same mistake, e.g.
+/// `ERROR_FILE_NOT_FOUND` for a task that does not exist.
+pub trait SchedulerBackend {
+    /// Connects to the scheduler on the local computer.
+    fn connect() -> Result<Self, SchtaskError>
+    where
+        Self: Sized;
+
+    /// Opens the folder at `path`.
+    fn get_folder(&self, path: &str) -> Result<TaskFolder, SchtaskError>;
+
+    /// Creates the folder at `path`, along with any missing parents.
+    fn create_folder(&mut self, path: &str) -> Result<TaskFolder, SchtaskError>;
+
+    /// Deletes the folder at `path`, which must be empty.
+    fn delete_folder(&mut self, path: &str) -> Result<(), SchtaskError>;
+
+    /// Registers `task` in its folder, which must exist.
+    fn register(&mut self, task: &Task, mode: RegisterMode) -> Result<RegisteredTaskInfo, SchtaskError>;
+
+    /// Looks up the task at `path`.
+    fn get(&self, path: &str) -> Result<RegisteredTask, SchtaskError>;
+
+    /// Lists the tasks directly in the folder at `folder`, hidden ones
+    /// included.
+    fn list(&self, folder: &str) -> Result<Vec<RegisteredTask>, SchtaskError>;
+
+    /// Starts the task at `path` now, as if it had been triggered.
+    fn run(&mut self, path: &str) -> Result<(), SchtaskError>;
+
+    /// Stops every running instance of the task at `path`.
+    fn stop(&mut self, path: &str) -> Result<(), SchtaskError>;
+
+    /// Enables or disables the task at `path`.
+    fn set_enabled(&mut self, path: &str, enabled: bool) -> Result<(), SchtaskError>;
+
+    /// Deletes the task at `path`.
+    fn delete(&mut self, path: &str) -> Result<(), SchtaskError>;
+}
+
+/// Splits a task path into its folder and name, e.g. `\Vendor\Backup` into
+/// `\Vendor` and `Backup`.
+pub(crate) fn split_task_path(path: &str) -> (&str, &str) {
+    match path.rfind('\\') {
+        Some(0) => ("\\", &path[1..]),
+        Some(index) => (&path[..index], &path[index + 1..]),
+        None => ("\\", path),
+    }
+}
+
+/// The folder at `path`, named after its last component.
+pub(crate) fn task_folder(path: &str) -> TaskFolder {
+    let (_, name) = split_task_path(path);
+    let name = if name.is_empty() { "\\" } else { name };
+    TaskFolder { name: name.to_string(), path: path.to_string() }
+}
+
+pub(crate) fn join_path(folder: &str, name: &str) -> String {
+    if folder == "\\" { format!("\\{}", name) } else { format!("{}\\{}", folder, name) }
+}
+
+/// `path` with its case folded, as names are compared: without regard to
+/// case, as in Task Scheduler, across all of Unicode rather than ASCII
+/// only, so that `\Ärger` and `\ärger` name the same task.
+pub(crate) fn fold_case(path: &str) -> String {
+    path.to_lowercase()
+}
+
+/// Whether `a` and `b` are the same task or folder path.
+pub(crate) fn same_path(a: &str, b: &str) -> bool {
+    fold_case(a) == fold_case(b)
+}
+
+/// Whether `path` lies inside `folder`, at any depth.
+pub(crate) fn is_inside(path: &str, folder: &str) -> bool {
+    fold_case(path).starts_with(&fold_case(&join_path(folder, "")))
+}
+
+/// Builds the error Task Scheduler would report for `path`.
+pub(crate) fn error(stage: fn(ComError) -> SchtaskError, hr: HResult, context: &str, path: &str) -> SchtaskError {
+    stage(ComError::new(hr, &format!("{} {}", context, path), &hr.describe()))
+}
+
+/// Builds the error for a file operation on `path` that failed.
+pub(crate) fn file_error(path: &Path, context: &str, error: io::Error) -> SchtaskError {
+    SchtaskError::File(FileError::new(path, context, error))
+}
+
+/// Builds the error for a `feature` that `backend` has no way to express.
+pub(crate) fn unsupported(backend: &'static str, feature: &str) -> SchtaskError {
+    Unsupported::new(backend, feature).into()
+}
+
+/// Opens the folder at `path` in a backend without folders of its own,
+/// where every valid folder path names one.
+pub(crate) fn path_folder(path: &str) -> Result<TaskFolder, SchtaskError> {
+    validate_folder(path)?;
+    Ok(task_folder(path))
+}
+
+/// Deletes the folder at `path` in a backend without folders of its own,
+/// which fails for the root folder and while any of the task paths
+/// `task_paths` returns lies inside it.
+pub(crate) fn delete_path_folder(
+    path: &str,
+    task_paths: impl FnOnce() -> Result<Vec<String>, SchtaskError>,
+) -> Result<(), SchtaskError> {
+    if path == "\\" {
+        return Err(error(SchtaskError::Folder, HResult::E_INVALIDARG, "Cannot delete the root folder", path));
+    }
+    if task_paths()?.iter().any(|task| is_inside(task, path)) {
+        return Err(error(SchtaskError::Folder, HResult::ERROR_DIR_NOT_EMPTY, "Cannot delete the folder", path));
+    }
+    Ok(())
+}
+
+/// The user's configuration directory: `$XDG_CONFIG_HOME` if it is set to
+/// an absolute path, else `$HOME/.config`.
+#[cfg(unix)]
+pub(crate) fn config_home() -> Result<PathBuf, SchtaskError> {
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+        && dir.is_absolute()
+    {
+        return Ok(dir);
+    }
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")).ok_or_else(|| {
+        let hr = HResult::ERROR_PATH_NOT_FOUND;
+        SchtaskError::Connect(ComError::new(hr, "Cannot find the configuration directory; HOME is not set", &hr.describe()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::builder::TaskBuilder;
+    use crate::model::Action;
+    use crate::xml::SchemaVersion;
+
+    /// A path in the temporary directory for one test to write to, with
+    /// nothing there yet.
+    pub(super) fn scratch_path(test: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("schtask-{}-{}", test, std::process::id()));
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_dir_all(&path);
+        path
+    }
+
+    /// The feature named by the [`SchtaskError::Unsupported`] `result`
+    /// fails with.
+    pub(super) fn unsupported_feature<T: Debug>(result: Result<T, SchtaskError>) -> String {
+        match result {
+            Err(SchtaskError::Unsupported(e)) => e.feature,
+            other => panic!("expected an unsupported feature, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn compares_paths_without_regard_to_case() {
+        assert!(same_path("\\Vendor\\Ärger", "\\VENDOR\\ärger"));
+        assert!(!same_path("\\Vendor\\Ärger", "\\Vendor\\Arger"));
+        assert!(is_inside("\\VENDOR\\Ärger\\Backup", "\\Vendor\\ärger"));
+        assert!(is_inside("\\Backup", "\\"));
+        assert!(!is_inside("\\Vendor-Backup", "\\Vendor"));
+        assert!(!is_inside("\\Vendor", "\\Vendor"));
+    }
+
+    #[test]
+    fn keeps_definitions_that_do_not_parse_as_errors() {
+        let task = TaskBuilder::new("Backup").action(Action::exec("backup.exe", None)).build().unwrap();
+        let xml = task.definition.to_xml(SchemaVersion::V1_2);
+        let good = RegisteredTask::from_xml("Backup".to_string(), "\\Backup".to_string(), TaskState::Ready, &xml);
+        assert_eq!(good.definition, Ok(task.definition));
+
+        let xml = xml.replace("<Enabled>true</Enabled>", "<Enabled>maybe</Enabled>");
+        let bad = RegisteredTask::from_xml("Backup".to_string(), "\\Backup".to_string(), TaskState::Ready, &xml);
+        assert_eq!((bad.name.as_str(), bad.path.as_str(), bad.state), ("Backup", "\\Backup", TaskState::Ready));
+        assert!(bad.definition.is_err());
+    }
+}
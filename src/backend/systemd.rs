@@ -0,0 +1,861 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{
+    config_home,
+    delete_path_folder,
+    error,
+    file_error,
+    path_folder,
+    same_path,
+    unsupported,
+    RegisterMode,
+    RegisteredTask,
+    SchedulerBackend,
+    TaskFolder,
+};
+use crate::error::SchtaskError;
+use crate::hresult::HResult;
+use crate::model::{
+    split_args,
+    ActionKind,
+    DaysOfWeek,
+    LogonType,
+    Months,
+    Principal,
+    RegisteredTaskInfo,
+    ServiceAccount,
+    Task,
+    TaskDuration,
+    TimeZone,
+    Timestamp,
+    Trigger,
+    TriggerKind,
+    WeeksOfMonth,
+};
+
+const BACKEND: &str = "systemd";
+
+/// Prefix of every unit name, which keeps tasks from replacing units they
+/// happen to share a name with.
+const UNIT_PREFIX: &str = "schtask-";
+
+/// Directory `systemctl enable` links enabled timers into.
+const TIMERS_WANTS: &str = "timers.target.wants";
+
+/// Which service manager runs a task's units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitScope {
+    /// The system manager, for tasks that run as a service account or
+    /// whether or not their user is logged on.
+    System,
+    /// The user's own manager, for tasks that run in the user's session.
+    User,
+}
+
+/// The unit files a task becomes: a `.service` unit that runs its actions
+/// and a `.timer` unit that starts the service.
+///
+/// Triggers map to timer settings: calendar triggers to `OnCalendar=`,
+/// boot triggers to `OnBootSec=`, logon triggers to `OnStartupSec=` of the
+/// user's manager and registration triggers to `OnActiveSec=`. Only the
+/// time of day of a recurring trigger's start boundary is used, so the
+/// timer fires from the day it is installed on, and triggers other than
+/// one-time ones that start after today (in UTC) are rejected. Anything
+/// systemd has no equivalent for is rejected with
+/// [`SchtaskError::Unsupported`] rather than approximated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemdUnits {
+    /// Name of both units without their suffix, e.g.
+    /// `schtask-Vendor-Backup`.
+    pub name: String,
+    pub scope: UnitScope,
+    /// Contents of the `.service` unit.
+    pub service: String,
+    /// Contents of the `.timer` unit; `None` for a task without enabled
+    /// triggers, which only runs when started by hand.
+    pub timer: Option<String>,
+}
+
+impl SystemdUnits {
+    /// Translates `task` into unit files.
+    pub fn render(task: &Task) -> Result<SystemdUnits, SchtaskError> {
+        let path = task.path();
+        let (scope, account) = unit_scope(&task.definition.principal)?;
+        let settings = &task.definition.settings;
+        if settings.run_only_if_network_available || settings.network_settings.is_some() {
+            return Err(unsupported(BACKEND, "network conditions"));
+        }
+        if settings.run_only_if_idle {
+            return Err(unsupported(BACKEND, "idle conditions"));
+        }
+        let description = unit_text(task.definition.registration_info.description.as_deref().unwrap_or(&path));
+
+        let mut service = format!("[Unit]\nDescription={}\n\n[Service]\nType=oneshot\n", description);
+        if let Some(account) = account {
+            service.push_str(&format!("{}\n", account));
+        }
+        let mut working_directory = None;
+        for action in &task.definition.actions {
+            let ActionKind::Exec(exec) = &action.kind else {
+                return Err(unsupported(BACKEND, &format!("{} actions", action.kind.name())));
+            };
+            if let Some(dir) = &exec.working_directory {
+                if working_directory.is_some_and(|other| other != dir) {
+                    return Err(unsupported(BACKEND, "exec actions with different working directories"));
+                }
+                working_directory = Some(dir);
+            }
+        }
+        if let Some(dir) = working_directory {
+            service.push_str(&format!("WorkingDirectory={}\n", unit_value(dir, "working directories")?));
+        }
+        for action in &task.definition.actions {
+            if let ActionKind::Exec(exec) = &action.kind {
+                let mut command = quote_exec_arg(&exec.path, "exec paths")?;
+                for arg in exec.arguments.as_deref().map(split_args).unwrap_or_default() {
+                    command.push(' ');
+                    command.push_str(&quote_exec_arg(&arg, "exec arguments")?);
+                }
+                service.push_str(&format!("ExecStart={}\n", command));
+            }
+        }
+        // A oneshot service is starting for as long as it runs
+        let limit = task.definition.settings.execution_time_limit;
+        if limit != TaskDuration::ZERO {
+            service.push_str(&format!("TimeoutStartSec={}\n", timespan(limit)));
+        }
+
+        Ok(SystemdUnits {
+            name: unit_name(&path),
+            scope,
+            service,
+            timer: timer_unit(task, &description, scope)?,
+        })
+    }
+}
+
+/// Writes the units of tasks to a system and a user unit directory,
+/// without calling `systemctl`.
+///
+/// Enabling a task links its timer into `timers.target.wants`, as
+/// `systemctl enable` does. The managers only see new or changed units
+/// after `systemctl daemon-reload` (or `systemctl --user daemon-reload`).
+///
+/// Unit files are looked up without regard to case, as task names
+/// compare. The units cannot be read back into task definitions, and
+/// running or stopping tasks takes `systemctl`, so
+/// [`get`](SchedulerBackend::get), [`list`](SchedulerBackend::list),
+/// [`run`](SchedulerBackend::run) and [`stop`](SchedulerBackend::stop)
+/// fail with [`SchtaskError::Unsupported`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemdBackend {
+    system_dir: PathBuf,
+    user_dir: PathBuf,
+}
+
+impl SystemdBackend {
+    /// Writes system units to `system_dir` and user units to `user_dir`.
+    pub fn new(system_dir: impl Into<PathBuf>, user_dir: impl Into<PathBuf>) -> Self {
+        Self { system_dir: system_dir.into(), user_dir: user_dir.into() }
+    }
+
+    /// The directory units of `scope` are written to.
+    pub fn dir(&self, scope: UnitScope) -> &Path {
+        match scope {
+            UnitScope::System => &self.system_dir,
+            UnitScope::User => &self.user_dir,
+        }
+    }
+
+    /// The scope whose directory holds the service unit of the task at
+    /// `path`, if any, and the name the units were written under. Names
+    /// match without regard to case, so `\Vendor\BACKUP` finds the units
+    /// of `\Vendor\Backup`.
+    fn find(&self, path: &str) -> Option<(UnitScope, String)> {
+        [UnitScope::System, UnitScope::User].into_iter().find_map(|scope| {
+            let entries = fs::read_dir(self.dir(scope)).ok()?;
+            entries.flatten().find_map(|entry| {
+                let file_name = entry.file_name().into_string().ok()?;
+                let name = file_name.strip_suffix(".service")?;
+                let found = unit_path(name).is_some_and(|unit| same_path(&unit, path)) && entry.path().is_file();
+                found.then(|| (scope, name.to_string()))
+            })
+        })
+    }
+
+    fn find_task(&self, path: &str, context: &str) -> Result<(UnitScope, String), SchtaskError> {
+        self.find(path)
+            .ok_or_else(|| error(SchtaskError::Task, HResult::ERROR_FILE_NOT_FOUND, context, path))
+    }
+
+    /// Links or unlinks the timer of `name` in `timers.target.wants`.
+    fn link_timer(&self, scope: UnitScope, name: &str, enabled: bool) -> Result<(), SchtaskError> {
+        let wants = self.dir(scope).join(TIMERS_WANTS);
+        let link = wants.join(format!("{}.timer", name));
+        remove_file(&link)?;
+        if enabled && self.dir(scope).join(format!("{}.timer", name)).is_file() {
+            fs::create_dir_all(&wants).map_err(|e| file_error(&wants, "Cannot create", e))?;
+            std::os::unix::fs::symlink(format!("../{}.timer", name), &link)
+                .map_err(|e| file_error(&link, "Cannot link", e))?;
+        }
+        Ok(())
+    }
+
+    /// Removes the units of `name` from the directory of `scope`.
+    fn remove_units(&self, scope: UnitScope, name: &str) -> Result<(), SchtaskError> {
+        self.link_timer(scope, name, false)?;
+        let dir = self.dir(scope);
+        remove_file(&dir.join(format!("{}.timer", name)))?;
+        remove_file(&dir.join(format!("{}.service", name)))
+    }
+}
+
+impl SchedulerBackend for SystemdBackend {
+    /// Uses `/etc/systemd/system` for system units and
+    /// `$XDG_CONFIG_HOME/systemd/user` for user units.
+    fn connect() -> Result<Self, SchtaskError> {
+        Ok(Self::new("/etc/systemd/system", config_home()?.join("systemd").join("user")))
+    }
+
+    fn get_folder(&self, path: &str) -> Result<TaskFolder, SchtaskError> {
+        path_folder(path)
+    }
+
+    fn create_folder(&mut self, path: &str) -> Result<TaskFolder, SchtaskError> {
+        path_folder(path)
+    }
+
+    fn delete_folder(&mut self, path: &str) -> Result<(), SchtaskError> {
+        delete_path_folder(path, || {
+            let mut paths = Vec::new();
+            for scope in [UnitScope::System, UnitScope::User] {
+                let entries = match fs::read_dir(self.dir(scope)) {
+                    Ok(entries) => entries,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(file_error(self.dir(scope), "Cannot read", e)),
+                };
+                for entry in entries.flatten() {
+                    let file_name = entry.file_name();
+                    paths.extend(file_name.to_str().and_then(|name| name.strip_suffix(".service")).and_then(unit_path));
+                }
+            }
+            Ok(paths)
+        })
+    }
+
+    fn register(&mut self, task: &Task, mode: RegisterMode) -> Result<RegisteredTaskInfo, SchtaskError> {
+        task.validate()?;
+        let path = task.path();
+        let units = SystemdUnits::render(task)?;
+
+        let existing = self.find(&path);
+        match (mode, &existing) {
+            (RegisterMode::Create, Some(_)) => {
+                return Err(error(SchtaskError::Register, HResult::ERROR_ALREADY_EXISTS, "Cannot register the task", &path));
+            }
+            (RegisterMode::Update, None) => {
+                return Err(error(SchtaskError::Register, HResult::ERROR_FILE_NOT_FOUND, "Cannot register the task", &path));
+            }
+            _ => {}
+        }
+        // A task whose principal changed moves to the other directory, and
+        // one registered again in other case replaces the old units
+        if let Some((scope, name)) = &existing
+            && (*scope != units.scope || *name != units.name)
+        {
+            self.remove_units(*scope, name)?;
+        }
+
+        let dir = self.dir(units.scope);
+        fs::create_dir_all(dir).map_err(|e| file_error(dir, "Cannot create", e))?;
+        write_file(&dir.join(format!("{}.service", units.name)), &units.service)?;
+        let timer = dir.join(format!("{}.timer", units.name));
+        match &units.timer {
+            Some(contents) => write_file(&timer, contents)?,
+            None => remove_file(&timer)?,
+        }
+        self.link_timer(units.scope, &units.name, task.definition.settings.enabled)?;
+
+        Ok(RegisteredTaskInfo { name: task.name.clone(), path })
+    }
+
+    fn get(&self, _path: &str) -> Result<RegisteredTask, SchtaskError> {
+        Err(unsupported(BACKEND, "reading tasks back from unit files"))
+    }
+
+    fn list(&self, _folder: &str) -> Result<Vec<RegisteredTask>, SchtaskError> {
+        Err(unsupported(BACKEND, "reading tasks back from unit files"))
+    }
+
+    fn run(&mut self, _path: &str) -> Result<(), SchtaskError> {
+        Err(unsupported(BACKEND, "running tasks on demand"))
+    }
+
+    fn stop(&mut self, _path: &str) -> Result<(), SchtaskError> {
+        Err(unsupported(BACKEND, "stopping running tasks"))
+    }
+
+    fn set_enabled(&mut self, path: &str, enabled: bool) -> Result<(), SchtaskError> {
+        let (scope, name) = self.find_task(path, "Cannot enable or disable the task")?;
+        self.link_timer(scope, &name, enabled)
+    }
+
+    fn delete(&mut self, path: &str) -> Result<(), SchtaskError> {
+        let (scope, name) = self.find_task(path, "Cannot delete the task")?;
+        self.remove_units(scope, &name)
+    }
+}
+
+/// The scope a principal's units run in, and the `[Service]` line that
+/// selects the account.
+fn unit_scope(principal: &Principal) -> Result<(UnitScope, Option<String>), SchtaskError> {
+    let user_id = principal.user_id.as_deref();
+    match principal.effective_logon_type() {
+        LogonType::ServiceAccount => match user_id.and_then(ServiceAccount::from_user_id) {
+            // Services run as root unless told otherwise
+            Some(ServiceAccount::System) | None => Ok((UnitScope::System, None)),
+            Some(ServiceAccount::LocalService | ServiceAccount::NetworkService) => {
+                Ok((UnitScope::System, Some("DynamicUser=yes".to_string())))
+            }
+        },
+        // Tasks that run whether or not the user is logged on
+        LogonType::Password | LogonType::S4U => {
+            let user_id = user_id.ok_or_else(|| unsupported(BACKEND, "password or S4U logons without a user"))?;
+            let name = user_id.rsplit_once('\\').map_or(user_id, |(_, name)| name);
+            Ok((UnitScope::System, Some(format!("User={}", unit_value(name, "user names")?))))
+        }
+        LogonType::InteractiveToken | LogonType::InteractiveTokenOrPassword | LogonType::Group => {
+            Ok((UnitScope::User, None))
+        }
+    }
+}
+
+/// The `.timer` unit that starts the task, if it has enabled triggers.
+fn timer_unit(task: &Task, description: &str, scope: UnitScope) -> Result<Option<String>, SchtaskError> {
+    let triggers: Vec<&Trigger> = task.definition.triggers.iter().filter(|trigger| trigger.enabled).collect();
+    if triggers.is_empty() {
+        return Ok(None);
+    }
+
+    let mut timer = format!("[Unit]\nDescription={}\n\n[Timer]\n", description);
+    let mut random_delay: Option<TaskDuration> = None;
+    let today = Timestamp::now_utc().date();
+    let single = triggers.len() == 1;
+    for trigger in triggers {
+        // Only a one-time trigger's OnCalendar= carries its date
+        let dated = matches!(trigger.kind, TriggerKind::Time);
+        if !dated && trigger.start_boundary.is_some_and(|start| start.date() > today) {
+            return Err(unsupported(BACKEND, "start dates after today"));
+        }
+        if trigger.end_boundary.is_some() {
+            return Err(unsupported(BACKEND, "trigger end boundaries"));
+        }
+        if trigger.execution_time_limit.is_some() {
+            return Err(unsupported(BACKEND, "trigger execution time limits"));
+        }
+        let delay = timespan(trigger.delay.unwrap_or(TaskDuration::ZERO));
+        match &trigger.kind {
+            TriggerKind::Boot => timer.push_str(&format!("OnBootSec={}\n", delay)),
+            TriggerKind::Registration => timer.push_str(&format!("OnActiveSec={}\n", delay)),
+            // The user's manager starts when the user first logs on
+            TriggerKind::Logon(_) if scope == UnitScope::User => {
+                timer.push_str(&format!("OnStartupSec={}\n", delay))
+            }
+            TriggerKind::Logon(_) => return Err(unsupported(BACKEND, "logon triggers in system units")),
+            TriggerKind::Idle => return Err(unsupported(BACKEND, "idle triggers")),
+            TriggerKind::SessionStateChange(_) => return Err(unsupported(BACKEND, "session state change triggers")),
+            TriggerKind::Event(_) => return Err(unsupported(BACKEND, "event triggers")),
+            _ => {
+                for calendar in on_calendar(trigger)? {
+                    timer.push_str(&format!("OnCalendar={}\n", calendar));
+                }
+            }
+        }
+        if let Some(repetition) = &trigger.repetition {
+            if repetition.duration.is_some() {
+                return Err(unsupported(BACKEND, "repetition durations"));
+            }
+            // OnUnitActiveSec= repeats every activation of the timer, not
+            // just those of this trigger
+            if !single {
+                return Err(unsupported(BACKEND, "repetition alongside other triggers"));
+            }
+            timer.push_str(&format!("OnUnitActiveSec={}\n", timespan(repetition.interval)));
+        }
+        random_delay = random_delay.max(trigger.random_delay);
+    }
+
+    // One random delay applies to the whole timer: the longest one asked for
+    if let Some(random_delay) = random_delay {
+        timer.push_str(&format!("RandomizedDelaySec={}\n", timespan(random_delay)));
+    }
+    let settings = &task.definition.settings;
+    if settings.start_when_available {
+        timer.push_str("Persistent=true\n");
+    }
+    if settings.wake_to_run {
+        timer.push_str("WakeSystem=true\n");
+    }
+    timer.push_str("\n[Install]\nWantedBy=timers.target\n");
+    Ok(Some(timer))
+}
+
+/// The `OnCalendar=` expressions of a scheduled trigger.
+fn on_calendar(trigger: &Trigger) -> Result<Vec<String>, SchtaskError> {
+    // Validation makes sure scheduled triggers have a start boundary
+    let start = trigger.start_boundary.expect("scheduled trigger without a start boundary");
+    let (hour, minute, second) = start.time();
+    let zone = match start.zone() {
+        TimeZone::Local => "",
+        TimeZone::Utc => " UTC",
+        TimeZone::Offset(_) => return Err(unsupported(BACKEND, "times with a fixed UTC offset")),
+    };
+    let time = format!("{:02}:{:02}:{:02}{}", hour, minute, second, zone);
+
+    let mut calendars = Vec::new();
+    match &trigger.kind {
+        TriggerKind::Time => {
+            let (year, month, day) = start.date();
+            calendars.push(format!("{:04}-{:02}-{:02} {}", year, month, day, time));
+        }
+        TriggerKind::Daily(daily) if daily.days_interval == 1 => calendars.push(format!("*-*-* {}", time)),
+        TriggerKind::Daily(_) => return Err(unsupported(BACKEND, "daily triggers with an interval of more than one day")),
+        TriggerKind::Weekly(weekly) if weekly.weeks_interval == 1 => {
+            calendars.push(format!("{} *-*-* {}", weekdays(weekly.days_of_week), time));
+        }
+        TriggerKind::Weekly(_) => return Err(unsupported(BACKEND, "weekly triggers with an interval of more than one week")),
+        TriggerKind::Monthly(monthly) => {
+            let months = months(monthly.months);
+            if !monthly.days_of_month.is_empty() {
+                let days: Vec<String> = monthly.days_of_month.days().map(|day| format!("{:02}", day)).collect();
+                calendars.push(format!("*-{}-{} {}", months, days.join(","), time));
+            }
+            if monthly.run_on_last_day_of_month {
+                calendars.push(format!("*-{}~01 {}", months, time));
+            }
+        }
+        TriggerKind::MonthlyDayOfWeek(monthly) => {
+            let (days, months) = (weekdays(monthly.days_of_week), months(monthly.months));
+            let weeks: Vec<&str> = [
+                (WeeksOfMonth::FIRST, "01..07"),
+                (WeeksOfMonth::SECOND, "08..14"),
+                (WeeksOfMonth::THIRD, "15..21"),
+                (WeeksOfMonth::FOURTH, "22..28"),
+            ]
+            .into_iter()
+            .filter(|(week, _)| monthly.weeks_of_month.contains(*week))
+            .map(|(_, range)| range)
+            .collect();
+            if !weeks.is_empty() {
+                calendars.push(format!("{} *-{}-{} {}", days, months, weeks.join(","), time));
+            }
+            // The last seven days of the month
+            if monthly.run_on_last_week_of_month {
+                calendars.push(format!("{} *-{}~07/1 {}", days, months, time));
+            }
+        }
+        _ => unreachable!("not a scheduled trigger"),
+    }
+    Ok(calendars)
+}
+
+/// Weekday names as `OnCalendar=` accepts them, e.g. `Monday,Friday`.
+fn weekdays(days: DaysOfWeek) -> String {
+    let names: Vec<&str> =
+        DaysOfWeek::NAMED.iter().filter(|(day, _)| days.contains(*day)).map(|(_, name)| *name).collect();
+    names.join(",")
+}
+
+/// Months as an `OnCalendar=` month field, e.g. `01,07` or `*`.
+fn months(months: Months) -> String {
+    if months == Months::ALL {
+        return "*".to_string();
+    }
+    let numbers: Vec<String> = Months::NAMED
+        .iter()
+        .enumerate()
+        .filter(|(_, (month, _))| months.contains(*month))
+        .map(|(index, _)| format!("{:02}", index + 1))
+        .collect();
+    numbers.join(",")
+}
+
+/// A duration as a systemd time span, e.g. `1d 2h 30min`.
+fn timespan(duration: TaskDuration) -> String {
+    let secs = duration.as_duration().as_secs();
+    let parts = [(secs / 86_400, "d"), (secs % 86_400 / 3600, "h"), (secs % 3600 / 60, "min"), (secs % 60, "s")];
+    let span: Vec<String> =
+        parts.iter().filter(|(value, _)| *value > 0).map(|(value, unit)| format!("{}{}", value, unit)).collect();
+    if span.is_empty() { "0".to_string() } else { span.join(" ") }
+}
+
+/// The unit name of a task or folder path, escaped like `systemd-escape`
+/// would escape it as a path: `\Vendor\My Task` becomes
+/// `schtask-Vendor-My\x20Task`.
+fn unit_name(path: &str) -> String {
+    let mut name = UNIT_PREFIX.to_string();
+    for (index, part) in path.trim_start_matches('\\').split('\\').enumerate() {
+        if index > 0 {
+            name.push('-');
+        }
+        for (offset, byte) in part.bytes().enumerate() {
+            let plain = byte.is_ascii_alphanumeric() || byte == b'_' || byte == b':' || (byte == b'.' && offset > 0);
+            if plain {
+                name.push(byte as char);
+            } else {
+                name.push_str(&format!("\\x{:02x}", byte));
+            }
+        }
+    }
+    name
+}
+
+/// The task or folder path a unit name was made from by [`unit_name`], or
+/// `None` if it is not one.
+fn unit_path(name: &str) -> Option<String> {
+    let mut path = Vec::new();
+    let mut bytes = name.strip_prefix(UNIT_PREFIX)?.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'-' => path.push(b'\\'),
+            b'\\' => {
+                let hex = [bytes.next()?, bytes.next()?, bytes.next()?];
+                let hex = std::str::from_utf8(hex.strip_prefix(b"x")?).ok()?;
+                path.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            _ => path.push(byte),
+        }
+    }
+    String::from_utf8(path).ok().map(|path| format!("\\{}", path))
+}
+
+/// Free text for a unit setting such as `Description=`, passed on
+/// literally: `%` is doubled so that it is not read as a specifier, and
+/// line breaks and other control characters, which would end the setting
+/// or start another, become spaces.
+fn unit_text(text: &str) -> String {
+    text.replace('%', "%%").replace(|c: char| c.is_control(), " ")
+}
+
+/// A value for a unit setting such as `User=`, passed on literally. Values
+/// with control characters cannot be written and are rejected; `what`
+/// names them in the error.
+fn unit_value(value: &str, what: &str) -> Result<String, SchtaskError> {
+    if value.contains(|c: char| c.is_control()) {
+        return Err(unsupported(BACKEND, &format!("{} with control characters", what)));
+    }
+    Ok(value.replace('%', "%%"))
+}
+
+/// Quotes one word of an `ExecStart=` command line. Specifiers and
+/// variables are escaped so that the word is passed on literally; words
+/// with control characters are rejected like other [`unit_value`]s.
+fn quote_exec_arg(arg: &str, what: &str) -> Result<String, SchtaskError> {
+    let escaped = unit_value(arg, what)?.replace('$', "$$");
+    let plain = !escaped.is_empty() && !escaped.contains(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '\\' | ';'));
+    if plain {
+        return Ok(escaped);
+    }
+    let mut quoted = String::from("\"");
+    for c in escaped.chars() {
+        if matches!(c, '"' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    Ok(quoted)
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), SchtaskError> {
+    fs::write(path, contents).map_err(|e| file_error(path, "Cannot write", e))
+}
+
+/// Removes a file that may not exist.
+fn remove_file(path: &Path) -> Result<(), SchtaskError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(file_error(path, "Cannot remove", e)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::tests::{scratch_path, unsupported_feature};
+    use crate::builder::TaskBuilder;
+    use crate::model::{Action, DaysOfMonth, ExecAction, Repetition, TaskSettings};
+
+    fn start() -> Timestamp {
+        Timestamp::new(2024, 6, 1, 9, 30, 0).unwrap()
+    }
+
+    fn task(trigger: Trigger) -> Task {
+        TaskBuilder::new("Backup")
+            .folder("\\Vendor")
+            .trigger(trigger)
+            .action(Action::exec("/usr/bin/backup", Some("--to \"/mnt/my disk\" 100%")))
+            .build()
+            .unwrap()
+    }
+
+    fn calendars(trigger: Trigger) -> Vec<String> {
+        let timer = SystemdUnits::render(&task(trigger)).unwrap().timer.unwrap();
+        timer.lines().filter_map(|line| line.strip_prefix("OnCalendar=")).map(str::to_string).collect()
+    }
+
+    #[test]
+    fn renders_a_daily_task() {
+        let trigger = Trigger { random_delay: Some(TaskDuration::from_mins(90)), ..Trigger::daily(start(), 1) };
+        let mut task = task(trigger);
+        task.definition.settings.start_when_available = true;
+        let units = SystemdUnits::render(&task).unwrap();
+
+        assert_eq!(units.name, "schtask-Vendor-Backup");
+        assert_eq!(units.scope, UnitScope::User);
+        assert_eq!(
+            units.service,
+            "[Unit]\n\
+             Description=\\Vendor\\Backup\n\
+             \n\
+             [Service]\n\
+             Type=oneshot\n\
+             ExecStart=/usr/bin/backup --to \"/mnt/my disk\" 100%%\n\
+             TimeoutStartSec=3d\n"
+        );
+        assert_eq!(
+            units.timer.as_deref(),
+            Some(
+                "[Unit]\n\
+                 Description=\\Vendor\\Backup\n\
+                 \n\
+                 [Timer]\n\
+                 OnCalendar=*-*-* 09:30:00\n\
+                 RandomizedDelaySec=1h 30min\n\
+                 Persistent=true\n\
+                 \n\
+                 [Install]\n\
+                 WantedBy=timers.target\n"
+            )
+        );
+    }
+
+    #[test]
+    fn translates_triggers_to_timer_settings() {
+        assert_eq!(calendars(Trigger::once(start().utc())), ["2024-06-01 09:30:00 UTC"]);
+        assert_eq!(
+            calendars(Trigger::weekly(start(), DaysOfWeek::MONDAY | DaysOfWeek::FRIDAY, 1)),
+            ["Monday,Friday *-*-* 09:30:00"]
+        );
+
+        let mut monthly = Trigger::monthly(start(), DaysOfMonth::from_days(&[1, 15]).unwrap(), Months::ALL);
+        if let TriggerKind::Monthly(settings) = &mut monthly.kind {
+            settings.run_on_last_day_of_month = true;
+        }
+        assert_eq!(calendars(monthly), ["*-*-01,15 09:30:00", "*-*~01 09:30:00"]);
+
+        let mut last_sunday = Trigger::monthly_day_of_week(
+            start(),
+            WeeksOfMonth::FIRST | WeeksOfMonth::THIRD,
+            DaysOfWeek::SUNDAY,
+            Months::JANUARY | Months::JULY,
+        );
+        if let TriggerKind::MonthlyDayOfWeek(settings) = &mut last_sunday.kind {
+            settings.run_on_last_week_of_month = true;
+        }
+        assert_eq!(
+            calendars(last_sunday),
+            ["Sunday *-01,07-01..07,15..21 09:30:00", "Sunday *-01,07~07/1 09:30:00"]
+        );
+
+        let boot = Trigger { delay: Some(TaskDuration::from_secs(45)), ..Trigger::boot() };
+        let timer = SystemdUnits::render(&task(boot)).unwrap().timer.unwrap();
+        assert!(timer.contains("OnBootSec=45s\n"), "{}", timer);
+        let repeated = Trigger { repetition: Some(Repetition::new(TaskDuration::from_hours(1), None)), ..Trigger::logon(None) };
+        let timer = SystemdUnits::render(&task(repeated)).unwrap().timer.unwrap();
+        assert!(timer.contains("OnStartupSec=0\nOnUnitActiveSec=1h\n"), "{}", timer);
+    }
+
+    #[test]
+    fn picks_user_or_system_units_by_principal() {
+        let render = |principal: Principal| {
+            let mut task = task(Trigger::boot());
+            task.definition.principal = principal;
+            let units = SystemdUnits::render(&task).unwrap();
+            let account = units.service.lines().find(|line| line.starts_with("User=") || line.starts_with("DynamicUser="));
+            (units.scope, account.map(str::to_string))
+        };
+
+        assert_eq!(render(Principal::default()), (UnitScope::User, None));
+        assert_eq!(render(Principal::group("BUILTIN\\Users")), (UnitScope::User, None));
+        assert_eq!(render(Principal::system()), (UnitScope::System, None));
+        assert_eq!(
+            render(Principal::service_account(ServiceAccount::NetworkService)),
+            (UnitScope::System, Some("DynamicUser=yes".to_string()))
+        );
+        assert_eq!(
+            render(Principal::user("CORP\\backup", LogonType::S4U)),
+            (UnitScope::System, Some("User=backup".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_what_systemd_cannot_express() {
+        let feature = |task: Task| unsupported_feature(SystemdUnits::render(&task));
+
+        assert_eq!(feature(task(Trigger::event("<QueryList/>"))), "event triggers");
+        assert_eq!(feature(task(Trigger::daily(start(), 2))), "daily triggers with an interval of more than one day");
+        let later = Timestamp::new(9999, 1, 1, 9, 30, 0).unwrap();
+        assert_eq!(feature(task(Trigger::daily(later, 1))), "start dates after today");
+        for trigger in [Trigger::boot(), Trigger::logon(None), Trigger::registration()] {
+            assert_eq!(feature(task(Trigger { start_boundary: Some(later), ..trigger })), "start dates after today");
+        }
+        assert_eq!(calendars(Trigger::once(later)), ["9999-01-01 09:30:00"]);
+        let repeated = Trigger {
+            repetition: Some(Repetition::new(TaskDuration::from_mins(5), Some(TaskDuration::from_hours(1)))),
+            ..Trigger::boot()
+        };
+        assert_eq!(feature(task(repeated)), "repetition durations");
+        let mut repeated_and_boot =
+            task(Trigger { repetition: Some(Repetition::new(TaskDuration::from_hours(1), None)), ..Trigger::daily(start(), 1) });
+        repeated_and_boot.definition.triggers.push(Trigger::boot());
+        assert_eq!(feature(repeated_and_boot), "repetition alongside other triggers");
+        let mut system_logon = task(Trigger::logon(None));
+        system_logon.definition.principal = Principal::system();
+        assert_eq!(feature(system_logon), "logon triggers in system units");
+        let mut message = task(Trigger::boot());
+        message.definition.actions = vec![Action::show_message("Hello", "World")];
+        assert_eq!(feature(message), "ShowMessage actions");
+        let mut network = task(Trigger::boot());
+        network.definition.settings.run_only_if_network_available = true;
+        assert_eq!(feature(network), "network conditions");
+        let mut idle = task(Trigger::boot());
+        idle.definition.settings.run_only_if_idle = true;
+        assert_eq!(feature(idle), "idle conditions");
+    }
+
+    #[test]
+    fn escapes_unit_names_and_commands() {
+        assert_eq!(unit_name("\\My Task"), "schtask-My\\x20Task");
+        assert_eq!(unit_name("\\a-b\\.hidden"), "schtask-a\\x2db-\\x2ehidden");
+        for path in ["\\My Task", "\\a-b\\.hidden", "\\Vendor\\Ärger"] {
+            assert_eq!(unit_path(&unit_name(path)).as_deref(), Some(path));
+        }
+        assert_eq!(unit_path("schtask-a\\x2"), None);
+        assert_eq!(unit_path("other-unit"), None);
+        assert_eq!(quote_exec_arg("$HOME", "exec arguments").unwrap(), "$$HOME");
+        assert_eq!(quote_exec_arg("", "exec arguments").unwrap(), "\"\"");
+        assert_eq!(quote_exec_arg("C:\\dir", "exec arguments").unwrap(), "\"C:\\\\dir\"");
+    }
+
+    #[test]
+    fn escapes_specifiers_and_line_breaks_in_settings() {
+        let mut task = task(Trigger::boot());
+        task.definition.registration_info.description = Some("Backs up 100% of the files\n[Service]\nExecStart=/bin/evil".to_string());
+        let in_dir = |dir: &str| {
+            let exec = ExecAction { path: "/usr/bin/backup".to_string(), arguments: None, working_directory: Some(dir.to_string()) };
+            Action::new(ActionKind::Exec(exec))
+        };
+        task.definition.actions = vec![in_dir("/srv/%h")];
+        task.definition.principal = Principal::user("CORP\\50%", LogonType::S4U);
+        let units = SystemdUnits::render(&task).unwrap();
+
+        let description = "Description=Backs up 100%% of the files [Service] ExecStart=/bin/evil\n";
+        assert!(units.service.starts_with(&format!("[Unit]\n{}\n[Service]\n", description)), "{}", units.service);
+        assert!(units.timer.unwrap().starts_with(&format!("[Unit]\n{}", description)));
+        assert!(units.service.contains("\nUser=50%%\nWorkingDirectory=/srv/%%h\n"), "{}", units.service);
+        assert_eq!(units.service.lines().filter(|line| line.starts_with("ExecStart=")).count(), 1);
+
+        task.definition.actions = vec![in_dir("/srv\n/x")];
+        assert_eq!(
+            SystemdUnits::render(&task),
+            Err(unsupported(BACKEND, "working directories with control characters"))
+        );
+
+        // A line break in an argument would end ExecStart= and start another setting
+        task.definition.actions = vec![Action::exec("/usr/bin/backup", Some("\"x\nExecStartPre=/bin/evil\" y"))];
+        assert_eq!(SystemdUnits::render(&task), Err(unsupported(BACKEND, "exec arguments with control characters")));
+        task.definition.actions = vec![Action::exec("/usr/bin/backup\r", None)];
+        assert_eq!(SystemdUnits::render(&task), Err(unsupported(BACKEND, "exec paths with control characters")));
+    }
+
+    #[test]
+    fn writes_enables_and_removes_units() {
+        let root = scratch_path("systemd-register");
+        let mut backend = SystemdBackend::new(root.join("system"), root.join("user"));
+        let user_dir = root.join("user");
+        let timer_link = user_dir.join("timers.target.wants/schtask-Vendor-Backup.timer");
+        let hresult = |result: Result<RegisteredTaskInfo, SchtaskError>| result.unwrap_err().hresult().unwrap();
+
+        let info = backend.register(&task(Trigger::boot()), RegisterMode::Create).unwrap();
+        assert_eq!(info.path, "\\Vendor\\Backup");
+        assert!(user_dir.join("schtask-Vendor-Backup.service").is_file());
+        assert!(user_dir.join("schtask-Vendor-Backup.timer").is_file());
+        assert_eq!(fs::read_link(&timer_link).unwrap(), Path::new("../schtask-Vendor-Backup.timer"));
+        assert_eq!(hresult(backend.register(&task(Trigger::boot()), RegisterMode::Create)), HResult::ERROR_ALREADY_EXISTS);
+        assert_eq!(
+            backend.delete_folder("\\Vendor").unwrap_err().hresult(),
+            Some(HResult::ERROR_DIR_NOT_EMPTY)
+        );
+
+        backend.set_enabled("\\VENDOR\\backup", false).unwrap();
+        assert!(fs::symlink_metadata(&timer_link).is_err());
+
+        // Running as SYSTEM moves the units to the system directory
+        let mut system = task(Trigger::boot());
+        system.definition.principal = Principal::system();
+        system.definition.settings = TaskSettings { enabled: false, ..system.definition.settings };
+        backend.register(&system, RegisterMode::Update).unwrap();
+        assert!(!user_dir.join("schtask-Vendor-Backup.service").exists());
+        assert!(root.join("system/schtask-Vendor-Backup.service").is_file());
+        assert!(!root.join("system/timers.target.wants").exists());
+
+        backend.delete("\\Vendor\\Backup").unwrap();
+        assert!(!root.join("system/schtask-Vendor-Backup.service").exists());
+        assert_eq!(backend.delete("\\Vendor\\Backup").unwrap_err().hresult(), Some(HResult::ERROR_FILE_NOT_FOUND));
+        backend.delete_folder("\\Vendor").unwrap();
+        assert_eq!(hresult(backend.register(&task(Trigger::boot()), RegisterMode::Update)), HResult::ERROR_FILE_NOT_FOUND);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn finds_units_without_regard_to_case() {
+        let root = scratch_path("systemd-case");
+        let mut backend = SystemdBackend::new(root.join("system"), root.join("user"));
+        let user_dir = root.join("user");
+
+        backend.register(&task(Trigger::boot()), RegisterMode::Create).unwrap();
+        let upper = TaskBuilder::new("BACKUP").folder("\\vendor").trigger(Trigger::boot()).action(Action::exec("/bin/true", None));
+        let upper = upper.build().unwrap();
+        assert_eq!(
+            backend.register(&upper, RegisterMode::Create).unwrap_err().hresult(),
+            Some(HResult::ERROR_ALREADY_EXISTS)
+        );
+
+        // Registering in other case replaces the units rather than adding more
+        backend.register(&upper, RegisterMode::Update).unwrap();
+        let mut names: Vec<String> =
+            fs::read_dir(&user_dir).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).collect();
+        names.sort();
+        assert_eq!(names, ["schtask-vendor-BACKUP.service", "schtask-vendor-BACKUP.timer", "timers.target.wants"]);
+        assert_eq!(
+            backend.delete_folder("\\Vendor").unwrap_err().hresult(),
+            Some(HResult::ERROR_DIR_NOT_EMPTY)
+        );
+
+        backend.delete("\\Vendor\\Backup").unwrap();
+        assert!(!user_dir.join("schtask-vendor-BACKUP.service").exists());
+        assert!(fs::read_dir(user_dir.join("timers.target.wants")).unwrap().next().is_none());
+
+        // Case folds beyond ASCII, as task names compare everywhere
+        let umlaut = TaskBuilder::new("Ärger").trigger(Trigger::boot()).action(Action::exec("/bin/true", None));
+        backend.register(&umlaut.build().unwrap(), RegisterMode::Create).unwrap();
+        backend.delete("\\ärger").unwrap();
+        assert!(!user_dir.join(format!("{}.service", unit_name("\\Ärger"))).exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
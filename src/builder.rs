@@ -0,0 +1,384 @@
+use crate::model::{
+    validate_folder,
+    validate_task_name,
+    Action,
+    Principal,
+    Task,
+    TaskDefinition,
+    TaskSettings,
+    Trigger,
+    ValidationError,
+};
+
+/// Fluent builder for a [`Task`].
+///
+/// Every setter checks its input as it is given. The first problem found is
+/// kept and returned by [`TaskBuilder::build`], so a chain of calls can be
+/// written without handling errors at each step.
+///
+/// ```
+/// use schtask::{Action, TaskBuilder, Trigger};
+///
+/// let task = TaskBuilder::new("Backup")
+///     .folder("\\Vendor")
+///     .author("IT")
+///     .trigger(Trigger::logon(None))
+///     .action(Action::exec("C:\\Tools\\backup.exe", Some("--full")))
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(task.path(), "\\Vendor\\Backup");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TaskBuilder {
+    name: String,
+    folder: String,
+    definition: TaskDefinition,
+    error: Option<ValidationError>,
+}
+
+impl TaskBuilder {
+    /// Starts a task called `name` in the root folder.
+    pub fn new(name: &str) -> Self {
+        let mut builder = Self {
+            name: name.to_string(),
+            folder: "\\".to_string(),
+            definition: TaskDefinition::new(),
+            error: None,
+        };
+        builder.check(validate_task_name(name));
+        builder
+    }
+
+    /// Sets the folder the task is registered in, e.g. `\Vendor\App`.
+    pub fn folder(mut self, folder: &str) -> Self {
+        self.check(validate_folder(folder));
+        self.folder = folder.to_string();
+        self
+    }
+
+    /// Sets the task description.
+    pub fn description(mut self, description: &str) -> Self {
+        self.definition.registration_info.description = Some(description.to_string());
+        self
+    }
+
+    /// Sets the task author.
+    pub fn author(mut self, author: &str) -> Self {
+        self.definition.registration_info.author = Some(author.to_string());
+        self
+    }
+
+    /// Adds a trigger.
+    pub fn trigger(mut self, trigger: Trigger) -> Self {
+        self.check(trigger.validate());
+        self.definition.triggers.push(trigger);
+        self
+    }
+
+    /// Adds an action.
+    pub fn action(mut self, action: Action) -> Self {
+        self.check(action.validate());
+        self.definition.actions.push(action);
+        self
+    }
+
+    /// Replaces the task settings.
+    pub fn settings(mut self, settings: TaskSettings) -> Self {
+        self.check(settings.validate());
+        self.definition.settings = settings;
+        self
+    }
+
+    /// Replaces the principal the task runs as.
+    pub fn principal(mut self, principal: Principal) -> Self {
+        self.check(principal.validate());
+        self.definition.principal = principal;
+        self
+    }
+
+    /// Returns the task, or the first problem found while building it.
+    ///
+    /// Triggers and actions added without an id are given one, as by
+    /// [`TaskDefinition::assign_ids`].
+    pub fn build(mut self) -> Result<Task, ValidationError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.definition.assign_ids();
+        let task = Task {
+            name: self.name,
+            folder: self.folder,
+            definition: self.definition,
+        };
+        task.validate()?;
+        Ok(task)
+    }
+
+    /// Records `result` if it is the first error seen.
+    fn check(&mut self, result: Result<(), ValidationError>) {
+        if let (None, Err(error)) = (&self.error, result) {
+            self.error = Some(error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        ActionKind,
+        DaysOfMonth,
+        DaysOfWeek,
+        DurationField,
+        EmailAction,
+        LogonType,
+        MaintenanceSettings,
+        Months,
+        NetworkSettings,
+        RestartOnFailure,
+        TaskDuration,
+        Timestamp,
+        MAX_ACTIONS,
+        MAX_TRIGGERS,
+    };
+
+    #[test]
+    fn builds_task_with_every_field() {
+        let settings = TaskSettings { start_when_available: true, priority: 5, ..TaskSettings::default() };
+        let principal = Principal::user("CORP\\svc", LogonType::S4U);
+
+        let task = TaskBuilder::new("Backup")
+            .folder("\\Vendor\\App")
+            .description("Nightly backup")
+            .author("IT")
+            .trigger(Trigger::logon(None))
+            .action(Action::exec("C:\\backup.exe", None))
+            .settings(settings.clone())
+            .principal(principal.clone())
+            .build()
+            .unwrap();
+
+        assert_eq!(task.path(), "\\Vendor\\App\\Backup");
+        assert_eq!(task.definition.registration_info.author.as_deref(), Some("IT"));
+        assert_eq!(task.definition.registration_info.description.as_deref(), Some("Nightly backup"));
+        assert_eq!(task.definition.triggers, vec![Trigger { id: Some("Logon1".to_string()), ..Trigger::logon(None) }]);
+        assert_eq!(task.definition.settings, settings);
+        assert_eq!(task.definition.principal, principal);
+    }
+
+    #[test]
+    fn keeps_the_first_error() {
+        let result = TaskBuilder::new("a/b")
+            .folder("Vendor")
+            .action(Action::exec("", None))
+            .build();
+
+        assert_eq!(result, Err(ValidationError::InvalidTaskName("a/b".to_string())));
+    }
+
+    #[test]
+    fn rejects_invalid_folders() {
+        for folder in ["", "Vendor", "\\Vendor\\", "\\\\Vendor", "\\Ven:dor", "\\Ven\ndor"] {
+            let result = TaskBuilder::new("Task")
+                .folder(folder)
+                .action(Action::exec("C:\\app.exe", None))
+                .build();
+            assert_eq!(result, Err(ValidationError::InvalidFolder(folder.to_string())));
+        }
+    }
+
+    #[test]
+    fn rejects_schedules_that_never_fire() {
+        let start = Timestamp::new(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut unscheduled = Trigger::daily(start, 1);
+        unscheduled.start_boundary = None;
+        let cases = [
+            (unscheduled, ValidationError::MissingStartBoundary),
+            (
+                Trigger { delay: Some(TaskDuration::from_secs(30)), ..Trigger::idle() },
+                ValidationError::UnsupportedTriggerSetting("Delay"),
+            ),
+            (
+                Trigger::once(start).ends_at(start),
+                ValidationError::EndNotAfterStart {
+                    start: "2024-01-01T00:00:00".to_string(),
+                    end: "2024-01-01T00:00:00".to_string(),
+                },
+            ),
+            (Trigger::daily(start, 0), ValidationError::InvalidDaysInterval(0)),
+            (
+                Trigger::weekly(start, DaysOfWeek::MONDAY, 53),
+                ValidationError::InvalidWeeksInterval(53),
+            ),
+            (
+                Trigger::weekly(start, DaysOfWeek::empty(), 1),
+                ValidationError::EmptySchedule("days of the week"),
+            ),
+            (
+                Trigger::monthly(start, DaysOfMonth::from_days(&[1]).unwrap(), Months::empty()),
+                ValidationError::EmptySchedule("months"),
+            ),
+        ];
+
+        for (trigger, error) in cases {
+            let result = TaskBuilder::new("Task")
+                .trigger(trigger)
+                .action(Action::exec("C:\\app.exe", None))
+                .build();
+            assert_eq!(result, Err(error));
+        }
+        assert_eq!(DaysOfMonth::from_days(&[0]), Err(ValidationError::InvalidDayOfMonth(0)));
+    }
+
+    #[test]
+    fn numbers_ids_per_kind_and_keeps_given_ones() {
+        let start = Timestamp::new(2024, 1, 1, 0, 0, 0).unwrap();
+        let task = TaskBuilder::new("Task")
+            .trigger(Trigger::daily(start, 1))
+            .trigger(Trigger::logon(None))
+            .trigger(Trigger { id: Some("Daily2".to_string()), ..Trigger::daily(start, 2) })
+            .trigger(Trigger::daily(start, 3))
+            .action(Action::exec("C:\\a.exe", None))
+            .action(Action::exec("C:\\b.exe", None))
+            .build()
+            .unwrap();
+
+        let trigger_ids: Vec<_> = task.definition.triggers.iter().map(|t| t.id.as_deref().unwrap()).collect();
+        let action_ids: Vec<_> = task.definition.actions.iter().map(|a| a.id.as_deref().unwrap()).collect();
+        assert_eq!(trigger_ids, ["Daily1", "Logon1", "Daily2", "Daily3"]);
+        assert_eq!(action_ids, ["Exec1", "Exec2"]);
+    }
+
+    #[test]
+    fn enforces_task_scheduler_limits() {
+        let mut builder = TaskBuilder::new("Task");
+        for _ in 0..=MAX_ACTIONS {
+            builder = builder.action(Action::exec("C:\\app.exe", None));
+        }
+        assert_eq!(builder.build(), Err(ValidationError::TooManyActions(MAX_ACTIONS + 1)));
+
+        let mut builder = TaskBuilder::new("Task").action(Action::exec("C:\\app.exe", None));
+        for _ in 0..=MAX_TRIGGERS {
+            builder = builder.trigger(Trigger::logon(None));
+        }
+        assert_eq!(builder.build(), Err(ValidationError::TooManyTriggers(MAX_TRIGGERS + 1)));
+
+        let duplicate = Trigger { id: Some("Same".to_string()), ..Trigger::logon(None) };
+        let result = TaskBuilder::new("Task")
+            .trigger(duplicate.clone())
+            .trigger(duplicate)
+            .action(Action::exec("C:\\app.exe", None))
+            .build();
+        assert_eq!(result, Err(ValidationError::DuplicateId("Same".to_string())));
+    }
+
+    #[test]
+    fn checks_each_action() {
+        let no_braces = "E3A19126-8E23-4E8A-9A9E-6EF3E26A6E39";
+        let too_short = "{E3A19126-8E23-4E8A-9A9E-6EF3E26A6E3}";
+        let no_sender = EmailAction { server: "smtp".to_string(), ..EmailAction::default() };
+        let cases = [
+            (Action::com_handler(no_braces, None), ValidationError::InvalidClassId(no_braces.to_string())),
+            (Action::com_handler(too_short, None), ValidationError::InvalidClassId(too_short.to_string())),
+            (Action::new(ActionKind::SendEmail(no_sender)), ValidationError::MissingActionField("From")),
+            (Action::show_message("", "Done"), ValidationError::MissingActionField("Title")),
+        ];
+
+        for (action, error) in cases {
+            assert_eq!(TaskBuilder::new("Task").action(action).build(), Err(error));
+        }
+        let handler = Action::com_handler("{e3a19126-8e23-4e8a-9a9e-6ef3e26a6e39}", None);
+        assert!(TaskBuilder::new("Task").action(handler).build().is_ok());
+    }
+
+    #[test]
+    fn rejects_incompatible_principals() {
+        let cases = [
+            (
+                Principal { group_id: Some("BUILTIN\\Users".to_string()), ..Principal::user("CORP\\alice", LogonType::Password) },
+                "it names both a user and a group",
+            ),
+            (Principal { logon_type: Some(LogonType::Group), ..Principal::default() }, "a group logon needs a group id"),
+            (
+                Principal { logon_type: Some(LogonType::S4U), ..Principal::group("BUILTIN\\Users") },
+                "a group must use the group logon type",
+            ),
+            (
+                Principal::user("CORP\\alice", LogonType::ServiceAccount),
+                "the service account logon type needs a built-in service account",
+            ),
+            (
+                Principal::user("NT AUTHORITY\\SYSTEM", LogonType::Password),
+                "built-in service accounts must use the service account logon type",
+            ),
+            (
+                Principal { logon_type: Some(LogonType::S4U), ..Principal::default() },
+                "password and S4U logons need a user id",
+            ),
+        ];
+
+        for (principal, reason) in cases {
+            let result = TaskBuilder::new("Task").principal(principal).action(Action::exec("C:\\app.exe", None)).build();
+            assert_eq!(result, Err(ValidationError::IncompatiblePrincipal(reason)));
+        }
+        for principal in [Principal::system(), Principal::group("BUILTIN\\Users"), Principal::user("S-1-5-20", LogonType::ServiceAccount)] {
+            assert_eq!(principal.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        let restart = |count, minutes| Some(RestartOnFailure { count, interval: TaskDuration::from_mins(minutes) });
+        let maintenance = |period, deadline: Option<u64>| {
+            Some(MaintenanceSettings {
+                period: TaskDuration::from_hours(period),
+                deadline: deadline.map(TaskDuration::from_hours),
+                exclusive: false,
+            })
+        };
+        let cases = [
+            (TaskSettings { priority: 11, ..TaskSettings::default() }, ValidationError::InvalidPriority(11)),
+            (TaskSettings { restart_on_failure: restart(0, 5), ..TaskSettings::default() }, ValidationError::InvalidRestartCount(0)),
+            (
+                TaskSettings { restart_on_failure: restart(3, 0), ..TaskSettings::default() },
+                ValidationError::DurationOutOfRange { field: DurationField::RestartInterval, value: TaskDuration::ZERO },
+            ),
+            (
+                TaskSettings { delete_expired_task_after: Some(TaskDuration::from_days(30)), ..TaskSettings::default() },
+                ValidationError::MissingEndBoundary,
+            ),
+            (
+                TaskSettings {
+                    network_settings: Some(NetworkSettings { name: None, id: Some("CorpNet".to_string()) }),
+                    ..TaskSettings::default()
+                },
+                ValidationError::InvalidNetworkId("CorpNet".to_string()),
+            ),
+            (
+                TaskSettings { maintenance_settings: maintenance(12, None), ..TaskSettings::default() },
+                ValidationError::DurationOutOfRange { field: DurationField::MaintenancePeriod, value: TaskDuration::from_hours(12) },
+            ),
+            (
+                TaskSettings { maintenance_settings: maintenance(48, Some(24)), ..TaskSettings::default() },
+                ValidationError::MaintenanceDeadlineTooShort,
+            ),
+        ];
+
+        for (settings, error) in cases {
+            let result = TaskBuilder::new("Task")
+                .trigger(Trigger::logon(None))
+                .action(Action::exec("C:\\app.exe", None))
+                .settings(settings)
+                .build();
+            assert_eq!(result, Err(error));
+        }
+    }
+
+    #[test]
+    fn requires_an_action() {
+        let result = TaskBuilder::new("Task").trigger(Trigger::logon(None)).build();
+
+        assert_eq!(result, Err(ValidationError::NoActions));
+    }
+}
@@ -0,0 +1,340 @@
+//! Command-line interface of the `schtask` binary.
+//!
+//! [`parse_args`] turns the arguments into a [`Command`] without touching
+//! the system, so the parsing can be tested anywhere; the binary then
+//! registers the task the command describes.
+
+use std::fmt;
+
+use crate::builder::TaskBuilder;
+use crate::model::{Action, IdleSettings, NetworkSettings, Task, TaskSettings, Timestamp, Trigger, ValidationError};
+
+/// Usage text printed by `--help` and after argument errors.
+pub const USAGE: &str = "\
+Usage: schtask --create --name <NAME> --action <PATH> [OPTIONS]
+
+Options:
+  --create               Create (or replace) a scheduled task
+  --name <NAME>          Name of the task
+  --folder <FOLDER>      Task folder, e.g. \\Vendor (default: \\)
+  --trigger <TRIGGER>    logon, boot, idle or registration (default: logon)
+  --action <PATH>        Program the task runs
+  --arguments <ARGS>     Arguments passed to the program
+  --condition <COND>     network, network:<NAME or {GUID}> or
+                         idle[:<DURATION>][,wait=<DURATION>][,stop|,nostop][,restart];
+                         may be given more than once
+  --start <WHEN>         now, or a date and time such as 2024-06-01T09:00:00
+  --help                 Print this help";
+
+/// What the command line asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Create a task.
+    Create(Box<CreateOptions>),
+    /// Print the usage text.
+    Help,
+}
+
+/// The options of `--create`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateOptions {
+    pub name: String,
+    pub folder: String,
+    pub trigger: Trigger,
+    pub action: Action,
+    pub conditions: Vec<Condition>,
+}
+
+impl CreateOptions {
+    /// Builds the task the options describe.
+    pub fn to_task(&self) -> Result<Task, ValidationError> {
+        let mut settings = TaskSettings::default();
+        for condition in &self.conditions {
+            condition.apply(&mut settings);
+        }
+        TaskBuilder::new(&self.name)
+            .folder(&self.folder)
+            .settings(settings)
+            .trigger(self.trigger.clone())
+            .action(self.action.clone())
+            .build()
+    }
+}
+
+/// A condition the task waits for before it starts, from `--condition`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    /// Start only while a network is available; with settings, only while
+    /// that network is.
+    Network(Option<NetworkSettings>),
+    /// Start only when the computer is idle, with how long it must be idle
+    /// for, how long to wait for that and what to do when it stops being
+    /// idle.
+    Idle(IdleSettings),
+}
+
+/// What `--condition` accepts, for errors.
+const CONDITIONS: &str = "network, network:<name or {GUID}> or idle[:<duration>][,wait=<duration>][,stop|,nostop][,restart]";
+
+impl Condition {
+    /// Parses `network`, `network:<name or {GUID}>` or
+    /// `idle[:<duration>][,wait=<duration>][,stop|,nostop][,restart]`.
+    /// Idle options left out keep their [`IdleSettings`] defaults.
+    pub fn parse(value: &str) -> Option<Condition> {
+        if value == "idle" || value.starts_with("idle:") || value.starts_with("idle,") {
+            return Self::parse_idle(value);
+        }
+        let (kind, argument) = match value.split_once(':') {
+            Some((kind, argument)) => (kind, Some(argument)),
+            None => (value, None),
+        };
+        match (kind, argument) {
+            ("network", None) => Some(Condition::Network(None)),
+            ("network", Some(network)) if !network.is_empty() => {
+                let settings = if network.starts_with('{') {
+                    NetworkSettings { name: None, id: Some(network.to_string()) }
+                } else {
+                    NetworkSettings { name: Some(network.to_string()), id: None }
+                };
+                Some(Condition::Network(Some(settings)))
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_idle(value: &str) -> Option<Condition> {
+        let mut options = value.split(',');
+        let mut idle = IdleSettings::default();
+        match options.next()?.strip_prefix("idle") {
+            Some("") => {}
+            Some(duration) => idle.idle_duration = duration.strip_prefix(':')?.parse().ok()?,
+            None => return None,
+        }
+        for option in options {
+            match option {
+                "stop" => idle.stop_on_idle_end = true,
+                "nostop" => idle.stop_on_idle_end = false,
+                "restart" => idle.restart_on_idle = true,
+                _ => idle.wait_timeout = option.strip_prefix("wait=")?.parse().ok()?,
+            }
+        }
+        Some(Condition::Idle(idle))
+    }
+
+    /// Sets the task settings that make Task Scheduler wait for the
+    /// condition.
+    pub fn apply(&self, settings: &mut TaskSettings) {
+        match self {
+            Condition::Network(network) => {
+                settings.run_only_if_network_available = true;
+                settings.network_settings = network.clone();
+            }
+            Condition::Idle(idle) => {
+                settings.run_only_if_idle = true;
+                settings.idle_settings = *idle;
+            }
+        }
+    }
+}
+
+/// A command line that could not be understood.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    /// Neither `--create` nor `--help` was given.
+    NoCommand,
+    /// An option this program does not know.
+    UnknownOption(String),
+    /// An option given without its value.
+    MissingValue(&'static str),
+    /// A required option that was not given.
+    MissingOption(&'static str),
+    /// An option given more than once.
+    DuplicateOption(&'static str),
+    /// An option value that could not be parsed.
+    InvalidValue { option: &'static str, value: String, expected: &'static str },
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::NoCommand => write!(f, "nothing to do; pass --create or --help"),
+            CliError::UnknownOption(option) => write!(f, "unknown option {:?}", option),
+            CliError::MissingValue(option) => write!(f, "{} needs a value", option),
+            CliError::MissingOption(option) => write!(f, "{} is required", option),
+            CliError::DuplicateOption(option) => write!(f, "{} is given more than once", option),
+            CliError::InvalidValue { option, value, expected } => {
+                write!(f, "invalid value {:?} for {}; expected {}", value, option, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Options that take a value, by name.
+const VALUE_OPTIONS: &[&str] = &["--name", "--folder", "--trigger", "--action", "--arguments", "--condition", "--start"];
+
+/// Parses the arguments that follow the program name.
+pub fn parse_args<I, S>(args: I) -> Result<Command, CliError>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let mut create = false;
+    let mut values: Vec<(&'static str, String)> = Vec::new();
+    let mut args = args.into_iter().map(Into::into);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--help" | "-h" => return Ok(Command::Help),
+            "--create" => create = true,
+            _ => {
+                let Some(&option) = VALUE_OPTIONS.iter().find(|&&option| option == arg) else {
+                    return Err(CliError::UnknownOption(arg));
+                };
+                let value = args.next().ok_or(CliError::MissingValue(option))?;
+                if option != "--condition" && values.iter().any(|(name, _)| *name == option) {
+                    return Err(CliError::DuplicateOption(option));
+                }
+                values.push((option, value));
+            }
+        }
+    }
+    if !create {
+        return Err(CliError::NoCommand);
+    }
+
+    let value = |option: &str| values.iter().find(|(name, _)| *name == option).map(|(_, value)| value.as_str());
+    let name = value("--name").ok_or(CliError::MissingOption("--name"))?;
+    let path = value("--action").ok_or(CliError::MissingOption("--action"))?;
+
+    let mut trigger = match value("--trigger").unwrap_or("logon") {
+        "logon" => Trigger::logon(None),
+        "boot" => Trigger::boot(),
+        "idle" => Trigger::idle(),
+        "registration" => Trigger::registration(),
+        other => return Err(invalid("--trigger", other, "logon, boot, idle or registration")),
+    };
+    match value("--start") {
+        None | Some("now") => {}
+        Some(start) => {
+            let start: Timestamp = start.parse().map_err(|_| invalid("--start", start, "now or a date and time"))?;
+            trigger = trigger.starts_at(start);
+        }
+    }
+
+    let conditions = values
+        .iter()
+        .filter(|(name, _)| *name == "--condition")
+        .map(|(_, value)| {
+            Condition::parse(value).ok_or_else(|| invalid("--condition", value, CONDITIONS))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Command::Create(Box::new(CreateOptions {
+        name: name.to_string(),
+        folder: value("--folder").unwrap_or("\\").to_string(),
+        trigger,
+        action: Action::exec(path, value("--arguments")),
+        conditions,
+    })))
+}
+
+fn invalid(option: &'static str, value: &str, expected: &'static str) -> CliError {
+    CliError::InvalidValue { option, value: value.to_string(), expected }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::TaskDuration;
+
+    fn create(args: &[&str]) -> CreateOptions {
+        match parse_args(args.iter().copied()) {
+            Ok(Command::Create(options)) => *options,
+            other => panic!("expected --create, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_the_readme_example() {
+        let options = create(&[
+            "--create", "--name", "MyApp", "--trigger", "logon", "--action", "/usr/bin/myapp", "--condition", "network",
+            "--start", "now",
+        ]);
+
+        assert_eq!(options.name, "MyApp");
+        assert_eq!(options.folder, "\\");
+        assert_eq!(options.trigger, Trigger::logon(None));
+        assert_eq!(options.action, Action::exec("/usr/bin/myapp", None));
+        assert_eq!(options.conditions, [Condition::Network(None)]);
+
+        let task = options.to_task().unwrap();
+        assert!(task.definition.settings.run_only_if_network_available);
+        assert_eq!(task.definition.settings.network_settings, None);
+    }
+
+    #[test]
+    fn applies_network_and_idle_conditions() {
+        let options = create(&[
+            "--create", "--name", "Sync", "--action", "C:\\sync.exe", "--condition", "network:CorpNet", "--condition",
+            "idle:PT20M", "--start", "2024-06-01T09:00:00",
+        ]);
+
+        assert_eq!(options.trigger.start_boundary, Some("2024-06-01T09:00:00".parse().unwrap()));
+        let settings = options.to_task().unwrap().definition.settings;
+        assert!(settings.run_only_if_network_available);
+        assert_eq!(settings.network_settings.and_then(|n| n.name).as_deref(), Some("CorpNet"));
+        assert!(settings.run_only_if_idle);
+        assert_eq!(settings.idle_settings.idle_duration, TaskDuration::from_mins(20));
+
+        assert_eq!(
+            Condition::parse("network:{6D5DD0A9-3F6A-4D2E-9C43-1B0E5C7A8F21}"),
+            Some(Condition::Network(Some(NetworkSettings {
+                name: None,
+                id: Some("{6D5DD0A9-3F6A-4D2E-9C43-1B0E5C7A8F21}".to_string()),
+            })))
+        );
+        assert_eq!(Condition::parse("idle"), Some(Condition::Idle(IdleSettings::default())));
+    }
+
+    #[test]
+    fn parses_idle_options() {
+        assert_eq!(
+            Condition::parse("idle:PT5M,wait=PT2H,nostop,restart"),
+            Some(Condition::Idle(IdleSettings {
+                idle_duration: TaskDuration::from_mins(5),
+                wait_timeout: TaskDuration::from_hours(2),
+                stop_on_idle_end: false,
+                restart_on_idle: true,
+            }))
+        );
+        assert_eq!(
+            Condition::parse("idle,wait=PT30M"),
+            Some(Condition::Idle(IdleSettings { wait_timeout: TaskDuration::from_mins(30), ..IdleSettings::default() }))
+        );
+        for value in ["idle:", "idle:PT5M,", "idle,wait", "idle,wait=soon", "idle,pause", "idler", "idle:PT5M:PT1M"] {
+            assert_eq!(Condition::parse(value), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn reports_bad_command_lines() {
+        let cases: [(&[&str], CliError); 6] = [
+            (&["--name", "Task"], CliError::NoCommand),
+            (&["--create", "--frobnicate"], CliError::UnknownOption("--frobnicate".to_string())),
+            (&["--create", "--name"], CliError::MissingValue("--name")),
+            (&["--create", "--name", "Task"], CliError::MissingOption("--action")),
+            (&["--create", "--name", "A", "--name", "B"], CliError::DuplicateOption("--name")),
+            (
+                &["--create", "--name", "Task", "--action", "a.exe", "--condition", "battery"],
+                invalid("--condition", "battery", CONDITIONS),
+            ),
+        ];
+
+        for (args, error) in cases {
+            assert_eq!(parse_args(args.iter().copied()), Err(error), "{:?}", args);
+        }
+        assert_eq!(parse_args(["--create", "--help"]), Ok(Command::Help));
+    }
+}
@@ -0,0 +1,1459 @@
+// Windows Task Scheduler backend built on the Task Scheduler 2.0 COM API.
+// https://learn.microsoft.com/en-us/windows/win32/taskschd/logon-trigger-example--c---
+
+// Standard library imports
+use std::ffi::OsStr;
+use std::ops::Deref;
+use std::os::windows::ffi::OsStrExt;
+
+// Windows Registry
+use winreg::enums::*;
+use winreg::RegKey;
+
+// Windows API - COM
+use winapi::um::combaseapi::{
+    CoInitializeEx,
+    CoInitializeSecurity,
+    CoCreateInstance,
+    CoUninitialize,
+    CLSCTX_ALL,
+};
+use winapi::um::objbase::COINIT_MULTITHREADED;
+use winapi::um::oleauto::{
+    SafeArrayCreateVector,
+    SafeArrayDestroy,
+    SysAllocString,
+    SysFreeString,
+    SysStringLen,
+    VariantClear,
+    VariantInit,
+};
+use winapi::um::oaidl::{SAFEARRAY, VARIANT};
+use winapi::um::unknwnbase::IUnknown;
+use winapi::um::winbase::{FormatMessageW, FORMAT_MESSAGE_FROM_SYSTEM, FORMAT_MESSAGE_IGNORE_INSERTS};
+use winapi::um::winnt::HRESULT;
+use winapi::shared::wtypes::{BSTR, VARIANT_BOOL, VARIANT_FALSE, VARIANT_TRUE, VT_BSTR, VT_I4, VT_VARIANT};
+use winapi::Interface;
+
+// Windows API - RPC
+use winapi::shared::rpcdce::{
+    RPC_C_AUTHN_LEVEL_PKT_PRIVACY,
+    RPC_C_IMP_LEVEL_IMPERSONATE,
+};
+
+// Windows API - GUID
+use winapi::shared::guiddef::{
+    IID,
+    CLSID,
+    GUID,
+};
+use winapi::ctypes::c_void as c_void_winapi;
+
+// Windows API - Task Scheduler
+use winapi::um::taskschd::{
+    ITaskService,
+    ITaskFolder,
+    ITaskDefinition,
+    IRegistrationInfo,
+    ITaskSettings,
+    ITriggerCollection,
+    ITrigger,
+    IRepetitionPattern,
+    ITaskSettings2,
+    ITaskSettings3,
+    IMaintenanceSettings,
+    IIdleSettings,
+    INetworkSettings,
+    TASK_INSTANCES_PARALLEL,
+    TASK_INSTANCES_QUEUE,
+    TASK_INSTANCES_IGNORE_NEW,
+    TASK_INSTANCES_STOP_EXISTING,
+    TASK_COMPATIBILITY_AT,
+    TASK_COMPATIBILITY_V1,
+    TASK_COMPATIBILITY_V2,
+    TASK_COMPATIBILITY_V2_1,
+    TASK_COMPATIBILITY_V2_2,
+    TASK_COMPATIBILITY_V2_3,
+    TASK_COMPATIBILITY_V2_4,
+    TASK_TRIGGER_TIME,
+    TASK_TRIGGER_DAILY,
+    TASK_TRIGGER_WEEKLY,
+    TASK_TRIGGER_MONTHLY,
+    TASK_TRIGGER_MONTHLYDOW,
+    TASK_TRIGGER_EVENT,
+    TASK_TRIGGER_BOOT,
+    TASK_TRIGGER_IDLE,
+    TASK_TRIGGER_REGISTRATION,
+    TASK_TRIGGER_LOGON,
+    TASK_TRIGGER_SESSION_STATE_CHANGE,
+    TASK_CONSOLE_CONNECT,
+    TASK_CONSOLE_DISCONNECT,
+    TASK_REMOTE_CONNECT,
+    TASK_REMOTE_DISCONNECT,
+    TASK_SESSION_LOCK,
+    TASK_SESSION_UNLOCK,
+    TASK_SESSION_STATE_CHANGE_TYPE,
+    IBootTrigger,
+    IEventTrigger,
+    ITaskNamedValueCollection,
+    ITaskNamedValuePair,
+    IRegistrationTrigger,
+    ISessionStateChangeTrigger,
+    ITimeTrigger,
+    IDailyTrigger,
+    IWeeklyTrigger,
+    IMonthlyTrigger,
+    IMonthlyDOWTrigger,
+    ILogonTrigger,
+    IActionCollection,
+    IAction,
+    TASK_ACTION_EXEC,
+    TASK_ACTION_COM_HANDLER,
+    TASK_ACTION_SEND_EMAIL,
+    TASK_ACTION_SHOW_MESSAGE,
+    IExecAction,
+    IComHandlerAction,
+    IEmailAction,
+    IShowMessageAction,
+    IRegisteredTask,
+    IRegisteredTaskCollection,
+    IRunningTask,
+    TASK_CREATE,
+    TASK_UPDATE,
+    TASK_CREATE_OR_UPDATE,
+    TASK_ENUM_HIDDEN,
+    TASK_STATE,
+    TASK_STATE_UNKNOWN,
+    TASK_STATE_DISABLED,
+    TASK_STATE_QUEUED,
+    TASK_STATE_READY,
+    TASK_STATE_RUNNING,
+    IPrincipal,
+    IPrincipal2,
+    TASK_PROCESSTOKENSID_NONE,
+    TASK_PROCESSTOKENSID_UNRESTRICTED,
+    TASK_PROCESSTOKENSID_DEFAULT,
+    TASK_LOGON_TYPE,
+    TASK_LOGON_PASSWORD,
+    TASK_LOGON_S4U,
+    TASK_LOGON_INTERACTIVE_TOKEN,
+    TASK_LOGON_INTERACTIVE_TOKEN_OR_PASSWORD,
+    TASK_LOGON_GROUP,
+    TASK_LOGON_SERVICE_ACCOUNT,
+    TASK_RUNLEVEL_LUA,
+    TASK_RUNLEVEL_HIGHEST,
+};
+
+use crate::backend::{split_task_path, RegisterMode, RegisteredTask, SchedulerBackend, TaskFolder, TaskState};
+use crate::error::{ComError, SchtaskError};
+use crate::hresult::HResult;
+use crate::xml::SchemaVersion;
+use crate::model::{
+    Action,
+    ActionKind,
+    ComHandlerAction,
+    Compatibility,
+    EmailAction,
+    ExecAction,
+    IdleSettings,
+    LogonType,
+    MaintenanceSettings,
+    MultipleInstancesPolicy,
+    NetworkSettings,
+    Principal,
+    ProcessTokenSidType,
+    RegisteredTaskInfo,
+    RegistrationInfo,
+    RunLevel,
+    SessionStateChange,
+    ShowMessageAction,
+    Task,
+    TaskDefinition,
+    TaskDuration,
+    TaskSettings,
+    Trigger,
+    TriggerKind,
+    ValidationError,
+    validate_folder,
+};
+
+/// Owned COM interface pointer, released when dropped.
+struct ComPtr<T: Interface>(*mut T);
+
+impl<T: Interface> ComPtr<T> {
+    /// Takes ownership of a pointer filled in by a COM call that returned `hr`.
+    fn from_out(hr: HRESULT, ptr: *mut T) -> Result<Self, HRESULT> {
+        if hr != 0 || ptr.is_null() {
+            return Err(hr);
+        }
+        Ok(ComPtr(ptr))
+    }
+
+    /// Queries the object for another interface it implements.
+    fn cast<U: Interface>(&self) -> Result<ComPtr<U>, HRESULT> {
+        let mut ptr: *mut U = std::ptr::null_mut();
+        let hr = unsafe {
+            (*(self.0 as *mut IUnknown)).QueryInterface(
+                &U::uuidof(),
+                &mut ptr as *mut *mut U as *mut *mut c_void_winapi
+            )
+        };
+        ComPtr::from_out(hr, ptr)
+    }
+
+    fn as_ptr(&self) -> *mut T {
+        self.0
+    }
+}
+
+impl<T: Interface> Deref for ComPtr<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.0 }
+    }
+}
+
+impl<T: Interface> Drop for ComPtr<T> {
+    fn drop(&mut self) {
+        unsafe { (*(self.0 as *mut IUnknown)).Release() };
+    }
+}
+
+/// Keeps COM initialized on this thread until dropped.
+struct ComApartment;
+
+impl ComApartment {
+    fn init() -> Result<Self, SchtaskError> {
+        // Try to initialize COM with multithreaded apartment, but don't fail if it's already initialized
+        let result = unsafe { CoInitializeEx(
+            std::ptr::null_mut(),
+            COINIT_MULTITHREADED
+        )};
+
+        // S_FALSE means COM was already initialized
+        if result != HResult::S_OK.0 && result != HResult::S_FALSE.0 {
+            return Err(fail(SchtaskError::ComInit, result, "Failed to initialize COM"));
+        }
+        let apartment = ComApartment;
+
+        // Try to initialize security, but don't fail if it's already initialized
+        let result = unsafe { CoInitializeSecurity(
+            std::ptr::null_mut(),  // pSecDesc
+            -1,                    // cAuthSvc
+            std::ptr::null_mut(),  // asAuthSvc
+            std::ptr::null_mut(),  // pReserved1
+            RPC_C_AUTHN_LEVEL_PKT_PRIVACY,  // dwAuthnLevel
+            RPC_C_IMP_LEVEL_IMPERSONATE,    // dwImpLevel
+            std::ptr::null_mut(),  // pAuthList
+            0,                     // dwCapabilities
+            std::ptr::null_mut(),  // pReserved3
+        )};
+
+        // RPC_E_TOO_LATE means security was already initialized
+        if result != HResult::S_OK.0 && result != HResult::RPC_E_TOO_LATE.0 {
+            return Err(fail(SchtaskError::SecurityInit, result, "Failed to initialize COM security"));
+        }
+
+        Ok(apartment)
+    }
+}
+
+impl Drop for ComApartment {
+    fn drop(&mut self) {
+        unsafe { CoUninitialize() };
+    }
+}
+
+/// Converts a string to a NUL-terminated wide string (UTF-16).
+pub(crate) fn to_wide(value: &str) -> Vec<u16> {
+    OsStr::new(value)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect()
+}
+
+/// Passes `value`, formatted as an ISO 8601 duration, to the property
+/// setter `put` if it is set.
+fn put_duration(
+    value: Option<TaskDuration>,
+    stage: Stage,
+    context: &str,
+    put: impl FnOnce(*mut u16) -> HRESULT,
+) -> Result<(), SchtaskError> {
+    match value {
+        Some(value) => {
+            let wide = to_wide(&value.to_string());
+            check(put(wide.as_ptr() as *mut u16), stage, context)
+        }
+        None => Ok(()),
+    }
+}
+
+fn to_variant_bool(value: bool) -> VARIANT_BOOL {
+    if value { VARIANT_TRUE } else { VARIANT_FALSE }
+}
+
+/// The `SchtaskError` variant for the stage a call belongs to.
+type Stage = fn(ComError) -> SchtaskError;
+
+/// Builds the error for a call that returned `hr` while doing `context`.
+fn fail(stage: Stage, hr: HRESULT, context: &str) -> SchtaskError {
+    // A successful call that left its out pointer null is reported as E_POINTER
+    let hr = if hr == 0 { HResult::E_POINTER } else { HResult(hr) };
+    stage(ComError::new(hr, context, &decode(hr)))
+}
+
+/// Turns a failed HRESULT into the error for `context`.
+fn check(hr: HRESULT, stage: Stage, context: &str) -> Result<(), SchtaskError> {
+    if hr != 0 {
+        return Err(fail(stage, hr, context));
+    }
+    Ok(())
+}
+
+/// Explains `hr`, preferring the built-in table over the system message
+/// table, which lacks Task Scheduler's own codes.
+pub(crate) fn decode(hr: HResult) -> String {
+    match hr.message() {
+        Some(message) => message.to_string(),
+        None => system_message(hr).unwrap_or_else(|| hr.describe()),
+    }
+}
+
+/// Looks up the system message for `hr`, without the trailing line break.
+fn system_message(hr: HResult) -> Option<String> {
+    let mut buffer = [0u16; 512];
+    let len = unsafe {
+        FormatMessageW(
+            FORMAT_MESSAGE_FROM_SYSTEM | FORMAT_MESSAGE_IGNORE_INSERTS,
+            std::ptr::null(),
+            hr.as_u32(),
+            0,
+            buffer.as_mut_ptr(),
+            buffer.len() as u32,
+            std::ptr::null_mut()
+        )
+    };
+    if len == 0 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&buffer[..len as usize]).trim_end().to_string())
+}
+
+/// Copies a BSTR returned by a COM call into a `String` and frees it.
+fn from_bstr(value: BSTR) -> String {
+    if value.is_null() {
+        return String::new();
+    }
+    let text = unsafe {
+        let len = SysStringLen(value) as usize;
+        String::from_utf16_lossy(std::slice::from_raw_parts(value, len))
+    };
+    unsafe { SysFreeString(value) };
+    text
+}
+
+/// Task Scheduler on the local computer, reached through `ITaskService`.
+///
+/// COM stays initialized on the connecting thread for as long as the
+/// backend lives, so it must be used from that thread.
+pub struct ComBackend {
+    service: ComPtr<ITaskService>,
+    // Declared last so that COM is uninitialized after the service is released
+    _apartment: ComApartment,
+}
+
+impl ComBackend {
+    fn folder(&self, path: &str) -> Result<ComPtr<ITaskFolder>, SchtaskError> {
+        let path_wide = to_wide(path);
+        let mut p_folder: *mut ITaskFolder = std::ptr::null_mut();
+        let hr = unsafe { self.service.GetFolder(path_wide.as_ptr() as *mut u16, &mut p_folder) };
+        ComPtr::from_out(hr, p_folder)
+            .map_err(|hr| fail(SchtaskError::Folder, hr, &format!("Cannot open the folder {}", path)))
+    }
+
+    fn task(&self, path: &str) -> Result<ComPtr<IRegisteredTask>, SchtaskError> {
+        let (folder, name) = split_task_path(path);
+        let task_folder = self.folder(folder)?;
+        let name_wide = to_wide(name);
+        let mut p_task: *mut IRegisteredTask = std::ptr::null_mut();
+        let hr = unsafe { task_folder.GetTask(name_wide.as_ptr() as *mut u16, &mut p_task) };
+        ComPtr::from_out(hr, p_task)
+            .map_err(|hr| fail(SchtaskError::Task, hr, &format!("Cannot open the task {}", path)))
+    }
+
+    /// Builds an `ITaskDefinition` holding everything in `definition`.
+    fn new_definition(&self, definition: &TaskDefinition) -> Result<ComPtr<ITaskDefinition>, SchtaskError> {
+        let mut p_task: *mut ITaskDefinition = std::ptr::null_mut();
+        let hr = unsafe { self.service.NewTask(0, &mut p_task) };
+        let new_task = ComPtr::from_out(hr, p_task)
+            .map_err(|hr| fail(SchtaskError::Definition, hr, "Failed to create a task definition"))?;
+
+        // IEventTrigger cannot count occurrences, so such tasks are loaded from their XML
+        let counts_events = definition.triggers.iter().any(|trigger| match &trigger.kind {
+            TriggerKind::Event(event) => event.counts_occurrences(),
+            _ => false,
+        });
+        if counts_events {
+            let xml_wide = to_wide(&definition.to_xml(SchemaVersion::V1_4));
+            let hr = unsafe { new_task.put_XmlText(xml_wide.as_ptr() as *mut u16) };
+            check(hr, SchtaskError::Definition, "Cannot load the task definition from XML")?;
+            return Ok(new_task);
+        }
+
+        apply_registration_info(&new_task, &definition.registration_info)?;
+        apply_principal(&new_task, &definition.principal)?;
+        apply_settings(&new_task, &definition.settings)?;
+
+        // Get the trigger collection to insert the triggers
+        let mut p_trigger_collection: *mut ITriggerCollection = std::ptr::null_mut();
+        let hr = unsafe { new_task.get_Triggers(&mut p_trigger_collection) };
+        let trigger_collection = ComPtr::from_out(hr, p_trigger_collection)
+            .map_err(|hr| fail(SchtaskError::Trigger, hr, "Cannot get trigger collection"))?;
+
+        for trigger in &definition.triggers {
+            add_trigger(&trigger_collection, trigger)?;
+        }
+
+        // Get the task action collection
+        let mut p_action_collection: *mut IActionCollection = std::ptr::null_mut();
+        let hr = unsafe { new_task.get_Actions(&mut p_action_collection) };
+        let action_collection = ComPtr::from_out(hr, p_action_collection)
+            .map_err(|hr| fail(SchtaskError::Action, hr, "Cannot get Task collection pointer"))?;
+
+        for action in &definition.actions {
+            add_action(&action_collection, action)?;
+        }
+
+        Ok(new_task)
+    }
+
+    /// Registers `task` for an account whose password Task Scheduler
+    /// stores, as the [`Password`](LogonType::Password) and
+    /// [`InteractiveTokenOrPassword`](LogonType::InteractiveTokenOrPassword)
+    /// logon types need. [`register`](SchedulerBackend::register) rejects
+    /// those logon types.
+    pub fn register_with_password(
+        &mut self,
+        task: &Task,
+        mode: RegisterMode,
+        password: &str,
+    ) -> Result<RegisteredTaskInfo, SchtaskError> {
+        self.register_task(task, mode, Some(password))
+    }
+
+    fn register_task(
+        &mut self,
+        task: &Task,
+        mode: RegisterMode,
+        password: Option<&str>,
+    ) -> Result<RegisteredTaskInfo, SchtaskError> {
+        task.validate()?;
+        let principal = &task.definition.principal;
+        // Task Scheduler would only fail later, with a less helpful error
+        if principal.effective_logon_type().needs_password() && password.is_none() {
+            return Err(ValidationError::MissingPassword(principal.effective_logon_type()).into());
+        }
+        let task_folder = self.folder(&task.folder)?;
+        let new_task = self.new_definition(&task.definition)?;
+
+        let flags = match mode {
+            RegisterMode::Create => TASK_CREATE,
+            RegisterMode::Update => TASK_UPDATE,
+            RegisterMode::CreateOrUpdate => TASK_CREATE_OR_UPDATE,
+        };
+
+        // The principal's user or group registers the task; empty variants
+        // select the current user, no password and the default security descriptor
+        let user = bstr_variant(principal.user_id.as_ref().or(principal.group_id.as_ref()));
+        let password = bstr_variant(password.map(str::to_string).as_ref());
+        let empty_variant = bstr_variant(None);
+        let logon_type = logon_type(principal.effective_logon_type());
+        let task_name_wide = to_wide(&task.name);
+
+        let mut p_registered_task: *mut IRegisteredTask = std::ptr::null_mut();
+        let hr = unsafe {
+            task_folder.RegisterTaskDefinition(
+                task_name_wide.as_ptr() as *mut u16,
+                new_task.as_ptr(),
+                flags as i32,
+                user.0,
+                password.0,
+                logon_type,
+                empty_variant.0,
+                &mut p_registered_task
+            )
+        };
+        let registered_task = ComPtr::from_out(hr, p_registered_task)
+            .map_err(|hr| fail(SchtaskError::Register, hr, "Error saving the Task"))?;
+
+        // Read back where the task ended up
+        let mut name: BSTR = std::ptr::null_mut();
+        let mut path: BSTR = std::ptr::null_mut();
+        unsafe {
+            registered_task.get_Name(&mut name);
+            registered_task.get_Path(&mut path);
+        }
+
+        Ok(RegisteredTaskInfo {
+            name: from_bstr(name),
+            path: from_bstr(path),
+        })
+    }
+}
+
+impl SchedulerBackend for ComBackend {
+    fn connect() -> Result<Self, SchtaskError> {
+        let apartment = ComApartment::init()?;
+
+        // Find the CLSID and IID dynamically
+        let (clsid, iid) = find_task_scheduler_guids()?;
+
+        // Create an instance of the Task Service
+        let mut p_service: *mut ITaskService = std::ptr::null_mut();
+        let hr = unsafe {
+            CoCreateInstance(
+                &clsid,
+                std::ptr::null_mut(),
+                CLSCTX_ALL,
+                &iid,
+                &mut p_service as *mut *mut ITaskService as *mut *mut c_void_winapi
+            )
+        };
+        let service = ComPtr::from_out(hr, p_service)
+            .map_err(|hr| fail(SchtaskError::Connect, hr, "Failed to create an instance of ITaskService"))?;
+
+        // Connect to the task service
+        let empty_variant = bstr_variant(None);
+        let hr = unsafe {
+            service.Connect(
+                empty_variant.0,  // serverName
+                empty_variant.0,  // user
+                empty_variant.0,  // domain
+                empty_variant.0   // password
+            )
+        };
+        check(hr, SchtaskError::Connect, "ITaskService::Connect failed")?;
+
+        Ok(ComBackend { service, _apartment: apartment })
+    }
+
+    fn get_folder(&self, path: &str) -> Result<TaskFolder, SchtaskError> {
+        let task_folder = self.folder(path)?;
+        let mut name: BSTR = std::ptr::null_mut();
+        let mut folder_path: BSTR = std::ptr::null_mut();
+        unsafe {
+            task_folder.get_Name(&mut name);
+            task_folder.get_Path(&mut folder_path);
+        }
+        Ok(TaskFolder { name: from_bstr(name), path: from_bstr(folder_path) })
+    }
+
+    fn create_folder(&mut self, path: &str) -> Result<TaskFolder, SchtaskError> {
+        validate_folder(path)?;
+        let root = self.folder("\\")?;
+        let path_wide = to_wide(path);
+        let empty_variant = bstr_variant(None);
+        let mut p_folder: *mut ITaskFolder = std::ptr::null_mut();
+        let hr = unsafe { root.CreateFolder(path_wide.as_ptr() as *mut u16, empty_variant.0, &mut p_folder) };
+        ComPtr::from_out(hr, p_folder)
+            .map_err(|hr| fail(SchtaskError::Folder, hr, &format!("Cannot create the folder {}", path)))?;
+        self.get_folder(path)
+    }
+
+    fn delete_folder(&mut self, path: &str) -> Result<(), SchtaskError> {
+        let (parent, name) = split_task_path(path);
+        let parent_folder = self.folder(parent)?;
+        let name_wide = to_wide(name);
+        let hr = unsafe { parent_folder.DeleteFolder(name_wide.as_ptr() as *mut u16, 0) };
+        check(hr, SchtaskError::Folder, &format!("Cannot delete the folder {}", path))
+    }
+
+    fn register(&mut self, task: &Task, mode: RegisterMode) -> Result<RegisteredTaskInfo, SchtaskError> {
+        self.register_task(task, mode, None)
+    }
+
+    fn get(&self, path: &str) -> Result<RegisteredTask, SchtaskError> {
+        read_registered_task(&self.task(path)?)
+    }
+
+    fn list(&self, folder: &str) -> Result<Vec<RegisteredTask>, SchtaskError> {
+        let task_folder = self.folder(folder)?;
+        let mut p_tasks: *mut IRegisteredTaskCollection = std::ptr::null_mut();
+        let hr = unsafe { task_folder.GetTasks(TASK_ENUM_HIDDEN as i32, &mut p_tasks) };
+        let tasks = ComPtr::from_out(hr, p_tasks)
+            .map_err(|hr| fail(SchtaskError::Folder, hr, &format!("Cannot list the tasks in {}", folder)))?;
+
+        let mut count = 0;
+        let hr = unsafe { tasks.get_Count(&mut count) };
+        check(hr, SchtaskError::Folder, "Cannot count the tasks")?;
+
+        // The collection is indexed from 1
+        let mut result = Vec::new();
+        for index in 1..=count {
+            let mut variant: VARIANT = unsafe { std::mem::zeroed() };
+            unsafe {
+                VariantInit(&mut variant);
+                let inner = variant.n1.n2_mut();
+                inner.vt = VT_I4 as u16;
+                *inner.n3.lVal_mut() = index;
+            }
+            let mut p_task: *mut IRegisteredTask = std::ptr::null_mut();
+            let hr = unsafe { tasks.get_Item(variant, &mut p_task) };
+            let task = ComPtr::from_out(hr, p_task)
+                .map_err(|hr| fail(SchtaskError::Task, hr, "Cannot get a task from the collection"))?;
+            result.push(read_registered_task(&task)?);
+        }
+        Ok(result)
+    }
+
+    fn run(&mut self, path: &str) -> Result<(), SchtaskError> {
+        let task = self.task(path)?;
+        let no_parameters = bstr_variant(None);
+        let mut p_running: *mut IRunningTask = std::ptr::null_mut();
+        let hr = unsafe { task.Run(no_parameters.0, &mut p_running) };
+        ComPtr::from_out(hr, p_running)
+            .map_err(|hr| fail(SchtaskError::Task, hr, &format!("Cannot run the task {}", path)))?;
+        Ok(())
+    }
+
+    fn stop(&mut self, path: &str) -> Result<(), SchtaskError> {
+        let hr = unsafe { self.task(path)?.Stop(0) };
+        check(hr, SchtaskError::Task, &format!("Cannot stop the task {}", path))
+    }
+
+    fn set_enabled(&mut self, path: &str, enabled: bool) -> Result<(), SchtaskError> {
+        let hr = unsafe { self.task(path)?.put_Enabled(to_variant_bool(enabled)) };
+        check(hr, SchtaskError::Task, &format!("Cannot enable or disable the task {}", path))
+    }
+
+    fn delete(&mut self, path: &str) -> Result<(), SchtaskError> {
+        let (folder, name) = split_task_path(path);
+        let task_folder = self.folder(folder)?;
+        let name_wide = to_wide(name);
+        let hr = unsafe { task_folder.DeleteTask(name_wide.as_ptr() as *mut u16, 0) };
+        check(hr, SchtaskError::Task, &format!("Cannot delete the task {}", path))
+    }
+}
+
+/// Reads a registered task's name, path, state and definition, the latter
+/// from its Task XML. Only failing to fetch the XML is an error; XML that
+/// does not parse is kept as the task's definition error.
+fn read_registered_task(task: &ComPtr<IRegisteredTask>) -> Result<RegisteredTask, SchtaskError> {
+    let mut name: BSTR = std::ptr::null_mut();
+    let mut path: BSTR = std::ptr::null_mut();
+    let mut xml: BSTR = std::ptr::null_mut();
+    let mut state: TASK_STATE = TASK_STATE_UNKNOWN;
+    unsafe {
+        task.get_Name(&mut name);
+        task.get_Path(&mut path);
+        task.get_State(&mut state);
+    }
+    let (name, path) = (from_bstr(name), from_bstr(path));
+
+    let hr = unsafe { task.get_Xml(&mut xml) };
+    check(hr, SchtaskError::Task, &format!("Cannot read the task {}", path))?;
+
+    let state = match state {
+        TASK_STATE_DISABLED => TaskState::Disabled,
+        TASK_STATE_QUEUED => TaskState::Queued,
+        TASK_STATE_READY => TaskState::Ready,
+        TASK_STATE_RUNNING => TaskState::Running,
+        _ => TaskState::Unknown,
+    };
+    // A definition this crate cannot read must not hide the task
+    Ok(RegisteredTask::from_xml(name, path, state, &from_bstr(xml)))
+}
+
+/// A VARIANT holding a BSTR, or an empty VARIANT, freed when dropped.
+struct BstrVariant(VARIANT);
+
+impl Drop for BstrVariant {
+    fn drop(&mut self) {
+        unsafe { VariantClear(&mut self.0) };
+    }
+}
+
+fn bstr_variant(value: Option<&String>) -> BstrVariant {
+    let mut variant: VARIANT = unsafe { std::mem::zeroed() };
+    unsafe { VariantInit(&mut variant) };
+    if let Some(value) = value {
+        let value_wide = to_wide(value);
+        unsafe {
+            let inner = variant.n1.n2_mut();
+            inner.vt = VT_BSTR as u16;
+            *inner.n3.bstrVal_mut() = SysAllocString(value_wide.as_ptr());
+        }
+    }
+    BstrVariant(variant)
+}
+
+fn logon_type(logon_type: LogonType) -> TASK_LOGON_TYPE {
+    match logon_type {
+        LogonType::Password => TASK_LOGON_PASSWORD,
+        LogonType::S4U => TASK_LOGON_S4U,
+        LogonType::InteractiveToken => TASK_LOGON_INTERACTIVE_TOKEN,
+        LogonType::InteractiveTokenOrPassword => TASK_LOGON_INTERACTIVE_TOKEN_OR_PASSWORD,
+        LogonType::Group => TASK_LOGON_GROUP,
+        LogonType::ServiceAccount => TASK_LOGON_SERVICE_ACCOUNT,
+    }
+}
+
+fn apply_principal(task: &ComPtr<ITaskDefinition>, principal: &Principal) -> Result<(), SchtaskError> {
+    let mut p_principal: *mut IPrincipal = std::ptr::null_mut();
+    let hr = unsafe { task.get_Principal(&mut p_principal) };
+    let task_principal = ComPtr::from_out(hr, p_principal)
+        .map_err(|hr| fail(SchtaskError::Definition, hr, "Cannot get principal pointer"))?;
+
+    let fields: [(&Option<String>, &str, PrincipalSetter); 3] = [
+        (&principal.user_id, "Cannot put the principal user ID", IPrincipal::put_UserId),
+        (&principal.group_id, "Cannot put the principal group ID", IPrincipal::put_GroupId),
+        (&principal.display_name, "Cannot put the principal display name", IPrincipal::put_DisplayName),
+    ];
+    for (value, context, put) in fields {
+        if let Some(value) = value {
+            let value_wide = to_wide(value);
+            let hr = unsafe { put(&task_principal, value_wide.as_ptr() as *mut u16) };
+            check(hr, SchtaskError::Definition, context)?;
+        }
+    }
+
+    let hr = unsafe { task_principal.put_LogonType(logon_type(principal.effective_logon_type())) };
+    check(hr, SchtaskError::Definition, "Cannot put the principal logon type")?;
+
+    let run_level = match principal.run_level.unwrap_or_default() {
+        RunLevel::LeastPrivilege => TASK_RUNLEVEL_LUA,
+        RunLevel::Highest => TASK_RUNLEVEL_HIGHEST,
+    };
+    let hr = unsafe { task_principal.put_RunLevel(run_level) };
+    check(hr, SchtaskError::Definition, "Cannot put the principal run level")?;
+
+    // IPrincipal2 only exists from Windows 7 on, so leave it alone unless needed
+    if principal.process_token_sid_type.is_none() && principal.required_privileges.is_empty() {
+        return Ok(());
+    }
+    let principal2 = task_principal
+        .cast::<IPrincipal2>()
+        .map_err(|hr| fail(SchtaskError::Definition, hr, "QueryInterface call failed for IPrincipal2"))?;
+    if let Some(sid_type) = principal.process_token_sid_type {
+        let sid_type = match sid_type {
+            ProcessTokenSidType::None => TASK_PROCESSTOKENSID_NONE,
+            ProcessTokenSidType::Unrestricted => TASK_PROCESSTOKENSID_UNRESTRICTED,
+            ProcessTokenSidType::Default => TASK_PROCESSTOKENSID_DEFAULT,
+        };
+        let hr = unsafe { principal2.put_ProcessTokenSidType(sid_type) };
+        check(hr, SchtaskError::Definition, "Cannot put the process token SID type")?;
+    }
+    for privilege in &principal.required_privileges {
+        let privilege_wide = to_wide(privilege);
+        let hr = unsafe { principal2.AddRequiredPrivilege(privilege_wide.as_ptr() as *mut u16) };
+        check(hr, SchtaskError::Definition, "Cannot add a required privilege")?;
+    }
+    Ok(())
+}
+
+/// One of the `IPrincipal::put_*` string setters.
+type PrincipalSetter = unsafe fn(&IPrincipal, *mut u16) -> HRESULT;
+
+/// One of the `IRegistrationInfo::put_*` string setters.
+type RegistrationInfoSetter = unsafe fn(&IRegistrationInfo, *mut u16) -> HRESULT;
+
+fn apply_registration_info(task: &ComPtr<ITaskDefinition>, info: &RegistrationInfo) -> Result<(), SchtaskError> {
+    // Get the registration info for setting the identification
+    let mut p_reg_info: *mut IRegistrationInfo = std::ptr::null_mut();
+    let hr = unsafe { task.get_RegistrationInfo(&mut p_reg_info) };
+    let reg_info = ComPtr::from_out(hr, p_reg_info)
+        .map_err(|hr| fail(SchtaskError::Definition, hr, "Cannot get identification pointer"))?;
+
+    let fields: [(&Option<String>, RegistrationInfoSetter); 7] = [
+        (&info.author, IRegistrationInfo::put_Author),
+        (&info.description, IRegistrationInfo::put_Description),
+        (&info.version, IRegistrationInfo::put_Version),
+        (&info.date, IRegistrationInfo::put_Date),
+        (&info.documentation, IRegistrationInfo::put_Documentation),
+        (&info.source, IRegistrationInfo::put_Source),
+        (&info.uri, IRegistrationInfo::put_URI),
+    ];
+
+    for (value, put) in fields {
+        if let Some(value) = value {
+            let value_wide = to_wide(value);
+            let hr = unsafe { put(&reg_info, value_wide.as_ptr() as *mut u16) };
+            check(hr, SchtaskError::Definition, "Cannot put identification info")?;
+        }
+    }
+
+    if let Some(sddl) = &info.security_descriptor {
+        let sddl = bstr_variant(Some(sddl));
+        let hr = unsafe { reg_info.put_SecurityDescriptor(sddl.0) };
+        check(hr, SchtaskError::Definition, "Cannot put the security descriptor")?;
+    }
+
+    Ok(())
+}
+
+/// One of the `ITaskSettings::put_*` boolean setters.
+type SettingsFlagSetter = unsafe fn(&ITaskSettings, VARIANT_BOOL) -> HRESULT;
+
+fn apply_settings(task: &ComPtr<ITaskDefinition>, settings: &TaskSettings) -> Result<(), SchtaskError> {
+    // Create the settings for the task
+    let mut p_settings: *mut ITaskSettings = std::ptr::null_mut();
+    let hr = unsafe { task.get_Settings(&mut p_settings) };
+    let task_settings = ComPtr::from_out(hr, p_settings)
+        .map_err(|hr| fail(SchtaskError::Definition, hr, "Cannot get settings pointer"))?;
+
+    // Set setting values for the task
+    let flags: [(bool, SettingsFlagSetter); 10] = [
+        (settings.allow_demand_start, ITaskSettings::put_AllowDemandStart),
+        (settings.allow_hard_terminate, ITaskSettings::put_AllowHardTerminate),
+        (settings.start_when_available, ITaskSettings::put_StartWhenAvailable),
+        (settings.stop_if_going_on_batteries, ITaskSettings::put_StopIfGoingOnBatteries),
+        (settings.disallow_start_if_on_batteries, ITaskSettings::put_DisallowStartIfOnBatteries),
+        (settings.run_only_if_network_available, ITaskSettings::put_RunOnlyIfNetworkAvailable),
+        (settings.run_only_if_idle, ITaskSettings::put_RunOnlyIfIdle),
+        (settings.wake_to_run, ITaskSettings::put_WakeToRun),
+        (settings.hidden, ITaskSettings::put_Hidden),
+        (settings.enabled, ITaskSettings::put_Enabled),
+    ];
+    for (value, put) in flags {
+        let hr = unsafe { put(&task_settings, to_variant_bool(value)) };
+        check(hr, SchtaskError::Definition, "Cannot put setting info")?;
+    }
+
+    let policy = match settings.multiple_instances {
+        MultipleInstancesPolicy::Parallel => TASK_INSTANCES_PARALLEL,
+        MultipleInstancesPolicy::Queue => TASK_INSTANCES_QUEUE,
+        MultipleInstancesPolicy::IgnoreNew => TASK_INSTANCES_IGNORE_NEW,
+        MultipleInstancesPolicy::StopExisting => TASK_INSTANCES_STOP_EXISTING,
+    };
+    let hr = unsafe { task_settings.put_MultipleInstances(policy) };
+    check(hr, SchtaskError::Definition, "Cannot put the multiple instances policy")?;
+
+    let hr = unsafe { task_settings.put_Priority(settings.priority.into()) };
+    check(hr, SchtaskError::Definition, "Cannot put the priority")?;
+
+    if let Some(restart) = &settings.restart_on_failure {
+        let hr = unsafe { task_settings.put_RestartCount(restart.count.into()) };
+        check(hr, SchtaskError::Definition, "Cannot put the restart count")?;
+        put_duration(Some(restart.interval), SchtaskError::Definition, "Cannot put the restart interval", |v| unsafe {
+            task_settings.put_RestartInterval(v)
+        })?;
+    }
+
+    put_duration(
+        Some(settings.execution_time_limit),
+        SchtaskError::Definition,
+        "Cannot put the execution time limit",
+        |v| unsafe { task_settings.put_ExecutionTimeLimit(v) },
+    )?;
+    put_duration(
+        settings.delete_expired_task_after,
+        SchtaskError::Definition,
+        "Cannot put the expired task deletion delay",
+        |v| unsafe { task_settings.put_DeleteExpiredTaskAfter(v) },
+    )?;
+
+    let compatibility = match settings.compatibility {
+        Compatibility::At => TASK_COMPATIBILITY_AT,
+        Compatibility::V1 => TASK_COMPATIBILITY_V1,
+        Compatibility::V2 => TASK_COMPATIBILITY_V2,
+        Compatibility::V2_1 => TASK_COMPATIBILITY_V2_1,
+        Compatibility::V2_2 => TASK_COMPATIBILITY_V2_2,
+        Compatibility::V2_3 => TASK_COMPATIBILITY_V2_3,
+        Compatibility::V2_4 => TASK_COMPATIBILITY_V2_4,
+    };
+    let hr = unsafe { task_settings.put_Compatibility(compatibility) };
+    check(hr, SchtaskError::Definition, "Cannot put the compatibility level")?;
+
+    apply_idle_settings(&task_settings, &settings.idle_settings)?;
+    if let Some(network) = &settings.network_settings {
+        apply_network_settings(&task_settings, network)?;
+    }
+
+    // ITaskSettings2 only exists from Windows 7 on, so leave it alone unless needed
+    if settings.use_unified_scheduling_engine || settings.disallow_start_on_remote_app_session {
+        let settings2 = task_settings
+            .cast::<ITaskSettings2>()
+            .map_err(|hr| fail(SchtaskError::Definition, hr, "QueryInterface call failed for ITaskSettings2"))?;
+        let hr = unsafe { settings2.put_UseUnifiedSchedulingEngine(to_variant_bool(settings.use_unified_scheduling_engine)) };
+        check(hr, SchtaskError::Definition, "Cannot put the unified scheduling engine setting")?;
+        let hr = unsafe {
+            settings2.put_DisallowStartOnRemoteAppSession(to_variant_bool(settings.disallow_start_on_remote_app_session))
+        };
+        check(hr, SchtaskError::Definition, "Cannot put the remote application session setting")?;
+    }
+
+    // Likewise ITaskSettings3, from Windows 8 on
+    if settings.volatile || settings.maintenance_settings.is_some() {
+        let settings3 = task_settings
+            .cast::<ITaskSettings3>()
+            .map_err(|hr| fail(SchtaskError::Definition, hr, "QueryInterface call failed for ITaskSettings3"))?;
+        let hr = unsafe { settings3.put_Volatile(to_variant_bool(settings.volatile)) };
+        check(hr, SchtaskError::Definition, "Cannot put the volatile setting")?;
+        if let Some(maintenance) = &settings.maintenance_settings {
+            apply_maintenance_settings(&settings3, maintenance)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn apply_maintenance_settings(
+    settings3: &ComPtr<ITaskSettings3>,
+    maintenance: &MaintenanceSettings,
+) -> Result<(), SchtaskError> {
+    let mut p_maintenance: *mut IMaintenanceSettings = std::ptr::null_mut();
+    let hr = unsafe { settings3.CreateMaintenanceSettings(&mut p_maintenance) };
+    let maintenance_settings = ComPtr::from_out(hr, p_maintenance)
+        .map_err(|hr| fail(SchtaskError::Definition, hr, "Cannot create maintenance settings"))?;
+
+    put_duration(Some(maintenance.period), SchtaskError::Definition, "Cannot put the maintenance period", |v| unsafe {
+        maintenance_settings.put_Period(v)
+    })?;
+    put_duration(maintenance.deadline, SchtaskError::Definition, "Cannot put the maintenance deadline", |v| unsafe {
+        maintenance_settings.put_Deadline(v)
+    })?;
+    let hr = unsafe { maintenance_settings.put_Exclusive(to_variant_bool(maintenance.exclusive)) };
+    check(hr, SchtaskError::Definition, "Cannot put the maintenance exclusivity")
+}
+
+/// One of the `INetworkSettings::put_*` string setters.
+type NetworkSettingsSetter = unsafe fn(&INetworkSettings, *mut u16) -> HRESULT;
+
+fn apply_network_settings(task_settings: &ComPtr<ITaskSettings>, network: &NetworkSettings) -> Result<(), SchtaskError> {
+    let mut p_network: *mut INetworkSettings = std::ptr::null_mut();
+    let hr = unsafe { task_settings.get_NetworkSettings(&mut p_network) };
+    let network_settings = ComPtr::from_out(hr, p_network)
+        .map_err(|hr| fail(SchtaskError::Definition, hr, "Cannot get network settings pointer"))?;
+
+    let fields: [(&Option<String>, &str, NetworkSettingsSetter); 2] = [
+        (&network.name, "Cannot put the network name", INetworkSettings::put_Name),
+        (&network.id, "Cannot put the network id", INetworkSettings::put_Id),
+    ];
+    for (value, context, put) in fields {
+        if let Some(value) = value {
+            let value_wide = to_wide(value);
+            let hr = unsafe { put(&network_settings, value_wide.as_ptr() as *mut u16) };
+            check(hr, SchtaskError::Definition, context)?;
+        }
+    }
+    Ok(())
+}
+
+fn apply_idle_settings(task_settings: &ComPtr<ITaskSettings>, idle: &IdleSettings) -> Result<(), SchtaskError> {
+    let mut p_idle: *mut IIdleSettings = std::ptr::null_mut();
+    let hr = unsafe { task_settings.get_IdleSettings(&mut p_idle) };
+    let idle_settings = ComPtr::from_out(hr, p_idle)
+        .map_err(|hr| fail(SchtaskError::Definition, hr, "Cannot get idle settings pointer"))?;
+
+    put_duration(Some(idle.idle_duration), SchtaskError::Definition, "Cannot put the idle duration", |v| unsafe {
+        idle_settings.put_IdleDuration(v)
+    })?;
+    put_duration(Some(idle.wait_timeout), SchtaskError::Definition, "Cannot put the idle wait timeout", |v| unsafe {
+        idle_settings.put_WaitTimeout(v)
+    })?;
+    let hr = unsafe { idle_settings.put_StopOnIdleEnd(to_variant_bool(idle.stop_on_idle_end)) };
+    check(hr, SchtaskError::Definition, "Cannot put idle setting info")?;
+    let hr = unsafe { idle_settings.put_RestartOnIdle(to_variant_bool(idle.restart_on_idle)) };
+    check(hr, SchtaskError::Definition, "Cannot put idle setting info")
+}
+
+fn add_trigger(collection: &ComPtr<ITriggerCollection>, trigger: &Trigger) -> Result<(), SchtaskError> {
+    let trigger_type = match trigger.kind {
+        TriggerKind::Logon(_) => TASK_TRIGGER_LOGON,
+        TriggerKind::Boot => TASK_TRIGGER_BOOT,
+        TriggerKind::Idle => TASK_TRIGGER_IDLE,
+        TriggerKind::Registration => TASK_TRIGGER_REGISTRATION,
+        TriggerKind::SessionStateChange(_) => TASK_TRIGGER_SESSION_STATE_CHANGE,
+        TriggerKind::Event(_) => TASK_TRIGGER_EVENT,
+        TriggerKind::Time => TASK_TRIGGER_TIME,
+        TriggerKind::Daily(_) => TASK_TRIGGER_DAILY,
+        TriggerKind::Weekly(_) => TASK_TRIGGER_WEEKLY,
+        TriggerKind::Monthly(_) => TASK_TRIGGER_MONTHLY,
+        TriggerKind::MonthlyDayOfWeek(_) => TASK_TRIGGER_MONTHLYDOW,
+    };
+
+    let mut p_trigger: *mut ITrigger = std::ptr::null_mut();
+    let hr = unsafe { collection.Create(trigger_type, &mut p_trigger) };
+    let new_trigger = ComPtr::from_out(hr, p_trigger)
+        .map_err(|hr| fail(SchtaskError::Trigger, hr, "Cannot create the trigger"))?;
+
+    // Set the trigger ID
+    if let Some(id) = &trigger.id {
+        let id_wide = to_wide(id);
+        let hr = unsafe { new_trigger.put_Id(id_wide.as_ptr() as *mut u16) };
+        check(hr, SchtaskError::Trigger, "Cannot put the trigger ID")?;
+    }
+
+    // Set the window in which the trigger is active
+    if let Some(start_boundary) = &trigger.start_boundary {
+        let start_wide = to_wide(&start_boundary.to_string());
+        let hr = unsafe { new_trigger.put_StartBoundary(start_wide.as_ptr() as *mut u16) };
+        check(hr, SchtaskError::Trigger, "Cannot put the start boundary")?;
+    }
+
+    if let Some(end_boundary) = &trigger.end_boundary {
+        let end_wide = to_wide(&end_boundary.to_string());
+        let hr = unsafe { new_trigger.put_EndBoundary(end_wide.as_ptr() as *mut u16) };
+        check(hr, SchtaskError::Trigger, "Cannot put the end boundary")?;
+    }
+
+    if let Some(repetition) = &trigger.repetition {
+        let mut p_repetition: *mut IRepetitionPattern = std::ptr::null_mut();
+        let hr = unsafe { new_trigger.get_Repetition(&mut p_repetition) };
+        let pattern = ComPtr::from_out(hr, p_repetition)
+            .map_err(|hr| fail(SchtaskError::Trigger, hr, "Cannot get the repetition pattern"))?;
+
+        put_duration(Some(repetition.interval), SchtaskError::Trigger, "Cannot put the repetition interval",
+            |value| unsafe { pattern.put_Interval(value) })?;
+        put_duration(repetition.duration, SchtaskError::Trigger, "Cannot put the repetition duration",
+            |value| unsafe { pattern.put_Duration(value) })?;
+        let hr = unsafe { pattern.put_StopAtDurationEnd(to_variant_bool(repetition.stop_at_duration_end)) };
+        check(hr, SchtaskError::Trigger, "Cannot put the repetition stop")?;
+    }
+
+    put_duration(trigger.execution_time_limit, SchtaskError::Trigger, "Cannot put the execution time limit",
+        |value| unsafe { new_trigger.put_ExecutionTimeLimit(value) })?;
+
+    let hr = unsafe { new_trigger.put_Enabled(to_variant_bool(trigger.enabled)) };
+    check(hr, SchtaskError::Trigger, "Cannot enable the trigger")?;
+
+    match &trigger.kind {
+        TriggerKind::Logon(logon) => {
+            // Get the logon trigger interface
+            let logon_trigger = new_trigger.cast::<ILogonTrigger>()
+                .map_err(|hr| fail(SchtaskError::Trigger, hr, "QueryInterface call failed for ILogonTrigger"))?;
+
+            if let Some(user_id) = &logon.user_id {
+                let user_id_wide = to_wide(user_id);
+                let hr = unsafe { logon_trigger.put_UserId(user_id_wide.as_ptr() as *mut u16) };
+                check(hr, SchtaskError::Trigger, "Cannot add user ID to logon trigger")?;
+            }
+            put_duration(trigger.delay, SchtaskError::Trigger, "Cannot put the logon delay",
+                |value| unsafe { logon_trigger.put_Delay(value) })?;
+        }
+        TriggerKind::Boot => {
+            let boot_trigger = new_trigger.cast::<IBootTrigger>()
+                .map_err(|hr| fail(SchtaskError::Trigger, hr, "QueryInterface call failed for IBootTrigger"))?;
+
+            put_duration(trigger.delay, SchtaskError::Trigger, "Cannot put the boot delay",
+                |value| unsafe { boot_trigger.put_Delay(value) })?;
+        }
+        // An idle trigger takes its conditions from the task's idle settings
+        TriggerKind::Idle => {}
+        TriggerKind::Registration => {
+            let registration_trigger = new_trigger.cast::<IRegistrationTrigger>()
+                .map_err(|hr| fail(SchtaskError::Trigger, hr, "QueryInterface call failed for IRegistrationTrigger"))?;
+
+            put_duration(trigger.delay, SchtaskError::Trigger, "Cannot put the registration delay",
+                |value| unsafe { registration_trigger.put_Delay(value) })?;
+        }
+        TriggerKind::SessionStateChange(session) => {
+            let session_trigger = new_trigger.cast::<ISessionStateChangeTrigger>()
+                .map_err(|hr| fail(SchtaskError::Trigger, hr, "QueryInterface call failed for ISessionStateChangeTrigger"))?;
+
+            put_duration(trigger.delay, SchtaskError::Trigger, "Cannot put the session state change delay",
+                |value| unsafe { session_trigger.put_Delay(value) })?;
+            if let Some(user_id) = &session.user_id {
+                let user_id_wide = to_wide(user_id);
+                let hr = unsafe { session_trigger.put_UserId(user_id_wide.as_ptr() as *mut u16) };
+                check(hr, SchtaskError::Trigger, "Cannot add user ID to session state change trigger")?;
+            }
+            let hr = unsafe { session_trigger.put_StateChange(state_change_type(session.state_change)) };
+            check(hr, SchtaskError::Trigger, "Cannot put the session state change")?;
+        }
+        TriggerKind::Event(event) => {
+            let event_trigger = new_trigger.cast::<IEventTrigger>()
+                .map_err(|hr| fail(SchtaskError::Trigger, hr, "QueryInterface call failed for IEventTrigger"))?;
+
+            let subscription_wide = to_wide(&event.subscription);
+            let hr = unsafe { event_trigger.put_Subscription(subscription_wide.as_ptr() as *mut u16) };
+            check(hr, SchtaskError::Trigger, "Cannot put the event subscription")?;
+            put_duration(trigger.delay, SchtaskError::Trigger, "Cannot put the event delay",
+                |value| unsafe { event_trigger.put_Delay(value) })?;
+
+            if !event.value_queries.is_empty() {
+                let mut p_queries: *mut ITaskNamedValueCollection = std::ptr::null_mut();
+                let hr = unsafe { event_trigger.get_ValueQueries(&mut p_queries) };
+                let queries = ComPtr::from_out(hr, p_queries)
+                    .map_err(|hr| fail(SchtaskError::Trigger, hr, "Cannot get the value queries"))?;
+
+                for (name, xpath) in &event.value_queries {
+                    let name_wide = to_wide(name);
+                    let xpath_wide = to_wide(xpath);
+                    let mut p_pair: *mut ITaskNamedValuePair = std::ptr::null_mut();
+                    let hr = unsafe {
+                        queries.Create(name_wide.as_ptr() as *mut u16, xpath_wide.as_ptr() as *mut u16, &mut p_pair)
+                    };
+                    ComPtr::from_out(hr, p_pair)
+                        .map_err(|hr| fail(SchtaskError::Trigger, hr, "Cannot add a value query"))?;
+                }
+            }
+        }
+        TriggerKind::Time => {
+            let time_trigger = new_trigger.cast::<ITimeTrigger>()
+                .map_err(|hr| fail(SchtaskError::Trigger, hr, "QueryInterface call failed for ITimeTrigger"))?;
+
+            put_duration(trigger.random_delay, SchtaskError::Trigger, "Cannot put the random delay",
+                |value| unsafe { time_trigger.put_RandomDelay(value) })?;
+        }
+        TriggerKind::Daily(daily) => {
+            let daily_trigger = new_trigger.cast::<IDailyTrigger>()
+                .map_err(|hr| fail(SchtaskError::Trigger, hr, "QueryInterface call failed for IDailyTrigger"))?;
+
+            put_duration(trigger.random_delay, SchtaskError::Trigger, "Cannot put the random delay",
+                |value| unsafe { daily_trigger.put_RandomDelay(value) })?;
+
+            let hr = unsafe { daily_trigger.put_DaysInterval(daily.days_interval as i16) };
+            check(hr, SchtaskError::Trigger, "Cannot put the days interval")?;
+        }
+        TriggerKind::Weekly(weekly) => {
+            let weekly_trigger = new_trigger.cast::<IWeeklyTrigger>()
+                .map_err(|hr| fail(SchtaskError::Trigger, hr, "QueryInterface call failed for IWeeklyTrigger"))?;
+
+            put_duration(trigger.random_delay, SchtaskError::Trigger, "Cannot put the random delay",
+                |value| unsafe { weekly_trigger.put_RandomDelay(value) })?;
+
+            let hr = unsafe { weekly_trigger.put_DaysOfWeek(weekly.days_of_week.bits() as i16) };
+            check(hr, SchtaskError::Trigger, "Cannot put the days of the week")?;
+            let hr = unsafe { weekly_trigger.put_WeeksInterval(weekly.weeks_interval as i16) };
+            check(hr, SchtaskError::Trigger, "Cannot put the weeks interval")?;
+        }
+        TriggerKind::Monthly(monthly) => {
+            let monthly_trigger = new_trigger.cast::<IMonthlyTrigger>()
+                .map_err(|hr| fail(SchtaskError::Trigger, hr, "QueryInterface call failed for IMonthlyTrigger"))?;
+
+            put_duration(trigger.random_delay, SchtaskError::Trigger, "Cannot put the random delay",
+                |value| unsafe { monthly_trigger.put_RandomDelay(value) })?;
+
+            let hr = unsafe { monthly_trigger.put_DaysOfMonth(monthly.days_of_month.bits() as i32) };
+            check(hr, SchtaskError::Trigger, "Cannot put the days of the month")?;
+            let hr = unsafe { monthly_trigger.put_MonthsOfYear(monthly.months.bits() as i16) };
+            check(hr, SchtaskError::Trigger, "Cannot put the months of the year")?;
+            let hr = unsafe {
+                monthly_trigger.put_RunOnLastDayOfMonth(to_variant_bool(monthly.run_on_last_day_of_month))
+            };
+            check(hr, SchtaskError::Trigger, "Cannot put the last day of the month")?;
+        }
+        TriggerKind::MonthlyDayOfWeek(monthly) => {
+            let monthly_trigger = new_trigger.cast::<IMonthlyDOWTrigger>()
+                .map_err(|hr| fail(SchtaskError::Trigger, hr, "QueryInterface call failed for IMonthlyDOWTrigger"))?;
+
+            put_duration(trigger.random_delay, SchtaskError::Trigger, "Cannot put the random delay",
+                |value| unsafe { monthly_trigger.put_RandomDelay(value) })?;
+
+            let hr = unsafe { monthly_trigger.put_DaysOfWeek(monthly.days_of_week.bits() as i16) };
+            check(hr, SchtaskError::Trigger, "Cannot put the days of the week")?;
+            let hr = unsafe { monthly_trigger.put_WeeksOfMonth(monthly.weeks_of_month.bits() as i16) };
+            check(hr, SchtaskError::Trigger, "Cannot put the weeks of the month")?;
+            let hr = unsafe { monthly_trigger.put_MonthsOfYear(monthly.months.bits() as i16) };
+            check(hr, SchtaskError::Trigger, "Cannot put the months of the year")?;
+            let hr = unsafe {
+                monthly_trigger.put_RunOnLastWeekOfMonth(to_variant_bool(monthly.run_on_last_week_of_month))
+            };
+            check(hr, SchtaskError::Trigger, "Cannot put the last week of the month")?;
+        }
+    }
+
+    Ok(())
+}
+
+fn state_change_type(state_change: SessionStateChange) -> TASK_SESSION_STATE_CHANGE_TYPE {
+    match state_change {
+        SessionStateChange::ConsoleConnect => TASK_CONSOLE_CONNECT,
+        SessionStateChange::ConsoleDisconnect => TASK_CONSOLE_DISCONNECT,
+        SessionStateChange::RemoteConnect => TASK_REMOTE_CONNECT,
+        SessionStateChange::RemoteDisconnect => TASK_REMOTE_DISCONNECT,
+        SessionStateChange::SessionLock => TASK_SESSION_LOCK,
+        SessionStateChange::SessionUnlock => TASK_SESSION_UNLOCK,
+    }
+}
+
+fn add_action(collection: &ComPtr<IActionCollection>, action: &Action) -> Result<(), SchtaskError> {
+    let action_type = match action.kind {
+        ActionKind::Exec(_) => TASK_ACTION_EXEC,
+        ActionKind::ComHandler(_) => TASK_ACTION_COM_HANDLER,
+        ActionKind::SendEmail(_) => TASK_ACTION_SEND_EMAIL,
+        ActionKind::ShowMessage(_) => TASK_ACTION_SHOW_MESSAGE,
+    };
+
+    let mut p_action: *mut IAction = std::ptr::null_mut();
+    let hr = unsafe { collection.Create(action_type, &mut p_action) };
+    let new_action = ComPtr::from_out(hr, p_action)
+        .map_err(|hr| fail(SchtaskError::Action, hr, "Cannot create the action"))?;
+
+    if let Some(id) = &action.id {
+        let id_wide = to_wide(id);
+        let hr = unsafe { new_action.put_Id(id_wide.as_ptr() as *mut u16) };
+        check(hr, SchtaskError::Action, "Cannot put the action ID")?;
+    }
+
+    match &action.kind {
+        ActionKind::Exec(exec) => apply_exec_action(&new_action, exec),
+        ActionKind::ComHandler(handler) => apply_com_handler_action(&new_action, handler),
+        ActionKind::SendEmail(email) => apply_email_action(&new_action, email),
+        ActionKind::ShowMessage(message) => apply_show_message_action(&new_action, message),
+    }
+}
+
+fn apply_exec_action(action: &ComPtr<IAction>, exec: &ExecAction) -> Result<(), SchtaskError> {
+    // Query for the executable task pointer
+    let exec_action = action.cast::<IExecAction>()
+        .map_err(|hr| fail(SchtaskError::Action, hr, "QueryInterface call failed for IExecAction"))?;
+
+    // Set the path of the executable
+    let path_wide = to_wide(&exec.path);
+    let hr = unsafe { exec_action.put_Path(path_wide.as_ptr() as *mut u16) };
+    check(hr, SchtaskError::Action, "Cannot set path of executable")?;
+
+    // Set arguments if provided
+    if let Some(args) = &exec.arguments {
+        let args_wide = to_wide(args);
+        let hr = unsafe { exec_action.put_Arguments(args_wide.as_ptr() as *mut u16) };
+        check(hr, SchtaskError::Action, "Cannot set arguments")?;
+    }
+
+    // Set the working directory if provided
+    if let Some(dir) = &exec.working_directory {
+        let dir_wide = to_wide(dir);
+        let hr = unsafe { exec_action.put_WorkingDirectory(dir_wide.as_ptr() as *mut u16) };
+        check(hr, SchtaskError::Action, "Cannot set working directory")?;
+    }
+
+    Ok(())
+}
+
+fn apply_com_handler_action(action: &ComPtr<IAction>, handler: &ComHandlerAction) -> Result<(), SchtaskError> {
+    let handler_action = action.cast::<IComHandlerAction>()
+        .map_err(|hr| fail(SchtaskError::Action, hr, "QueryInterface call failed for IComHandlerAction"))?;
+
+    let class_id_wide = to_wide(&handler.class_id);
+    let hr = unsafe { handler_action.put_ClassId(class_id_wide.as_ptr() as *mut u16) };
+    check(hr, SchtaskError::Action, "Cannot set the handler class ID")?;
+
+    if let Some(data) = &handler.data {
+        let data_wide = to_wide(data);
+        let hr = unsafe { handler_action.put_Data(data_wide.as_ptr() as *mut u16) };
+        check(hr, SchtaskError::Action, "Cannot set the handler data")?;
+    }
+
+    Ok(())
+}
+
+/// One of the `IEmailAction::put_*` string setters.
+type EmailActionSetter = unsafe fn(&IEmailAction, *mut u16) -> HRESULT;
+
+fn apply_email_action(action: &ComPtr<IAction>, email: &EmailAction) -> Result<(), SchtaskError> {
+    let email_action = action.cast::<IEmailAction>()
+        .map_err(|hr| fail(SchtaskError::Action, hr, "QueryInterface call failed for IEmailAction"))?;
+
+    let fields: [(Option<&String>, &str, EmailActionSetter); 8] = [
+        (Some(&email.server), "Cannot set the email server", IEmailAction::put_Server),
+        (email.subject.as_ref(), "Cannot set the email subject", IEmailAction::put_Subject),
+        (email.to.as_ref(), "Cannot set the email recipients", IEmailAction::put_To),
+        (email.cc.as_ref(), "Cannot set the email Cc recipients", IEmailAction::put_Cc),
+        (email.bcc.as_ref(), "Cannot set the email Bcc recipients", IEmailAction::put_Bcc),
+        (email.reply_to.as_ref(), "Cannot set the email reply-to address", IEmailAction::put_ReplyTo),
+        (Some(&email.from), "Cannot set the email sender", IEmailAction::put_From),
+        (email.body.as_ref(), "Cannot set the email body", IEmailAction::put_Body),
+    ];
+    for (value, context, put) in fields {
+        if let Some(value) = value {
+            let value_wide = to_wide(value);
+            let hr = unsafe { put(&email_action, value_wide.as_ptr() as *mut u16) };
+            check(hr, SchtaskError::Action, context)?;
+        }
+    }
+
+    if !email.header_fields.is_empty() {
+        let mut p_fields: *mut ITaskNamedValueCollection = std::ptr::null_mut();
+        let hr = unsafe { email_action.get_HeaderFields(&mut p_fields) };
+        let header_fields = ComPtr::from_out(hr, p_fields)
+            .map_err(|hr| fail(SchtaskError::Action, hr, "Cannot get the email header fields"))?;
+
+        for (name, value) in &email.header_fields {
+            let name_wide = to_wide(name);
+            let value_wide = to_wide(value);
+            let mut p_pair: *mut ITaskNamedValuePair = std::ptr::null_mut();
+            let hr = unsafe {
+                header_fields.Create(name_wide.as_ptr() as *mut u16, value_wide.as_ptr() as *mut u16, &mut p_pair)
+            };
+            ComPtr::from_out(hr, p_pair)
+                .map_err(|hr| fail(SchtaskError::Action, hr, "Cannot add an email header field"))?;
+        }
+    }
+
+    if !email.attachments.is_empty() {
+        put_attachments(&email_action, &email.attachments)?;
+    }
+
+    Ok(())
+}
+
+/// Sets an email's attachments from a SAFEARRAY of BSTR variants.
+fn put_attachments(email_action: &ComPtr<IEmailAction>, files: &[String]) -> Result<(), SchtaskError> {
+    let array = unsafe { SafeArrayCreateVector(VT_VARIANT as u16, 0, files.len() as u32) };
+    if array.is_null() {
+        return Err(fail(SchtaskError::Action, HResult::E_OUTOFMEMORY.0, "Cannot allocate the email attachments"));
+    }
+
+    // The array owns the strings from here on; destroying it frees them
+    let variants = unsafe { (*array).pvData as *mut VARIANT };
+    for (index, file) in files.iter().enumerate() {
+        let file_wide = to_wide(file);
+        unsafe {
+            let variant = &mut *variants.add(index);
+            let inner = variant.n1.n2_mut();
+            inner.vt = VT_BSTR as u16;
+            *inner.n3.bstrVal_mut() = SysAllocString(file_wide.as_ptr());
+        }
+    }
+
+    // winapi declares the parameter as a SAFEARRAY by value, but the
+    // method takes a pointer to one
+    let hr = unsafe {
+        let put: unsafe extern "system" fn(*mut IEmailAction, *mut SAFEARRAY) -> HRESULT =
+            std::mem::transmute((*email_action.lpVtbl).put_Attachments);
+        put(email_action.as_ptr(), array)
+    };
+    unsafe { SafeArrayDestroy(array) };
+    check(hr, SchtaskError::Action, "Cannot set the email attachments")
+}
+
+fn apply_show_message_action(action: &ComPtr<IAction>, message: &ShowMessageAction) -> Result<(), SchtaskError> {
+    let message_action = action.cast::<IShowMessageAction>()
+        .map_err(|hr| fail(SchtaskError::Action, hr, "QueryInterface call failed for IShowMessageAction"))?;
+
+    let title_wide = to_wide(&message.title);
+    let hr = unsafe { message_action.put_Title(title_wide.as_ptr() as *mut u16) };
+    check(hr, SchtaskError::Action, "Cannot set the message title")?;
+
+    let body_wide = to_wide(&message.body);
+    let hr = unsafe { message_action.put_MessageBody(body_wide.as_ptr() as *mut u16) };
+    check(hr, SchtaskError::Action, "Cannot set the message body")
+}
+
+/// Builds the error for a CLSID or IID string that is not a valid GUID.
+fn invalid_guid(context: &str) -> SchtaskError {
+    fail(SchtaskError::GuidLookup, HResult::CO_E_CLASSSTRING.0, context)
+}
+
+/// Builds the error for a failed registry read while looking up GUIDs.
+fn registry_error(error: std::io::Error, context: &str) -> SchtaskError {
+    // Registry errors are Win32 codes
+    let hr = match error.raw_os_error() {
+        Some(code) => HResult::from_win32(code as u32),
+        None => HResult::E_FAIL,
+    };
+    fail(SchtaskError::GuidLookup, hr.0, context)
+}
+
+fn parse_guid(guid_str: &str) -> Result<GUID, SchtaskError> {
+    // Remove curly braces if present
+    let guid_str = guid_str.trim_matches(|c| c == '{' || c == '}');
+
+    // Split into parts
+    let parts: Vec<&str> = guid_str.split('-').collect();
+    if parts.len() != 5 || parts[3].len() != 4 {
+        return Err(invalid_guid("Invalid GUID format"));
+    }
+
+    // Parse Data1 (first part)
+    let data1 = u32::from_str_radix(parts[0], 16)
+        .map_err(|_| invalid_guid("Failed to parse Data1"))?;
+
+    // Parse Data2 (second part)
+    let data2 = u16::from_str_radix(parts[1], 16)
+        .map_err(|_| invalid_guid("Failed to parse Data2"))?;
+
+    // Parse Data3 (third part)
+    let data3 = u16::from_str_radix(parts[2], 16)
+        .map_err(|_| invalid_guid("Failed to parse Data3"))?;
+
+    // Parse Data4 (last two parts combined)
+    let data4_1 = u8::from_str_radix(&parts[3][0..2], 16)
+        .map_err(|_| invalid_guid("Failed to parse Data4[0]"))?;
+    let data4_2 = u8::from_str_radix(&parts[3][2..4], 16)
+        .map_err(|_| invalid_guid("Failed to parse Data4[1]"))?;
+
+    // Parse the last part (6 bytes)
+    let last_part = parts[4];
+    if last_part.len() != 12 {
+        return Err(invalid_guid("Invalid Data4 format"));
+    }
+
+    let data4_rest: Vec<u8> = (0..6)
+        .map(|i| {
+            let start = i * 2;
+            let end = start + 2;
+            u8::from_str_radix(&last_part[start..end], 16)
+                .map_err(|_| invalid_guid("Failed to parse Data4"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(GUID {
+        Data1: data1,
+        Data2: data2,
+        Data3: data3,
+        Data4: [
+            data4_1,
+            data4_2,
+            data4_rest[0],
+            data4_rest[1],
+            data4_rest[2],
+            data4_rest[3],
+            data4_rest[4],
+            data4_rest[5],
+        ],
+    })
+}
+
+fn find_task_scheduler_guids() -> Result<(CLSID, IID), SchtaskError> {
+    // Open the CLSID key
+    let hkcr = RegKey::predef(HKEY_CLASSES_ROOT);
+    let clsid_key = hkcr.open_subkey("CLSID")
+        .map_err(|e| registry_error(e, "Cannot open HKCR\\CLSID"))?;
+
+    // Search for the TaskScheduler class
+    let mut task_scheduler_clsid = None;
+    for subkey_name in clsid_key.enum_keys() {
+        let subkey_name = subkey_name.map_err(|e| registry_error(e, "Cannot enumerate HKCR\\CLSID"))?;
+        let subkey = clsid_key.open_subkey(&subkey_name)
+            .map_err(|e| registry_error(e, "Cannot open CLSID subkey"))?;
+        let default_value: String = subkey.get_value("").unwrap_or_default();
+        if default_value.contains("TaskScheduler") {
+            task_scheduler_clsid = Some(subkey_name);
+            break;
+        }
+    }
+
+    let clsid = task_scheduler_clsid
+        .ok_or_else(|| fail(SchtaskError::GuidLookup, HResult::REGDB_E_CLASSNOTREG.0, "TaskScheduler CLSID not found"))?;
+
+    // Open the Interface key
+    let interface_key = hkcr.open_subkey("Interface")
+        .map_err(|e| registry_error(e, "Cannot open HKCR\\Interface"))?;
+
+    // Search for the ITaskService interface
+    let mut task_service_iid = None;
+    for subkey_name in interface_key.enum_keys() {
+        let subkey_name = subkey_name.map_err(|e| registry_error(e, "Cannot enumerate HKCR\\Interface"))?;
+        let subkey = interface_key.open_subkey(&subkey_name)
+            .map_err(|e| registry_error(e, "Cannot open Interface subkey"))?;
+        let default_value: String = subkey.get_value("").unwrap_or_default();
+        if default_value.contains("ITaskService") {
+            task_service_iid = Some(subkey_name);
+            break;
+        }
+    }
+
+    let iid = task_service_iid
+        .ok_or_else(|| fail(SchtaskError::GuidLookup, HResult::E_NOINTERFACE.0, "ITaskService IID not found"))?;
+
+    // Convert the CLSID and IID strings to GUID structs
+    let clsid_guid = parse_guid(&clsid)?;
+    let iid_guid = parse_guid(&iid)?;
+
+    Ok((clsid_guid, iid_guid))
+}
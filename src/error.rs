@@ -0,0 +1,275 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::account::AccountError;
+use crate::hresult::HResult;
+use crate::model::ValidationError;
+use crate::xml::XmlError;
+
+/// A failed COM or Task Scheduler call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComError {
+    /// The HRESULT the call returned.
+    pub hresult: HResult,
+    /// What was being done when the call failed.
+    pub context: String,
+    /// The decoded meaning of `hresult`.
+    pub message: String,
+}
+
+impl ComError {
+    /// Creates an error for a call that returned `hresult` while doing
+    /// `context`.
+    pub fn new(hresult: HResult, context: &str, message: &str) -> Self {
+        Self {
+            hresult,
+            context: context.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ComError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.context, self.hresult, self.message)
+    }
+}
+
+impl std::error::Error for ComError {}
+
+/// A file a backend keeps its tasks in that could not be read or written.
+///
+/// Errors compare equal when their fields do; the underlying I/O error is
+/// only reachable through [`source`](std::error::Error::source).
+#[derive(Debug, Clone)]
+pub struct FileError {
+    /// The file or directory.
+    pub path: PathBuf,
+    /// What was being done when the operation failed.
+    pub context: String,
+    /// The kind of the underlying I/O error.
+    pub kind: io::ErrorKind,
+    /// The message of the underlying I/O error.
+    pub message: String,
+    source: Arc<io::Error>,
+}
+
+impl FileError {
+    /// Creates an error for `error`, which occurred on `path` while doing
+    /// `context`.
+    pub fn new(path: &Path, context: &str, error: io::Error) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            context: context.to_string(),
+            kind: error.kind(),
+            message: error.to_string(),
+            source: Arc::new(error),
+        }
+    }
+}
+
+impl PartialEq for FileError {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+            && self.context == other.context
+            && self.kind == other.kind
+            && self.message == other.message
+    }
+}
+
+impl Eq for FileError {}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.context, self.path.display(), self.message)
+    }
+}
+
+impl std::error::Error for FileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// Part of a task, or an operation on one, that a backend cannot carry
+/// out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsupported {
+    /// The backend, e.g. `systemd`.
+    pub backend: &'static str,
+    /// What it cannot do, e.g. `event triggers`.
+    pub feature: String,
+}
+
+impl Unsupported {
+    pub fn new(backend: &'static str, feature: &str) -> Self {
+        Self { backend, feature: feature.to_string() }
+    }
+}
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the {} backend does not support {}", self.backend, self.feature)
+    }
+}
+
+/// Everything that can go wrong while creating or managing a task, by
+/// stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchtaskError {
+    /// COM could not be initialized on the calling thread.
+    ComInit(ComError),
+    /// COM security could not be initialized for the process.
+    SecurityInit(ComError),
+    /// The Task Scheduler CLSID or `ITaskService` IID could not be found.
+    GuidLookup(ComError),
+    /// The Task Scheduler service could not be created or connected to.
+    Connect(ComError),
+    /// The task folder could not be opened.
+    Folder(ComError),
+    /// The task definition could not be created or filled in.
+    Definition(ComError),
+    /// A trigger could not be added to the task.
+    Trigger(ComError),
+    /// An action could not be added to the task.
+    Action(ComError),
+    /// Task Scheduler refused to register the task.
+    Register(ComError),
+    /// A registered task could not be opened, run, stopped, changed or
+    /// deleted.
+    Task(ComError),
+    /// An account the task needs could not be resolved.
+    Account(AccountError),
+    /// The task was rejected before talking to Task Scheduler.
+    Invalid(ValidationError),
+    /// A Task XML document could not be read.
+    Xml(XmlError),
+    /// A backend could not read or write the files it keeps tasks in.
+    File(FileError),
+    /// The backend cannot express the task or carry out the operation.
+    Unsupported(Unsupported),
+}
+
+impl SchtaskError {
+    /// The failed COM call, if the error came from one.
+    pub fn com_error(&self) -> Option<&ComError> {
+        match self {
+            SchtaskError::ComInit(e)
+            | SchtaskError::SecurityInit(e)
+            | SchtaskError::GuidLookup(e)
+            | SchtaskError::Connect(e)
+            | SchtaskError::Folder(e)
+            | SchtaskError::Definition(e)
+            | SchtaskError::Trigger(e)
+            | SchtaskError::Action(e)
+            | SchtaskError::Register(e)
+            | SchtaskError::Task(e)
+            | SchtaskError::Account(AccountError::Lookup(e)) => Some(e),
+            SchtaskError::Account(_)
+            | SchtaskError::Invalid(_)
+            | SchtaskError::Xml(_)
+            | SchtaskError::File(_)
+            | SchtaskError::Unsupported(_) => None,
+        }
+    }
+
+    /// The HRESULT of the failed COM call, if the error came from one.
+    pub fn hresult(&self) -> Option<HResult> {
+        self.com_error().map(|e| e.hresult)
+    }
+}
+
+impl fmt::Display for SchtaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchtaskError::ComInit(e) => write!(f, "COM initialization failed: {}", e),
+            SchtaskError::SecurityInit(e) => write!(f, "COM security initialization failed: {}", e),
+            SchtaskError::GuidLookup(e) => write!(f, "Task Scheduler GUID lookup failed: {}", e),
+            SchtaskError::Connect(e) => write!(f, "connecting to Task Scheduler failed: {}", e),
+            SchtaskError::Folder(e) => write!(f, "opening the task folder failed: {}", e),
+            SchtaskError::Definition(e) => write!(f, "building the task definition failed: {}", e),
+            SchtaskError::Trigger(e) => write!(f, "adding a trigger failed: {}", e),
+            SchtaskError::Action(e) => write!(f, "adding an action failed: {}", e),
+            SchtaskError::Register(e) => write!(f, "registering the task failed: {}", e),
+            SchtaskError::Task(e) => write!(f, "managing the task failed: {}", e),
+            SchtaskError::Account(e) => write!(f, "resolving an account failed: {}", e),
+            SchtaskError::Invalid(e) => write!(f, "invalid task: {}", e),
+            SchtaskError::Xml(e) => write!(f, "invalid task XML: {}", e),
+            SchtaskError::File(e) => write!(f, "accessing a task file failed: {}", e),
+            SchtaskError::Unsupported(e) => write!(f, "unsupported task: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SchtaskError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SchtaskError::ComInit(e)
+            | SchtaskError::SecurityInit(e)
+            | SchtaskError::GuidLookup(e)
+            | SchtaskError::Connect(e)
+            | SchtaskError::Folder(e)
+            | SchtaskError::Definition(e)
+            | SchtaskError::Trigger(e)
+            | SchtaskError::Action(e)
+            | SchtaskError::Register(e)
+            | SchtaskError::Task(e) => Some(e),
+            SchtaskError::Account(e) => Some(e),
+            SchtaskError::Invalid(e) => Some(e),
+            SchtaskError::Xml(e) => Some(e),
+            SchtaskError::File(e) => Some(e),
+            SchtaskError::Unsupported(_) => None,
+        }
+    }
+}
+
+impl From<Unsupported> for SchtaskError {
+    fn from(error: Unsupported) -> Self {
+        SchtaskError::Unsupported(error)
+    }
+}
+
+impl From<AccountError> for SchtaskError {
+    fn from(error: AccountError) -> Self {
+        SchtaskError::Account(error)
+    }
+}
+
+impl From<ValidationError> for SchtaskError {
+    fn from(error: ValidationError) -> Self {
+        SchtaskError::Invalid(error)
+    }
+}
+
+impl From<XmlError> for SchtaskError {
+    fn from(error: XmlError) -> Self {
+        SchtaskError::Xml(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn sources_lead_to_the_com_and_io_errors() {
+        let com = ComError::new(HResult::E_ACCESSDENIED, "Cannot register the task", "Access is denied.");
+        let error = SchtaskError::Register(com.clone());
+        let source = error.source().and_then(|e| e.downcast_ref::<ComError>());
+        assert_eq!(source, Some(&com));
+
+        let io = io::Error::new(io::ErrorKind::PermissionDenied, "denied");
+        let error = SchtaskError::File(FileError::new(Path::new("/etc/crontab"), "Cannot write", io));
+        let file = error.source().unwrap();
+        let io = file.source().and_then(|e| e.downcast_ref::<io::Error>()).unwrap();
+        assert_eq!(io.kind(), io::ErrorKind::PermissionDenied);
+
+        let lookup = SchtaskError::Account(AccountError::Lookup(com.clone()));
+        let source = lookup.source().and_then(|e| e.source()).and_then(|e| e.downcast_ref::<ComError>());
+        assert_eq!(source, Some(&com));
+    }
+}
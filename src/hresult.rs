@@ -0,0 +1,266 @@
+//! Decoding of COM `HRESULT` status codes.
+//!
+//! An HRESULT packs a severity bit, a facility and a code into 32 bits.
+//! This module splits them apart and knows the names and meanings of the
+//! codes Task Scheduler, RPC, COM and Win32 return most often. It is plain
+//! Rust so errors can be decoded (and tested) on any platform.
+
+use std::fmt;
+
+/// Whether an HRESULT reports success or failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Success,
+    Failure,
+}
+
+/// Facility for codes without a facility, e.g. `E_UNEXPECTED`.
+pub const FACILITY_NULL: u16 = 0;
+/// Facility for RPC and COM marshalling codes (`RPC_E_*`).
+pub const FACILITY_RPC: u16 = 1;
+/// Facility for codes defined by an interface, e.g. `SCHED_E_*`.
+pub const FACILITY_ITF: u16 = 4;
+/// Facility for Win32 error codes wrapped by `HRESULT_FROM_WIN32`.
+pub const FACILITY_WIN32: u16 = 7;
+
+/// A COM status code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HResult(pub i32);
+
+macro_rules! known_hresults {
+    ($($name:ident = $value:expr, $message:expr;)*) => {
+        impl HResult {
+            $(
+                #[doc = $message]
+                pub const $name: HResult = HResult($value as u32 as i32);
+            )*
+        }
+
+        /// Every HRESULT with a symbolic name, with its explanation.
+        const KNOWN: &[(HResult, &str, &str)] = &[
+            $((HResult::$name, stringify!($name), $message),)*
+        ];
+    };
+}
+
+known_hresults! {
+    // Generic COM results
+    S_OK = 0x0000_0000, "The operation completed successfully.";
+    S_FALSE = 0x0000_0001, "The operation completed successfully but returned false.";
+    E_UNEXPECTED = 0x8000_FFFF, "Catastrophic failure.";
+    E_NOTIMPL = 0x8000_4001, "Not implemented.";
+    E_NOINTERFACE = 0x8000_4002, "No such interface supported.";
+    E_POINTER = 0x8000_4003, "Invalid pointer.";
+    E_ABORT = 0x8000_4004, "Operation aborted.";
+    E_FAIL = 0x8000_4005, "Unspecified error.";
+    E_ACCESSDENIED = 0x8007_0005, "Access is denied.";
+    E_HANDLE = 0x8007_0006, "The handle is invalid.";
+    E_OUTOFMEMORY = 0x8007_000E, "Not enough memory resources are available to complete this operation.";
+    E_INVALIDARG = 0x8007_0057, "One or more arguments are invalid.";
+    REGDB_E_CLASSNOTREG = 0x8004_0154, "Class not registered.";
+    CO_E_NOTINITIALIZED = 0x8004_01F0, "CoInitialize has not been called.";
+    CO_E_CLASSSTRING = 0x8004_01F3, "Invalid class string.";
+
+    // RPC and marshalling
+    RPC_E_SERVERFAULT = 0x8001_0105, "The server threw an exception.";
+    RPC_E_CHANGED_MODE = 0x8001_0106, "Cannot change thread mode after it is set.";
+    RPC_E_DISCONNECTED = 0x8001_0108, "The object invoked has disconnected from its clients.";
+    RPC_E_TOO_LATE = 0x8001_0119, "Security must be initialized before any interfaces are marshalled or unmarshalled. It cannot be changed once initialized.";
+    RPC_S_SERVER_UNAVAILABLE = 0x8007_06BA, "The RPC server is unavailable.";
+    RPC_S_CALL_FAILED = 0x8007_06BE, "The remote procedure call failed.";
+
+    // Win32 errors wrapped by HRESULT_FROM_WIN32
+    ERROR_FILE_NOT_FOUND = 0x8007_0002, "The system cannot find the file specified.";
+    ERROR_PATH_NOT_FOUND = 0x8007_0003, "The system cannot find the path specified.";
+    ERROR_NOT_SUPPORTED = 0x8007_0032, "The request is not supported.";
+    ERROR_INVALID_NAME = 0x8007_007B, "The filename, directory name, or volume label syntax is incorrect.";
+    ERROR_DIR_NOT_EMPTY = 0x8007_0091, "The directory is not empty.";
+    ERROR_ALREADY_EXISTS = 0x8007_00B7, "Cannot create a file when that file already exists.";
+    ERROR_PRIVILEGE_NOT_HELD = 0x8007_0522, "A required privilege is not held by the client.";
+    ERROR_LOGON_FAILURE = 0x8007_052E, "The user name or password is incorrect.";
+    ERROR_NONE_MAPPED = 0x8007_0534, "No mapping between account names and security IDs was done.";
+
+    // Task Scheduler
+    SCHED_S_TASK_READY = 0x0004_1300, "The task is ready to run at its next scheduled time.";
+    SCHED_S_TASK_RUNNING = 0x0004_1301, "The task is currently running.";
+    SCHED_S_TASK_DISABLED = 0x0004_1302, "The task will not run at the scheduled times because it has been disabled.";
+    SCHED_S_TASK_HAS_NOT_RUN = 0x0004_1303, "The task has not yet run.";
+    SCHED_S_TASK_NO_MORE_RUNS = 0x0004_1304, "There are no more runs scheduled for this task.";
+    SCHED_S_TASK_NOT_SCHEDULED = 0x0004_1305, "One or more of the properties that are needed to run this task on a schedule have not been set.";
+    SCHED_S_TASK_TERMINATED = 0x0004_1306, "The last run of the task was terminated by the user.";
+    SCHED_S_TASK_NO_VALID_TRIGGERS = 0x0004_1307, "Either the task has no triggers or the existing triggers are disabled or not set.";
+    SCHED_S_EVENT_TRIGGER = 0x0004_1308, "Event triggers do not have set run times.";
+    SCHED_E_TRIGGER_NOT_FOUND = 0x8004_1309, "A task's trigger is not found.";
+    SCHED_E_TASK_NOT_READY = 0x8004_130A, "One or more of the properties required to run this task have not been set.";
+    SCHED_E_TASK_NOT_RUNNING = 0x8004_130B, "There is no running instance of the task.";
+    SCHED_E_SERVICE_NOT_INSTALLED = 0x8004_130C, "The Task Scheduler service is not installed on this computer.";
+    SCHED_E_CANNOT_OPEN_TASK = 0x8004_130D, "The task object could not be opened.";
+    SCHED_E_INVALID_TASK = 0x8004_130E, "The object is either an invalid task object or is not a task object.";
+    SCHED_E_ACCOUNT_INFORMATION_NOT_SET = 0x8004_130F, "No account information could be found in the Task Scheduler security database for the task indicated.";
+    SCHED_E_ACCOUNT_NAME_NOT_FOUND = 0x8004_1310, "Unable to establish existence of the account specified.";
+    SCHED_E_ACCOUNT_DBASE_CORRUPT = 0x8004_1311, "Corruption was detected in the Task Scheduler security database; the database has been reset.";
+    SCHED_E_NO_SECURITY_SERVICES = 0x8004_1312, "Task Scheduler security services are available only on Windows NT.";
+    SCHED_E_UNKNOWN_OBJECT_VERSION = 0x8004_1313, "The task object version is either unsupported or invalid.";
+    SCHED_E_UNSUPPORTED_ACCOUNT_OPTION = 0x8004_1314, "The task has been configured with an unsupported combination of account settings and run time options.";
+    SCHED_E_SERVICE_NOT_RUNNING = 0x8004_1315, "The Task Scheduler Service is not running.";
+    SCHED_E_UNEXPECTEDNODE = 0x8004_1316, "The task XML contains an unexpected node.";
+    SCHED_E_NAMESPACE = 0x8004_1317, "The task XML contains an element or attribute from an unexpected namespace.";
+    SCHED_E_INVALIDVALUE = 0x8004_1318, "The task XML contains a value which is incorrectly formatted or out of range.";
+    SCHED_E_MISSINGNODE = 0x8004_1319, "The task XML is missing a required element or attribute.";
+    SCHED_E_MALFORMEDXML = 0x8004_131A, "The task XML is malformed.";
+    SCHED_S_SOME_TRIGGERS_FAILED = 0x0004_131B, "The task is registered, but not all specified triggers will start the task.";
+    SCHED_S_BATCH_LOGON_PROBLEM = 0x0004_131C, "The task is registered, but may fail to start. Batch logon privilege needs to be enabled for the task principal.";
+    SCHED_E_TOO_MANY_NODES = 0x8004_131D, "The task XML contains too many nodes of the same type.";
+    SCHED_E_PAST_END_BOUNDARY = 0x8004_131E, "The task cannot be started after the trigger end boundary.";
+    SCHED_E_ALREADY_RUNNING = 0x8004_131F, "An instance of this task is already running.";
+    SCHED_E_USER_NOT_LOGGED_ON = 0x8004_1320, "The task will not run because the user is not logged on.";
+    SCHED_E_INVALID_TASK_HASH = 0x8004_1321, "The task image is corrupt or has been tampered with.";
+    SCHED_E_SERVICE_NOT_AVAILABLE = 0x8004_1322, "The Task Scheduler service is not available.";
+    SCHED_E_SERVICE_TOO_BUSY = 0x8004_1323, "The Task Scheduler service is too busy to handle your request. Please try again later.";
+    SCHED_E_TASK_ATTEMPTED = 0x8004_1324, "The Task Scheduler service attempted to run the task, but the task did not run due to one of the constraints in the task definition.";
+    SCHED_S_TASK_QUEUED = 0x0004_1325, "The Task Scheduler service has asked the task to run.";
+    SCHED_E_TASK_DISABLED = 0x8004_1326, "The task is disabled.";
+    SCHED_E_TASK_NOT_V1_COMPAT = 0x8004_1327, "The task has properties that are not compatible with previous versions of Windows.";
+    SCHED_E_START_ON_DEMAND = 0x8004_1328, "The task settings do not allow the task to start on demand.";
+}
+
+impl HResult {
+    /// Wraps a Win32 error code the way `HRESULT_FROM_WIN32` does.
+    pub fn from_win32(code: u32) -> HResult {
+        if code as i32 <= 0 {
+            HResult(code as i32)
+        } else {
+            HResult(((code & 0xFFFF) | ((FACILITY_WIN32 as u32) << 16) | 0x8000_0000) as i32)
+        }
+    }
+
+    /// The HRESULT as the unsigned value it is usually written as.
+    pub fn as_u32(self) -> u32 {
+        self.0 as u32
+    }
+
+    pub fn is_success(self) -> bool {
+        self.0 >= 0
+    }
+
+    pub fn is_failure(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn severity(self) -> Severity {
+        if self.is_failure() { Severity::Failure } else { Severity::Success }
+    }
+
+    /// The facility that defined the code, e.g. [`FACILITY_WIN32`].
+    pub fn facility(self) -> u16 {
+        ((self.as_u32() >> 16) & 0x1FFF) as u16
+    }
+
+    /// The facility-specific code, e.g. the Win32 error for
+    /// [`FACILITY_WIN32`] results.
+    pub fn code(self) -> u16 {
+        (self.as_u32() & 0xFFFF) as u16
+    }
+
+    /// The symbolic name of a well-known HRESULT, e.g. `E_ACCESSDENIED`.
+    pub fn name(self) -> Option<&'static str> {
+        lookup(self).map(|(_, name, _)| *name)
+    }
+
+    /// The explanation of a well-known HRESULT.
+    pub fn message(self) -> Option<&'static str> {
+        lookup(self).map(|(_, _, message)| *message)
+    }
+
+    /// The explanation of the HRESULT, or a description of its parts if it
+    /// is not a well-known code.
+    pub fn describe(self) -> String {
+        match self.message() {
+            Some(message) => message.to_string(),
+            None => format!(
+                "Unknown {} (facility {}, code 0x{:04x}).",
+                if self.is_failure() { "error" } else { "status" },
+                self.facility(),
+                self.code()
+            ),
+        }
+    }
+}
+
+fn lookup(hr: HResult) -> Option<&'static (HResult, &'static str, &'static str)> {
+    KNOWN.iter().find(|(known, _, _)| *known == hr)
+}
+
+impl fmt::Display for HResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{} (0x{:08x})", name, self.as_u32()),
+            None => write!(f, "0x{:08x}", self.as_u32()),
+        }
+    }
+}
+
+impl From<i32> for HResult {
+    fn from(value: i32) -> Self {
+        HResult(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_severity_facility_and_code() {
+        let hr = HResult::E_ACCESSDENIED;
+
+        assert_eq!(hr.severity(), Severity::Failure);
+        assert_eq!(hr.facility(), FACILITY_WIN32);
+        assert_eq!(hr.code(), 5);
+
+        let hr = HResult::SCHED_S_TASK_READY;
+        assert_eq!(hr.severity(), Severity::Success);
+        assert_eq!(hr.facility(), FACILITY_ITF);
+        assert_eq!(hr.code(), 0x1300);
+
+        assert_eq!(HResult::RPC_E_TOO_LATE.facility(), FACILITY_RPC);
+        assert_eq!(HResult::E_UNEXPECTED.facility(), FACILITY_NULL);
+    }
+
+    #[test]
+    fn wraps_win32_codes() {
+        assert_eq!(HResult::from_win32(0), HResult::S_OK);
+        assert_eq!(HResult::from_win32(2), HResult::ERROR_FILE_NOT_FOUND);
+        assert_eq!(HResult::from_win32(183), HResult::ERROR_ALREADY_EXISTS);
+        assert_eq!(HResult::from_win32(1332), HResult::ERROR_NONE_MAPPED);
+    }
+
+    #[test]
+    fn names_well_known_codes() {
+        assert_eq!(HResult(-2147417831).name(), Some("RPC_E_TOO_LATE"));
+        assert_eq!(HResult(0x8004_1318u32 as i32).name(), Some("SCHED_E_INVALIDVALUE"));
+        assert_eq!(HResult::E_ACCESSDENIED.message(), Some("Access is denied."));
+        assert_eq!(HResult(0x8004_FFFFu32 as i32).name(), None);
+    }
+
+    #[test]
+    fn every_known_code_has_a_unique_value() {
+        for (i, (hr, name, _)) in KNOWN.iter().enumerate() {
+            assert!(
+                KNOWN[i + 1..].iter().all(|(other, _, _)| other != hr),
+                "{} is listed twice",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn formats_with_name_when_known() {
+        assert_eq!(HResult::E_ACCESSDENIED.to_string(), "E_ACCESSDENIED (0x80070005)");
+        assert_eq!(HResult(0x8004_FFFFu32 as i32).to_string(), "0x8004ffff");
+        assert_eq!(
+            HResult(0x8004_FFFFu32 as i32).describe(),
+            "Unknown error (facility 4, code 0xffff)."
+        );
+    }
+}
@@ -1,15 +1,51 @@
-use schtask::create_task;
-
-fn main() {
-    // Example 1: Create a task without arguments
-    let result = create_task("MyTask", "C:\\Windows\\System32\\notepad.exe", None);
-    println!("Task without arguments: {}", result);
-
-    // Example 2: Create a task with arguments
-    let result = create_task(
-        "MyTaskWithArgs", 
-        "C:\\Windows\\System32\\notepad.exe", 
-        Some("C:\\Windows\\System32\\drivers\\etc\\hosts")
-    );
-    println!("Task with arguments: {}", result);
-}
\ No newline at end of file
+use std::env;
+use std::error::Error;
+use std::process::ExitCode;
+
+use schtask::cli::{self, Command, USAGE};
+use schtask::{RegisteredTaskInfo, SchtaskError, Task};
+#[cfg(unix)]
+use schtask::{RegisterMode, SchedulerBackend, SystemdBackend};
+
+fn main() -> ExitCode {
+    let options = match cli::parse_args(env::args().skip(1)) {
+        Ok(Command::Help) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Create(options)) => options,
+        Err(e) => {
+            eprintln!("schtask: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = options.to_task().map_err(|e| SchtaskError::from(e).into()).and_then(|task| register(&task));
+    match result {
+        Ok(task) => {
+            println!("Created {}", task.path);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("schtask: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(all(windows, feature = "windows-backend"))]
+fn register(task: &Task) -> Result<RegisteredTaskInfo, Box<dyn Error>> {
+    Ok(schtask::register_task(task)?)
+}
+
+/// Writes the task out as systemd units; the user's manager picks them up
+/// after `systemctl --user daemon-reload`.
+#[cfg(unix)]
+fn register(task: &Task) -> Result<RegisteredTaskInfo, Box<dyn Error>> {
+    Ok(SystemdBackend::connect()?.register(task, RegisterMode::CreateOrUpdate)?)
+}
+
+#[cfg(not(any(unix, all(windows, feature = "windows-backend"))))]
+fn register(task: &Task) -> Result<RegisteredTaskInfo, Box<dyn Error>> {
+    Err(format!("cannot register {}: this build has no scheduler backend", task.path()).into())
+}
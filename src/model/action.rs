@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+
+use super::command_line::join_args;
+use super::env::expand_env_vars;
+
+/// Work performed when a task runs (`IAction`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Action {
+    /// Identifier of the action within its task, e.g. `Exec1`. Task
+    /// builders fill in missing ids; see [`TaskDefinition::assign_ids`].
+    ///
+    /// [`TaskDefinition::assign_ids`]: super::TaskDefinition::assign_ids
+    pub id: Option<String>,
+    /// What the action does.
+    pub kind: ActionKind,
+}
+
+impl Action {
+    /// Creates an action with no id.
+    pub fn new(kind: ActionKind) -> Self {
+        Self { id: None, kind }
+    }
+
+    /// Creates an action that runs the executable at `path`.
+    pub fn exec(path: &str, arguments: Option<&str>) -> Self {
+        Self::new(ActionKind::Exec(ExecAction {
+            path: path.to_string(),
+            arguments: arguments.map(str::to_string),
+            working_directory: None,
+        }))
+    }
+
+    /// Creates an action that runs the COM handler with class id
+    /// `class_id`, e.g. `{E3A19126-8E23-4E8A-9A9E-6EF3E26A6E39}`, passing it
+    /// `data`.
+    pub fn com_handler(class_id: &str, data: Option<&str>) -> Self {
+        Self::new(ActionKind::ComHandler(ComHandlerAction {
+            class_id: class_id.to_string(),
+            data: data.map(str::to_string),
+        }))
+    }
+
+    /// Creates an action that shows a message box. See
+    /// [`ActionKind::ShowMessage`].
+    pub fn show_message(title: &str, body: &str) -> Self {
+        Self::new(ActionKind::ShowMessage(ShowMessageAction {
+            title: title.to_string(),
+            body: body.to_string(),
+        }))
+    }
+
+    /// Creates an action that runs the executable at `path` with each of
+    /// `args` passed as a separate argument, quoting them as needed. See
+    /// [`join_args`].
+    ///
+    /// [`join_args`]: super::join_args
+    pub fn exec_args<S: AsRef<OsStr>>(path: &str, args: &[S]) -> Self {
+        let arguments = join_args(args);
+        Self::exec(path, (!args.is_empty()).then_some(arguments.as_str()))
+    }
+}
+
+/// The kind of work an [`Action`] performs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionKind {
+    /// Runs an executable (`TASK_ACTION_EXEC`).
+    Exec(ExecAction),
+    /// Calls a COM handler (`TASK_ACTION_COM_HANDLER`).
+    ComHandler(ComHandlerAction),
+    /// Sends an email (`TASK_ACTION_SEND_EMAIL`). Deprecated: Windows 8
+    /// and later reject it, so it is only kept to round-trip older
+    /// exports.
+    SendEmail(EmailAction),
+    /// Shows a message box (`TASK_ACTION_SHOW_MESSAGE`). Deprecated like
+    /// [`ActionKind::SendEmail`].
+    ShowMessage(ShowMessageAction),
+}
+
+impl ActionKind {
+    /// Short name of the kind, used as the prefix of generated action ids.
+    /// It is also the name of the action's element in Task XML.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ActionKind::Exec(_) => "Exec",
+            ActionKind::ComHandler(_) => "ComHandler",
+            ActionKind::SendEmail(_) => "SendEmail",
+            ActionKind::ShowMessage(_) => "ShowMessage",
+        }
+    }
+
+    /// Whether the kind is one Windows 8 and later no longer run.
+    pub fn is_deprecated(&self) -> bool {
+        matches!(self, ActionKind::SendEmail(_) | ActionKind::ShowMessage(_))
+    }
+}
+
+/// Settings specific to an executable action (`IExecAction`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecAction {
+    /// Path to the executable.
+    pub path: String,
+    /// Command-line arguments passed to the executable, as one string.
+    /// [`Action::exec_args`] builds it from separate arguments and
+    /// [`split_args`] splits it again.
+    ///
+    /// [`split_args`]: super::split_args
+    pub arguments: Option<String>,
+    /// Directory the executable is started in.
+    pub working_directory: Option<String>,
+}
+
+impl ExecAction {
+    /// Returns the action with the `%NAME%` references in its path,
+    /// arguments and working directory replaced from `env`, previewing what
+    /// Task Scheduler will run. See [`expand_env_vars`].
+    ///
+    /// [`expand_env_vars`]: super::expand_env_vars
+    pub fn expanded(&self, env: &HashMap<String, String>) -> ExecAction {
+        ExecAction {
+            path: expand_env_vars(&self.path, env),
+            arguments: self.arguments.as_deref().map(|arguments| expand_env_vars(arguments, env)),
+            working_directory: self.working_directory.as_deref().map(|dir| expand_env_vars(dir, env)),
+        }
+    }
+}
+
+/// Settings specific to a COM handler action (`IComHandlerAction`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComHandlerAction {
+    /// Class id of the handler, a GUID in braces.
+    pub class_id: String,
+    /// Data passed to the handler's `Start` method.
+    pub data: Option<String>,
+}
+
+/// Settings specific to an email action (`IEmailAction`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmailAction {
+    /// SMTP server to send through.
+    pub server: String,
+    /// Subject line of the message.
+    pub subject: Option<String>,
+    /// Recipients' addresses, separated by commas.
+    pub to: Option<String>,
+    /// Carbon copy recipients' addresses, separated by commas.
+    pub cc: Option<String>,
+    /// Blind carbon copy recipients' addresses, separated by commas.
+    pub bcc: Option<String>,
+    /// Address replies are sent to.
+    pub reply_to: Option<String>,
+    /// Sender's address.
+    pub from: String,
+    /// Extra header fields, as (name, value) pairs.
+    pub header_fields: Vec<(String, String)>,
+    /// Text of the message.
+    pub body: Option<String>,
+    /// Paths of files to attach.
+    pub attachments: Vec<String>,
+}
+
+/// Settings specific to a message box action (`IShowMessageAction`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShowMessageAction {
+    /// Title of the message box.
+    pub title: String,
+    /// Message shown in the box.
+    pub body: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_every_field() {
+        let env = HashMap::from([
+            ("SystemRoot".to_string(), "C:\\Windows".to_string()),
+            ("TEMP".to_string(), "C:\\Temp".to_string()),
+        ]);
+        let exec = ExecAction {
+            path: "%SystemRoot%\\System32\\cmd.exe".to_string(),
+            arguments: Some("/c del %TEMP%\\*.log %UNSET%".to_string()),
+            working_directory: Some("%TEMP%".to_string()),
+        };
+
+        assert_eq!(
+            exec.expanded(&env),
+            ExecAction {
+                path: "C:\\Windows\\System32\\cmd.exe".to_string(),
+                arguments: Some("/c del C:\\Temp\\*.log %UNSET%".to_string()),
+                working_directory: Some("C:\\Temp".to_string()),
+            }
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::ops::{BitOr, BitOrAssign};
+
+use super::ValidationError;
+
+/// Defines a set of calendar values stored as the bit mask Task Scheduler
+/// uses for it, with one named constant per value.
+macro_rules! bit_set {
+    (
+        $(#[$meta:meta])*
+        $name:ident($bits:ty) {
+            $($(#[$value_meta:meta])* $value:ident = $bit:literal, $xml:literal;)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+        pub struct $name($bits);
+
+        impl $name {
+            $($(#[$value_meta])* pub const $value: $name = $name($bit);)*
+
+            /// Each value in the set, with its element name in Task XML.
+            pub(crate) const NAMED: &[($name, &'static str)] = &[$(($name::$value, $xml),)*];
+
+            /// The set with no values.
+            pub const fn empty() -> Self {
+                $name(0)
+            }
+
+            /// The set with the given Task Scheduler bit mask.
+            pub const fn from_bits(bits: $bits) -> Self {
+                $name(bits)
+            }
+
+            /// The Task Scheduler bit mask of the set.
+            pub const fn bits(self) -> $bits {
+                self.0
+            }
+
+            /// Whether the set has no values.
+            pub const fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+
+            /// Whether every value in `other` is also in the set.
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+        }
+
+        impl BitOr for $name {
+            type Output = $name;
+
+            fn bitor(self, other: $name) -> $name {
+                $name(self.0 | other.0)
+            }
+        }
+
+        impl BitOrAssign for $name {
+            fn bitor_assign(&mut self, other: $name) {
+                self.0 |= other.0;
+            }
+        }
+    };
+}
+
+bit_set! {
+    /// Days of the week on which a weekly or monthly trigger fires.
+    DaysOfWeek(u16) {
+        SUNDAY = 0x01, "Sunday";
+        MONDAY = 0x02, "Monday";
+        TUESDAY = 0x04, "Tuesday";
+        WEDNESDAY = 0x08, "Wednesday";
+        THURSDAY = 0x10, "Thursday";
+        FRIDAY = 0x20, "Friday";
+        SATURDAY = 0x40, "Saturday";
+    }
+}
+
+bit_set! {
+    /// Months in which a monthly trigger fires.
+    Months(u16) {
+        JANUARY = 0x001, "January";
+        FEBRUARY = 0x002, "February";
+        MARCH = 0x004, "March";
+        APRIL = 0x008, "April";
+        MAY = 0x010, "May";
+        JUNE = 0x020, "June";
+        JULY = 0x040, "July";
+        AUGUST = 0x080, "August";
+        SEPTEMBER = 0x100, "September";
+        OCTOBER = 0x200, "October";
+        NOVEMBER = 0x400, "November";
+        DECEMBER = 0x800, "December";
+    }
+}
+
+bit_set! {
+    /// Weeks of the month in which a day-of-week monthly trigger fires.
+    WeeksOfMonth(u16) {
+        FIRST = 0x01, "1";
+        SECOND = 0x02, "2";
+        THIRD = 0x04, "3";
+        FOURTH = 0x08, "4";
+    }
+}
+
+impl DaysOfWeek {
+    /// Monday to Friday.
+    pub const WEEKDAYS: DaysOfWeek = DaysOfWeek(0b011_1110);
+    /// Every day of the week.
+    pub const ALL: DaysOfWeek = DaysOfWeek(0b111_1111);
+}
+
+impl Months {
+    /// Every month of the year.
+    pub const ALL: Months = Months(0xFFF);
+}
+
+/// Days of the month (1 to 31) on which a monthly trigger fires.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DaysOfMonth(u32);
+
+impl DaysOfMonth {
+    /// The set of the given days, each of which must be between 1 and 31.
+    pub fn from_days(days: &[u8]) -> Result<Self, ValidationError> {
+        let mut bits = 0;
+        for &day in days {
+            if !(1..=31).contains(&day) {
+                return Err(ValidationError::InvalidDayOfMonth(day));
+            }
+            bits |= 1 << (day - 1);
+        }
+        Ok(DaysOfMonth(bits))
+    }
+
+    /// The set with the given Task Scheduler bit mask, where bit 0 is the
+    /// first day of the month.
+    pub const fn from_bits(bits: u32) -> Self {
+        DaysOfMonth(bits)
+    }
+
+    /// The Task Scheduler bit mask of the set.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Whether the set has no days.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The days in the set, in ascending order.
+    pub fn days(self) -> impl Iterator<Item = u8> {
+        (1..=31u8).filter(move |day| self.0 & (1 << (day - 1)) != 0)
+    }
+}
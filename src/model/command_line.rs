@@ -0,0 +1,149 @@
+use std::ffi::OsStr;
+
+/// Joins `args` into a Windows command line that splits back into the same
+/// arguments, for use as the arguments of an exec action.
+///
+/// Quoting follows the rules the Microsoft C runtime and
+/// `CommandLineToArgvW` use to split a command line:
+///
+/// - Arguments that are empty or contain a space or tab are wrapped in `"`.
+/// - A `"` inside an argument is escaped with a backslash.
+/// - Backslashes are doubled where they come before a `"`, and left alone
+///   elsewhere.
+///
+/// Arguments that are not valid Unicode are converted lossily.
+///
+/// ```
+/// use schtask::model::join_args;
+///
+/// assert_eq!(
+///     join_args(&["C:\\Program Files\\", "say \"hi\"", ""]),
+///     r#""C:\Program Files\\" "say \"hi\"" """#
+/// );
+/// ```
+pub fn join_args<S: AsRef<OsStr>>(args: &[S]) -> String {
+    let mut line = String::new();
+    for (index, arg) in args.iter().enumerate() {
+        if index > 0 {
+            line.push(' ');
+        }
+        quote_into(&mut line, &arg.as_ref().to_string_lossy());
+    }
+    line
+}
+
+fn quote_into(out: &mut String, arg: &str) {
+    let quoted = arg.is_empty() || arg.contains([' ', '\t']);
+    if quoted {
+        out.push('"');
+    }
+
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                push_backslashes(out, backslashes + 1);
+                backslashes = 0;
+            }
+            _ => backslashes = 0,
+        }
+        out.push(c);
+    }
+
+    if quoted {
+        push_backslashes(out, backslashes);
+        out.push('"');
+    }
+}
+
+fn push_backslashes(out: &mut String, count: usize) {
+    out.extend(std::iter::repeat_n('\\', count));
+}
+
+/// Splits a Windows command line into arguments, the inverse of
+/// [`join_args`].
+///
+/// Uses the rules of the Microsoft C runtime since 2008, which every
+/// program built with it applies to its own arguments:
+///
+/// - Spaces and tabs outside quotes separate arguments.
+/// - `"` starts or ends a quoted part; inside one, `""` is a literal `"`.
+/// - `2n` backslashes before a `"` become `n` backslashes and the `"` is
+///   a quote; `2n + 1` backslashes become `n` backslashes and a literal
+///   `"`.
+/// - Other backslashes are literal.
+///
+/// The line is taken to hold arguments only. A program name at its start
+/// would be split with the same rules, which differ from the ones
+/// Windows uses for the program name.
+pub fn split_args(command_line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut chars = command_line.chars().peekable();
+
+    loop {
+        while chars.next_if(|&c| c == ' ' || c == '\t').is_some() {}
+        if chars.peek().is_none() {
+            return args;
+        }
+
+        let mut arg = String::new();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    let mut backslashes = 1;
+                    while chars.next_if_eq(&'\\').is_some() {
+                        backslashes += 1;
+                    }
+                    if chars.peek() == Some(&'"') {
+                        push_backslashes(&mut arg, backslashes / 2);
+                        if backslashes % 2 == 1 {
+                            arg.push('"');
+                            chars.next();
+                        }
+                    } else {
+                        push_backslashes(&mut arg, backslashes);
+                    }
+                }
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    arg.push('"');
+                    chars.next();
+                }
+                '"' => quoted = !quoted,
+                ' ' | '\t' if !quoted => break,
+                c => arg.push(c),
+            }
+        }
+        args.push(arg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_the_documented_examples() {
+        let cases: [(&str, &[&str]); 6] = [
+            (r#""a b c" d e"#, &["a b c", "d", "e"]),
+            (r#""ab\"c" "\\" d"#, &["ab\"c", "\\", "d"]),
+            (r#"a\\\b d"e f"g h"#, &["a\\\\\\b", "de fg", "h"]),
+            (r#"a\\\"b c d"#, &["a\\\"b", "c", "d"]),
+            (r#"a\\\\"b c" d e"#, &["a\\\\b c", "d", "e"]),
+            (r#"a"b"" c d"#, &["ab\" c d"]),
+        ];
+        for (line, args) in cases {
+            assert_eq!(split_args(line), args, "{}", line);
+        }
+        assert_eq!(split_args(" \t "), Vec::<String>::new());
+    }
+
+    #[test]
+    fn quotes_only_when_needed() {
+        assert_eq!(join_args::<&str>(&[]), "");
+        assert_eq!(join_args(&["plain", "C:\\dir\\", "a\"b"]), r#"plain C:\dir\ a\"b"#);
+        assert_eq!(join_args(&["two words", "tab\there", ""]), "\"two words\" \"tab\there\" \"\"");
+        assert_eq!(join_args(&["C:\\my dir\\"]), r#""C:\my dir\\""#);
+    }
+}
@@ -0,0 +1,327 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::ValidationError;
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// A length of time as Task Scheduler stores it: an ISO 8601 duration such
+/// as `PT15M` or `P1DT2H`.
+///
+/// Converts to and from [`std::time::Duration`]; [`Display`](fmt::Display)
+/// and [`FromStr`] write and read the ISO 8601 form. Years and months are
+/// not supported because they have no fixed length.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskDuration(Duration);
+
+impl TaskDuration {
+    /// No time at all; as an execution time limit, no limit.
+    pub const ZERO: TaskDuration = TaskDuration(Duration::ZERO);
+
+    /// A duration of `secs` seconds.
+    pub const fn from_secs(secs: u64) -> Self {
+        TaskDuration(Duration::from_secs(secs))
+    }
+
+    /// A duration of `mins` minutes.
+    pub const fn from_mins(mins: u64) -> Self {
+        TaskDuration(Duration::from_secs(mins * MINUTE))
+    }
+
+    /// A duration of `hours` hours.
+    pub const fn from_hours(hours: u64) -> Self {
+        TaskDuration(Duration::from_secs(hours * HOUR))
+    }
+
+    /// A duration of `days` days of 24 hours.
+    pub const fn from_days(days: u64) -> Self {
+        TaskDuration(Duration::from_secs(days * DAY))
+    }
+
+    /// The duration as a [`std::time::Duration`].
+    pub const fn as_duration(self) -> Duration {
+        self.0
+    }
+
+    /// Checks that the duration is in the range Task Scheduler accepts for
+    /// `field`.
+    pub fn check(self, field: DurationField) -> Result<(), ValidationError> {
+        let (min, max) = field.range();
+        if self < min || max.is_some_and(|max| self > max) {
+            return Err(ValidationError::DurationOutOfRange { field, value: self });
+        }
+        Ok(())
+    }
+}
+
+impl From<Duration> for TaskDuration {
+    fn from(duration: Duration) -> Self {
+        TaskDuration(duration)
+    }
+}
+
+impl From<TaskDuration> for Duration {
+    fn from(duration: TaskDuration) -> Self {
+        duration.0
+    }
+}
+
+impl fmt::Display for TaskDuration {
+    /// Writes the duration in days, hours, minutes and seconds, leaving out
+    /// zero components. A zero duration is `PT0S`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.0.as_secs();
+        let (days, hours, minutes, seconds) = (secs / DAY, secs % DAY / HOUR, secs % HOUR / MINUTE, secs % MINUTE);
+        let nanos = self.0.subsec_nanos();
+
+        f.write_str("P")?;
+        if days > 0 {
+            write!(f, "{}D", days)?;
+        }
+        if days > 0 && hours == 0 && minutes == 0 && seconds == 0 && nanos == 0 {
+            return Ok(());
+        }
+        f.write_str("T")?;
+        if hours > 0 {
+            write!(f, "{}H", hours)?;
+        }
+        if minutes > 0 {
+            write!(f, "{}M", minutes)?;
+        }
+        if nanos > 0 {
+            let fraction = format!("{:09}", nanos);
+            write!(f, "{}.{}S", seconds, fraction.trim_end_matches('0'))
+        } else if seconds > 0 || secs == 0 {
+            write!(f, "{}S", seconds)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl FromStr for TaskDuration {
+    type Err = ValidationError;
+
+    /// Parses an ISO 8601 duration such as `PT15M`, `P1DT2H` or `P2W`. Only
+    /// the seconds may have a fractional part.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ValidationError::InvalidDuration(s.to_string());
+        let rest = s.strip_prefix('P').ok_or_else(invalid)?;
+        let (date, time) = match rest.split_once('T') {
+            Some((_, "")) => return Err(invalid()),
+            Some((date, time)) => (date, time),
+            None => (rest, ""),
+        };
+        if date.is_empty() && time.is_empty() {
+            return Err(invalid());
+        }
+
+        let date = components(date, &[('W', WEEK), ('D', DAY)]).ok_or_else(invalid)?;
+        let time = components(time, &[('H', HOUR), ('M', MINUTE), ('S', 1)]).ok_or_else(invalid)?;
+        date.checked_add(time).map(TaskDuration).ok_or_else(invalid)
+    }
+}
+
+/// Sums the `<number><unit>` components of one part of a duration. Units
+/// must appear in the order given, each at most once.
+fn components(mut part: &str, units: &[(char, u64)]) -> Option<Duration> {
+    let mut total = Duration::ZERO;
+    let mut next_unit = 0;
+
+    while !part.is_empty() {
+        let len = part.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let (number, rest) = part.split_at(len);
+        let designator = rest.chars().next()?;
+        let index = next_unit + units[next_unit..].iter().position(|&(unit, _)| unit == designator)?;
+        let scale = units[index].1;
+
+        let (whole, fraction) = match number.split_once('.') {
+            Some((whole, fraction)) if scale == 1 && !fraction.is_empty() => (whole, fraction),
+            Some(_) => return None,
+            None => (number, ""),
+        };
+        if whole.is_empty() {
+            return None;
+        }
+        let seconds = whole.parse::<u64>().ok()?.checked_mul(scale)?;
+        let digits = &fraction[..fraction.len().min(9)];
+        let nanos = if digits.is_empty() {
+            0
+        } else {
+            digits.parse::<u32>().ok()? * 10u32.pow(9 - digits.len() as u32)
+        };
+
+        total = total.checked_add(Duration::new(seconds, nanos))?;
+        next_unit = index + 1;
+        part = &rest[designator.len_utf8()..];
+    }
+
+    Some(total)
+}
+
+/// A task setting that holds a [`TaskDuration`], used to look up the range
+/// Task Scheduler accepts for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DurationField {
+    /// Time between runs of a repeating trigger.
+    RepetitionInterval,
+    /// How long a trigger keeps repeating.
+    RepetitionDuration,
+    /// How long a task, or the run started by a trigger, may run.
+    ExecutionTimeLimit,
+    /// Fixed wait between a trigger's event and the start of the task.
+    Delay,
+    /// Upper bound of the random wait added to a scheduled start.
+    RandomDelay,
+    /// Time between restarts of a failed task.
+    RestartInterval,
+    /// How long the computer must be idle before the task starts.
+    IdleDuration,
+    /// How long to wait for the computer to become idle.
+    IdleWaitTimeout,
+    /// How long an expired task is kept before it is deleted.
+    DeleteExpiredTaskAfter,
+    /// How often a task runs during regular maintenance.
+    MaintenancePeriod,
+}
+
+impl DurationField {
+    /// Name of the setting's element in Task XML.
+    pub fn name(self) -> &'static str {
+        match self {
+            DurationField::RepetitionInterval => "Interval",
+            DurationField::RepetitionDuration => "Duration",
+            DurationField::ExecutionTimeLimit => "ExecutionTimeLimit",
+            DurationField::Delay => "Delay",
+            DurationField::RandomDelay => "RandomDelay",
+            DurationField::RestartInterval => "RestartInterval",
+            DurationField::IdleDuration => "IdleDuration",
+            DurationField::IdleWaitTimeout => "WaitTimeout",
+            DurationField::DeleteExpiredTaskAfter => "DeleteExpiredTaskAfter",
+            DurationField::MaintenancePeriod => "Period",
+        }
+    }
+
+    /// Smallest and largest accepted values; `None` means no upper bound.
+    pub fn range(self) -> (TaskDuration, Option<TaskDuration>) {
+        match self {
+            DurationField::RepetitionInterval
+            | DurationField::RestartInterval
+            | DurationField::IdleDuration
+            | DurationField::IdleWaitTimeout => {
+                (TaskDuration::from_mins(1), Some(TaskDuration::from_days(31)))
+            }
+            DurationField::RepetitionDuration => (TaskDuration::from_mins(1), None),
+            DurationField::MaintenancePeriod => (TaskDuration::from_days(1), None),
+            DurationField::ExecutionTimeLimit
+            | DurationField::Delay
+            | DurationField::RandomDelay
+            | DurationField::DeleteExpiredTaskAfter => (TaskDuration::ZERO, None),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for TaskDuration {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TaskDuration {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = TaskDuration;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an ISO 8601 duration such as PT15M")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<TaskDuration, E> {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<TaskDuration, ValidationError> {
+        s.parse()
+    }
+
+    #[test]
+    fn formats_each_component() {
+        let cases = [
+            (TaskDuration::ZERO, "PT0S"),
+            (TaskDuration::from_mins(15), "PT15M"),
+            (TaskDuration::from_hours(8), "PT8H"),
+            (TaskDuration::from_secs(DAY + 2 * HOUR), "P1DT2H"),
+            (TaskDuration::from_days(3), "P3D"),
+            (TaskDuration::from_secs(HOUR + 30), "PT1H30S"),
+            (Duration::from_millis(1500).into(), "PT1.5S"),
+            (Duration::from_millis(250).into(), "PT0.25S"),
+        ];
+        for (duration, text) in cases {
+            assert_eq!(duration.to_string(), text);
+            assert_eq!(parse(text), Ok(duration));
+        }
+    }
+
+    #[test]
+    fn parses_weeks_and_unnormalized_values() {
+        assert_eq!(parse("P2W"), Ok(TaskDuration::from_days(14)));
+        assert_eq!(parse("PT90M"), Ok(TaskDuration::from_mins(90)));
+        assert_eq!(parse("P1DT0H0M0S"), Ok(TaskDuration::from_days(1)));
+        assert_eq!(parse("PT0.0000001S"), Ok(Duration::from_nanos(100).into()));
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        for text in [
+            "", "P", "PT", "T1H", "1H", "PT1", "P1H", "PT1D", "PT1M1H", "PT1H1H", "P1Y", "P1M",
+            "PT1.5M", "PT.5S", "PT1.S", "P-1D", "PT 1S", "PT99999999999999999999S",
+        ] {
+            assert_eq!(parse(text), Err(ValidationError::InvalidDuration(text.to_string())), "{}", text);
+        }
+    }
+
+    #[test]
+    fn converts_to_and_from_std_durations() {
+        let duration = Duration::from_secs(90);
+        assert_eq!(Duration::from(TaskDuration::from(duration)), duration);
+        assert_eq!(TaskDuration::from_mins(2).as_duration(), Duration::from_secs(120));
+    }
+
+    #[test]
+    fn checks_ranges_per_field() {
+        let field = DurationField::RestartInterval;
+        assert_eq!(TaskDuration::from_mins(1).check(field), Ok(()));
+        assert_eq!(TaskDuration::from_days(31).check(field), Ok(()));
+        for value in [TaskDuration::from_secs(59), TaskDuration::from_secs(31 * DAY + 1)] {
+            assert_eq!(value.check(field), Err(ValidationError::DurationOutOfRange { field, value }));
+        }
+        assert_eq!(TaskDuration::ZERO.check(DurationField::DeleteExpiredTaskAfter), Ok(()));
+        assert_eq!(TaskDuration::from_days(365).check(DurationField::RepetitionDuration), Ok(()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serializes_as_an_iso_8601_string() {
+        let duration = TaskDuration::from_secs(DAY + 2 * HOUR);
+        assert_eq!(serde_json::to_string(&duration).unwrap(), "\"P1DT2H\"");
+        assert_eq!(serde_json::from_str::<TaskDuration>("\"PT15M\"").unwrap(), TaskDuration::from_mins(15));
+        assert!(serde_json::from_str::<TaskDuration>("\"P1M\"").is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+/// Replaces `%NAME%` references in `text` with values from `env`, the way
+/// `ExpandEnvironmentStrings` does when the action runs.
+///
+/// Names are matched without regard to case, as Windows environment
+/// variables are. References to variables missing from `env` are left as
+/// they are, and their closing `%` may open the next reference.
+pub fn expand_env_vars(text: &str, env: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('%') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('%') else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let name = &after[..end];
+        match lookup(env, name) {
+            Some(value) if !name.is_empty() => {
+                out.push_str(value);
+                rest = &after[end + 1..];
+            }
+            _ => {
+                out.push('%');
+                out.push_str(name);
+                rest = &after[end..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+fn lookup<'a>(env: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    env.iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env() -> HashMap<String, String> {
+        [("SystemRoot", "C:\\Windows"), ("ProgramData", "C:\\ProgramData"), ("USER", "alice")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn expands_known_variables_ignoring_case() {
+        assert_eq!(
+            expand_env_vars("%systemroot%\\System32\\%USER%.log", &env()),
+            "C:\\Windows\\System32\\alice.log"
+        );
+        assert_eq!(expand_env_vars("%ProgramData%%USER%", &env()), "C:\\ProgramDataalice");
+    }
+
+    #[test]
+    fn leaves_unknown_and_unterminated_references() {
+        assert_eq!(expand_env_vars("%MISSING%\\x", &env()), "%MISSING%\\x");
+        assert_eq!(expand_env_vars("100%", &env()), "100%");
+        assert_eq!(expand_env_vars("%%", &env()), "%%");
+        assert_eq!(expand_env_vars("50% of %USER%", &env()), "50% of alice");
+        assert_eq!(expand_env_vars("%MISSING%USER%", &env()), "%MISSINGalice");
+    }
+}
@@ -0,0 +1,146 @@
+//! Platform-neutral description of a scheduled task.
+//!
+//! Nothing in this module talks to Windows: a [`TaskDefinition`] is plain
+//! data that a [`SchedulerBackend`](crate::SchedulerBackend) consumes when
+//! the task is registered.
+
+use std::collections::HashMap;
+
+mod action;
+mod calendar;
+mod command_line;
+mod duration;
+mod env;
+mod principal;
+mod registration;
+mod settings;
+mod timestamp;
+mod trigger;
+mod validate;
+
+pub use action::{Action, ActionKind, ComHandlerAction, EmailAction, ExecAction, ShowMessageAction};
+pub use calendar::{DaysOfMonth, DaysOfWeek, Months, WeeksOfMonth};
+pub use command_line::{join_args, split_args};
+pub use duration::{DurationField, TaskDuration};
+pub use env::expand_env_vars;
+pub use principal::{LogonType, Principal, ProcessTokenSidType, RunLevel, ServiceAccount};
+pub use registration::RegistrationInfo;
+pub use settings::{
+    Compatibility,
+    IdleSettings,
+    MaintenanceSettings,
+    MultipleInstancesPolicy,
+    NetworkSettings,
+    RestartOnFailure,
+    TaskSettings,
+};
+pub use timestamp::{TimeZone, Timestamp};
+pub use trigger::{
+    DailyTrigger,
+    EventTrigger,
+    LogonTrigger,
+    MonthlyDayOfWeekTrigger,
+    MonthlyTrigger,
+    Repetition,
+    SessionStateChange,
+    SessionStateChangeTrigger,
+    Trigger,
+    TriggerKind,
+    WeeklyTrigger,
+};
+pub use validate::{validate_folder, validate_task_name, ValidationError};
+
+/// Most triggers Task Scheduler accepts on one task.
+pub const MAX_TRIGGERS: usize = 48;
+
+/// Most actions Task Scheduler accepts on one task.
+pub const MAX_ACTIONS: usize = 32;
+
+/// Everything Task Scheduler needs to know about a task, minus its name
+/// and the folder it is registered in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskDefinition {
+    /// Descriptive information such as the author and description.
+    pub registration_info: RegistrationInfo,
+    /// The security context the task runs under.
+    pub principal: Principal,
+    /// Settings that control how Task Scheduler runs the task.
+    pub settings: TaskSettings,
+    /// Conditions that start the task, at most [`MAX_TRIGGERS`].
+    pub triggers: Vec<Trigger>,
+    /// Work performed, in order, when the task runs; at most
+    /// [`MAX_ACTIONS`].
+    pub actions: Vec<Action>,
+}
+
+impl TaskDefinition {
+    /// Creates an empty task definition with Task Scheduler's defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gives each trigger and action that has no id one made of its kind
+    /// and a number, e.g. `Daily2` for the second daily trigger. Numbers
+    /// count per kind and skip ids already taken, so adding a trigger of
+    /// one kind never renames those of another.
+    pub fn assign_ids(&mut self) {
+        let triggers = self.triggers.iter_mut().map(|t| (&mut t.id, t.kind.name()));
+        assign_ids(triggers);
+        let actions = self.actions.iter_mut().map(|a| (&mut a.id, a.kind.name()));
+        assign_ids(actions);
+    }
+}
+
+fn assign_ids<'a>(items: impl Iterator<Item = (&'a mut Option<String>, &'static str)>) {
+    let items: Vec<_> = items.collect();
+    let mut taken: Vec<String> = items.iter().filter_map(|(id, _)| (**id).clone()).collect();
+    let mut counters: HashMap<&str, usize> = HashMap::new();
+
+    for (id, prefix) in items {
+        if id.is_some() {
+            continue;
+        }
+        let counter = counters.entry(prefix).or_insert(0);
+        let candidate = loop {
+            *counter += 1;
+            let candidate = format!("{}{}", prefix, counter);
+            if !taken.contains(&candidate) {
+                break candidate;
+            }
+        };
+        taken.push(candidate.clone());
+        *id = Some(candidate);
+    }
+}
+
+/// A task definition together with the name and folder it is registered
+/// under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    /// Name of the task within its folder.
+    pub name: String,
+    /// Folder the task is registered in, e.g. `\` or `\Vendor\App`.
+    pub folder: String,
+    /// What the task does and when.
+    pub definition: TaskDefinition,
+}
+
+impl Task {
+    /// Full path of the task, e.g. `\Vendor\App\Backup`.
+    pub fn path(&self) -> String {
+        if self.folder.ends_with('\\') {
+            format!("{}{}", self.folder, self.name)
+        } else {
+            format!("{}\\{}", self.folder, self.name)
+        }
+    }
+}
+
+/// Where a task ended up after it was registered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisteredTaskInfo {
+    /// Name of the registered task.
+    pub name: String,
+    /// Full path of the registered task, e.g. `\Vendor\Backup`.
+    pub path: String,
+}
@@ -0,0 +1,279 @@
+/// The security context a task runs under (`IPrincipal`).
+///
+/// A principal names either a user or a group. Use the constructors for
+/// the usual combinations; [`TaskDefinition::validate`] rejects ones Task
+/// Scheduler would refuse.
+///
+/// [`TaskDefinition::validate`]: super::TaskDefinition::validate
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Principal {
+    /// Account the task runs as, e.g. `CORP\alice` or `S-1-5-18`. `None`
+    /// (with no group either) registers the task for the current user.
+    pub user_id: Option<String>,
+    /// Group whose members the task runs for, e.g. `BUILTIN\Users`.
+    pub group_id: Option<String>,
+    /// Name shown for the principal in the Task Scheduler UI.
+    pub display_name: Option<String>,
+    /// How the account is logged on. `None` lets Task Scheduler pick; see
+    /// [`Principal::effective_logon_type`].
+    pub logon_type: Option<LogonType>,
+    /// Privileges the task runs with. `None` is least privilege.
+    pub run_level: Option<RunLevel>,
+    /// SID the task's process token carries (Windows 7 and later). `None`
+    /// leaves the choice to Task Scheduler.
+    pub process_token_sid_type: Option<ProcessTokenSidType>,
+    /// Privileges the task's process token is limited to, e.g.
+    /// `SeChangeNotifyPrivilege` (Windows 7 and later). Empty keeps all of
+    /// the account's privileges.
+    pub required_privileges: Vec<String>,
+}
+
+impl Principal {
+    /// Runs the task as `user_id`, logged on with `logon_type`.
+    pub fn user(user_id: &str, logon_type: LogonType) -> Self {
+        Self {
+            user_id: Some(user_id.to_string()),
+            logon_type: Some(logon_type),
+            ..Self::default()
+        }
+    }
+
+    /// Runs the task as one of the built-in service accounts, named by its
+    /// SID.
+    pub fn service_account(account: ServiceAccount) -> Self {
+        Self::user(account.sid(), LogonType::ServiceAccount)
+    }
+
+    /// Runs the task as `LocalSystem` with the highest privileges, like
+    /// tasks created by `schtasks /ru SYSTEM`.
+    pub fn system() -> Self {
+        Self {
+            run_level: Some(RunLevel::Highest),
+            ..Self::service_account(ServiceAccount::System)
+        }
+    }
+
+    /// Runs the task for any logged-on member of `group_id`.
+    pub fn group(group_id: &str) -> Self {
+        Self {
+            group_id: Some(group_id.to_string()),
+            logon_type: Some(LogonType::Group),
+            ..Self::default()
+        }
+    }
+
+    /// The logon type Task Scheduler will use: the one given, or else
+    /// [`LogonType::Group`] for groups, [`LogonType::ServiceAccount`] for
+    /// service accounts and [`LogonType::InteractiveToken`] for anyone
+    /// else.
+    pub fn effective_logon_type(&self) -> LogonType {
+        match (self.logon_type, &self.user_id, &self.group_id) {
+            (Some(logon_type), _, _) => logon_type,
+            (None, None, Some(_)) => LogonType::Group,
+            (None, Some(user_id), _) if ServiceAccount::from_user_id(user_id).is_some() => {
+                LogonType::ServiceAccount
+            }
+            (None, _, _) => LogonType::InteractiveToken,
+        }
+    }
+}
+
+/// How a principal's account is logged on when the task runs
+/// (`TASK_LOGON_TYPE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogonType {
+    /// With a password stored at registration; the task runs whether or
+    /// not the user is logged on.
+    Password,
+    /// Service for User: without a password, and without network or
+    /// encrypted file access.
+    S4U,
+    /// Only while the user is logged on, using their session.
+    InteractiveToken,
+    /// Interactively when the user is logged on, with the stored password
+    /// otherwise.
+    InteractiveTokenOrPassword,
+    /// For the members of a group; the principal must have a group id.
+    Group,
+    /// As a built-in service account, which needs no password; see
+    /// [`ServiceAccount`].
+    ServiceAccount,
+}
+
+impl LogonType {
+    /// The value of the `LogonType` element in Task XML. Group and service
+    /// account logons have none: they follow from `GroupId` and `UserId`.
+    pub fn as_xml(self) -> Option<&'static str> {
+        match self {
+            LogonType::Password => Some("Password"),
+            LogonType::S4U => Some("S4U"),
+            LogonType::InteractiveToken => Some("InteractiveToken"),
+            LogonType::InteractiveTokenOrPassword => Some("InteractiveTokenOrPassword"),
+            LogonType::Group | LogonType::ServiceAccount => None,
+        }
+    }
+
+    /// Whether Task Scheduler stores the account's password, which must
+    /// then be given when the task is registered.
+    pub fn needs_password(self) -> bool {
+        matches!(self, LogonType::Password | LogonType::InteractiveTokenOrPassword)
+    }
+
+    /// Parses the value of the `LogonType` element.
+    pub fn parse(value: &str) -> Option<LogonType> {
+        match value {
+            "Password" => Some(LogonType::Password),
+            "S4U" => Some(LogonType::S4U),
+            "InteractiveToken" => Some(LogonType::InteractiveToken),
+            "InteractiveTokenOrPassword" => Some(LogonType::InteractiveTokenOrPassword),
+            _ => None,
+        }
+    }
+}
+
+/// Privileges a task runs with (`TASK_RUNLEVEL`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RunLevel {
+    /// The account's filtered, non-elevated token.
+    #[default]
+    LeastPrivilege,
+    /// The account's full token, elevated if UAC would otherwise filter
+    /// it.
+    Highest,
+}
+
+impl RunLevel {
+    /// The value of the `RunLevel` element in Task XML.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RunLevel::LeastPrivilege => "LeastPrivilege",
+            RunLevel::Highest => "HighestAvailable",
+        }
+    }
+
+    /// Parses the value of the `RunLevel` element.
+    pub fn parse(value: &str) -> Option<RunLevel> {
+        match value {
+            "LeastPrivilege" => Some(RunLevel::LeastPrivilege),
+            "HighestAvailable" => Some(RunLevel::Highest),
+            _ => None,
+        }
+    }
+}
+
+/// The SID a task's process token carries (`TASK_PROCESSTOKENSID`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProcessTokenSidType {
+    /// No task SID.
+    None,
+    /// A task SID derived from the task name, added to the token's
+    /// discretionary access control list.
+    Unrestricted,
+    /// Task Scheduler's default.
+    Default,
+}
+
+impl ProcessTokenSidType {
+    /// The value of the `ProcessTokenSidType` element in Task XML.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProcessTokenSidType::None => "None",
+            ProcessTokenSidType::Unrestricted => "Unrestricted",
+            ProcessTokenSidType::Default => "Default",
+        }
+    }
+
+    /// Parses the value of the `ProcessTokenSidType` element.
+    pub fn parse(value: &str) -> Option<ProcessTokenSidType> {
+        match value {
+            "None" => Some(ProcessTokenSidType::None),
+            "Unrestricted" => Some(ProcessTokenSidType::Unrestricted),
+            "Default" => Some(ProcessTokenSidType::Default),
+            _ => None,
+        }
+    }
+}
+
+/// A built-in account a task can run as without a password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ServiceAccount {
+    /// `NT AUTHORITY\SYSTEM` (`S-1-5-18`).
+    System,
+    /// `NT AUTHORITY\LOCAL SERVICE` (`S-1-5-19`).
+    LocalService,
+    /// `NT AUTHORITY\NETWORK SERVICE` (`S-1-5-20`).
+    NetworkService,
+}
+
+impl ServiceAccount {
+    const ALL: [ServiceAccount; 3] = [ServiceAccount::System, ServiceAccount::LocalService, ServiceAccount::NetworkService];
+
+    /// The account's well-known SID.
+    pub fn sid(self) -> &'static str {
+        match self {
+            ServiceAccount::System => "S-1-5-18",
+            ServiceAccount::LocalService => "S-1-5-19",
+            ServiceAccount::NetworkService => "S-1-5-20",
+        }
+    }
+
+    /// The account's name, without the `NT AUTHORITY\` domain.
+    pub fn name(self) -> &'static str {
+        match self {
+            ServiceAccount::System => "SYSTEM",
+            ServiceAccount::LocalService => "LOCAL SERVICE",
+            ServiceAccount::NetworkService => "NETWORK SERVICE",
+        }
+    }
+
+    /// Recognizes a service account given as a SID, a name or an
+    /// `NT AUTHORITY\` name, ignoring case.
+    pub fn from_user_id(user_id: &str) -> Option<ServiceAccount> {
+        let name = match user_id.split_once('\\') {
+            Some((domain, name)) if domain.eq_ignore_ascii_case("NT AUTHORITY") => name,
+            Some(_) => return None,
+            None => user_id,
+        };
+        Self::ALL
+            .into_iter()
+            .find(|account| name.eq_ignore_ascii_case(account.sid()) || name.eq_ignore_ascii_case(account.name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_service_accounts_by_sid_and_name() {
+        for user_id in ["S-1-5-18", "system", "NT AUTHORITY\\SYSTEM", "nt authority\\s-1-5-18"] {
+            assert_eq!(ServiceAccount::from_user_id(user_id), Some(ServiceAccount::System), "{}", user_id);
+        }
+        assert_eq!(ServiceAccount::from_user_id("Network Service"), Some(ServiceAccount::NetworkService));
+        assert_eq!(ServiceAccount::from_user_id("CORP\\SYSTEM"), None);
+        assert_eq!(ServiceAccount::from_user_id("S-1-5-21-1-2-3-500"), None);
+    }
+
+    #[test]
+    fn infers_the_logon_type() {
+        assert_eq!(Principal::default().effective_logon_type(), LogonType::InteractiveToken);
+        assert_eq!(
+            Principal { user_id: Some("S-1-5-19".to_string()), ..Principal::default() }.effective_logon_type(),
+            LogonType::ServiceAccount
+        );
+        assert_eq!(
+            Principal { group_id: Some("BUILTIN\\Users".to_string()), ..Principal::default() }.effective_logon_type(),
+            LogonType::Group
+        );
+        assert_eq!(Principal::user("CORP\\svc", LogonType::S4U).effective_logon_type(), LogonType::S4U);
+    }
+
+    #[test]
+    fn only_password_logons_need_a_password() {
+        assert!(LogonType::Password.needs_password());
+        assert!(LogonType::InteractiveTokenOrPassword.needs_password());
+        for logon_type in [LogonType::S4U, LogonType::InteractiveToken, LogonType::Group, LogonType::ServiceAccount] {
+            assert!(!logon_type.needs_password(), "{:?}", logon_type);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+/// Administrative information about a task (`IRegistrationInfo`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegistrationInfo {
+    /// Author of the task.
+    pub author: Option<String>,
+    /// Free-form description of the task.
+    pub description: Option<String>,
+    /// Version number of the task.
+    pub version: Option<String>,
+    /// Date the task was registered, in ISO 8601 form.
+    pub date: Option<String>,
+    /// Additional documentation for the task.
+    pub documentation: Option<String>,
+    /// Where the task came from, e.g. a component or application name.
+    pub source: Option<String>,
+    /// URI of the task.
+    pub uri: Option<String>,
+    /// Security descriptor of the task, in SDDL form, e.g.
+    /// `D:(A;;FA;;;BA)`.
+    pub security_descriptor: Option<String>,
+}
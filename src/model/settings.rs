@@ -0,0 +1,207 @@
+use super::TaskDuration;
+
+/// Settings that control how Task Scheduler runs a task (`ITaskSettings`).
+///
+/// Defaults match the ones Task Scheduler applies to a new task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskSettings {
+    /// Allow the task to be run on demand, e.g. from the UI or
+    /// `schtasks /run`.
+    pub allow_demand_start: bool,
+    /// Allow the task to be terminated if it does not end when asked to
+    /// stop.
+    pub allow_hard_terminate: bool,
+    /// Start the task as soon as possible after a missed scheduled start.
+    pub start_when_available: bool,
+    /// Stop the task if the computer switches to battery power.
+    pub stop_if_going_on_batteries: bool,
+    /// Do not start the task while the computer runs on batteries.
+    pub disallow_start_if_on_batteries: bool,
+    /// What to do when the task is triggered while already running.
+    pub multiple_instances: MultipleInstancesPolicy,
+    /// Process priority of the task, from 0 (realtime) to 10 (idle).
+    pub priority: u8,
+    /// Restart the task if it fails, at most a number of times.
+    pub restart_on_failure: Option<RestartOnFailure>,
+    /// Start the task only while a network connection is available.
+    pub run_only_if_network_available: bool,
+    /// Start the task only when the computer is idle, as set in
+    /// `idle_settings`.
+    pub run_only_if_idle: bool,
+    /// How long the computer must be idle, and what happens when it stops
+    /// being idle.
+    pub idle_settings: IdleSettings,
+    /// The network that must be available when `run_only_if_network_available`
+    /// is set; any network will do if this is `None`.
+    pub network_settings: Option<NetworkSettings>,
+    /// Wake the computer to run the task.
+    pub wake_to_run: bool,
+    /// Hide the task in the Task Scheduler UI.
+    pub hidden: bool,
+    /// Whether the task runs at all.
+    pub enabled: bool,
+    /// Delete the task this long after it last expires. Needs a trigger
+    /// with an end boundary.
+    pub delete_expired_task_after: Option<TaskDuration>,
+    /// Stop the task after it has run this long; zero lets it run
+    /// indefinitely.
+    pub execution_time_limit: TaskDuration,
+    /// The Task Scheduler version the task is written for.
+    pub compatibility: Compatibility,
+    /// Run the task with the unified scheduling engine (Windows 7 and
+    /// later).
+    pub use_unified_scheduling_engine: bool,
+    /// Do not start the task in a remote application session (Windows 7
+    /// and later).
+    pub disallow_start_on_remote_app_session: bool,
+    /// Disable the task every time Windows starts (Windows 8 and later).
+    pub volatile: bool,
+    /// Run the task during automatic maintenance (Windows 8 and later).
+    pub maintenance_settings: Option<MaintenanceSettings>,
+}
+
+impl Default for TaskSettings {
+    fn default() -> Self {
+        Self {
+            allow_demand_start: true,
+            allow_hard_terminate: true,
+            start_when_available: false,
+            stop_if_going_on_batteries: true,
+            disallow_start_if_on_batteries: true,
+            multiple_instances: MultipleInstancesPolicy::default(),
+            priority: 7,
+            restart_on_failure: None,
+            run_only_if_network_available: false,
+            run_only_if_idle: false,
+            idle_settings: IdleSettings::default(),
+            network_settings: None,
+            wake_to_run: false,
+            hidden: false,
+            enabled: true,
+            delete_expired_task_after: None,
+            execution_time_limit: TaskDuration::from_hours(72),
+            compatibility: Compatibility::default(),
+            use_unified_scheduling_engine: false,
+            disallow_start_on_remote_app_session: false,
+            volatile: false,
+            maintenance_settings: None,
+        }
+    }
+}
+
+/// What Task Scheduler does when a task is triggered while an instance of
+/// it is already running (`TASK_INSTANCES_POLICY`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MultipleInstancesPolicy {
+    /// Start another instance alongside.
+    Parallel,
+    /// Start the new instance after the running one ends.
+    Queue,
+    /// Do not start a new instance.
+    #[default]
+    IgnoreNew,
+    /// Stop the running instance and start a new one.
+    StopExisting,
+}
+
+impl MultipleInstancesPolicy {
+    /// The value of the `MultipleInstancesPolicy` element in Task XML.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MultipleInstancesPolicy::Parallel => "Parallel",
+            MultipleInstancesPolicy::Queue => "Queue",
+            MultipleInstancesPolicy::IgnoreNew => "IgnoreNew",
+            MultipleInstancesPolicy::StopExisting => "StopExisting",
+        }
+    }
+
+    /// Parses the value of the `MultipleInstancesPolicy` element.
+    pub fn parse(value: &str) -> Option<MultipleInstancesPolicy> {
+        match value {
+            "Parallel" => Some(MultipleInstancesPolicy::Parallel),
+            "Queue" => Some(MultipleInstancesPolicy::Queue),
+            "IgnoreNew" => Some(MultipleInstancesPolicy::IgnoreNew),
+            "StopExisting" => Some(MultipleInstancesPolicy::StopExisting),
+            _ => None,
+        }
+    }
+}
+
+/// How often, and how far apart, a failed task is restarted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RestartOnFailure {
+    /// Number of restarts, from 1 to 999.
+    pub count: u16,
+    /// Time between restarts, from one minute to 31 days.
+    pub interval: TaskDuration,
+}
+
+/// When a task that runs only while the computer is idle starts and stops
+/// (`IIdleSettings`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IdleSettings {
+    /// How long the computer must have been idle before the task starts.
+    pub idle_duration: TaskDuration,
+    /// How long to wait for the computer to become idle before giving up.
+    pub wait_timeout: TaskDuration,
+    /// Stop the task when the computer stops being idle.
+    pub stop_on_idle_end: bool,
+    /// Start the task again when the computer becomes idle again.
+    pub restart_on_idle: bool,
+}
+
+impl Default for IdleSettings {
+    fn default() -> Self {
+        Self {
+            idle_duration: TaskDuration::from_mins(10),
+            wait_timeout: TaskDuration::from_hours(1),
+            stop_on_idle_end: true,
+            restart_on_idle: false,
+        }
+    }
+}
+
+/// How often a task runs during automatic maintenance
+/// (`IMaintenanceSettings`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaintenanceSettings {
+    /// How often the task runs during regular maintenance, at least a day.
+    pub period: TaskDuration,
+    /// How long after a missed period the task runs during emergency
+    /// maintenance, longer than the period. `None` waits for regular
+    /// maintenance.
+    pub deadline: Option<TaskDuration>,
+    /// Run the task on its own rather than alongside other maintenance
+    /// tasks.
+    pub exclusive: bool,
+}
+
+/// The network profile a task waits for (`INetworkSettings`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct NetworkSettings {
+    /// Name of the network profile, e.g. `CorpNet`.
+    pub name: Option<String>,
+    /// GUID of the network profile, in braces.
+    pub id: Option<String>,
+}
+
+/// The Task Scheduler version a task is written for
+/// (`TASK_COMPATIBILITY`), which limits the features it may use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Compatibility {
+    /// The `AT` command.
+    At,
+    /// Task Scheduler 1.0 (Windows XP and Windows Server 2003).
+    V1,
+    /// Task Scheduler 2.0 (Windows Vista and Windows Server 2008).
+    #[default]
+    V2,
+    /// Windows 7 and Windows Server 2008 R2.
+    V2_1,
+    /// Windows 8 and Windows Server 2012.
+    V2_2,
+    /// Windows 10 and Windows Server 2016.
+    V2_3,
+    /// Later Windows 10 releases.
+    V2_4,
+}
@@ -0,0 +1,346 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::ValidationError;
+
+/// The time zone a [`Timestamp`] is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeZone {
+    /// The local time of the machine running the task, which follows its
+    /// daylight saving rules. Written without a suffix.
+    Local,
+    /// Coordinated Universal Time. Written with a `Z` suffix.
+    Utc,
+    /// A fixed offset from UTC in minutes, from -14:00 to +14:00. Written as
+    /// `+HH:MM` or `-HH:MM`.
+    Offset(i16),
+}
+
+/// A date and time such as a trigger's start or end boundary, in the ISO
+/// 8601 form Task Scheduler uses: `2024-03-19T08:30:00`, optionally
+/// followed by `Z` or an offset such as `+01:00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timestamp {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    nanosecond: u32,
+    zone: TimeZone,
+}
+
+/// Largest offset from UTC in use, in minutes.
+const MAX_OFFSET: i16 = 14 * 60;
+
+impl Timestamp {
+    /// A timestamp in local time. Years are limited to 1601 to 9999, the
+    /// range Task Scheduler can store and the format can write.
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Result<Self, ValidationError> {
+        let timestamp = Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            nanosecond: 0,
+            zone: TimeZone::Local,
+        };
+        let valid = (1601..=9999).contains(&year)
+            && (1..=12).contains(&month)
+            && (1..=days_in_month(year, month)).contains(&day)
+            && hour < 24
+            && minute < 60
+            && second < 60;
+        if !valid {
+            return Err(ValidationError::InvalidTimestamp(timestamp.to_string()));
+        }
+        Ok(timestamp)
+    }
+
+    /// The current time in UTC, to the second.
+    pub fn now_utc() -> Self {
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs() as i64);
+        let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+        let secs_of_day = secs.rem_euclid(86_400);
+        Self {
+            year: year as u16,
+            month,
+            day,
+            hour: (secs_of_day / 3600) as u8,
+            minute: (secs_of_day % 3600 / 60) as u8,
+            second: (secs_of_day % 60) as u8,
+            nanosecond: 0,
+            zone: TimeZone::Utc,
+        }
+    }
+
+    /// The same wall-clock time in UTC.
+    pub fn utc(self) -> Self {
+        Self { zone: TimeZone::Utc, ..self }
+    }
+
+    /// The same wall-clock time at `minutes` east of UTC.
+    pub fn with_offset(self, minutes: i16) -> Result<Self, ValidationError> {
+        let timestamp = Self { zone: TimeZone::Offset(minutes), ..self };
+        if !(-MAX_OFFSET..=MAX_OFFSET).contains(&minutes) {
+            return Err(ValidationError::InvalidTimestamp(timestamp.to_string()));
+        }
+        Ok(timestamp)
+    }
+
+    /// The same wall-clock time in local time.
+    pub fn local(self) -> Self {
+        Self { zone: TimeZone::Local, ..self }
+    }
+
+    /// The year, month and day.
+    pub fn date(&self) -> (u16, u8, u8) {
+        (self.year, self.month, self.day)
+    }
+
+    /// The hour, minute and second, leaving out fractions of a second.
+    pub fn time(&self) -> (u8, u8, u8) {
+        (self.hour, self.minute, self.second)
+    }
+
+    /// The time zone the wall-clock time is in.
+    pub fn zone(&self) -> TimeZone {
+        self.zone
+    }
+
+    /// Orders two timestamps in time. Local timestamps are compared with
+    /// each other by wall clock and the others as instants; a local
+    /// timestamp and one with a fixed offset have no known order.
+    pub fn compare(&self, other: &Timestamp) -> Option<Ordering> {
+        match (self.zone, other.zone) {
+            (TimeZone::Local, TimeZone::Local) => {}
+            (TimeZone::Local, _) | (_, TimeZone::Local) => return None,
+            _ => {}
+        }
+        let key = |t: &Timestamp| (t.wall_clock_seconds() - t.offset_minutes() * 60, t.nanosecond);
+        Some(key(self).cmp(&key(other)))
+    }
+
+    /// Seconds from 1970-01-01T00:00:00 to the wall-clock time, ignoring
+    /// the time zone.
+    fn wall_clock_seconds(&self) -> i64 {
+        let days = days_from_civil(i64::from(self.year), self.month, self.day);
+        days * 86_400 + i64::from(self.hour) * 3600 + i64::from(self.minute) * 60 + i64::from(self.second)
+    }
+
+    fn offset_minutes(&self) -> i64 {
+        match self.zone {
+            TimeZone::Offset(minutes) => i64::from(minutes),
+            TimeZone::Local | TimeZone::Utc => 0,
+        }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )?;
+        if self.nanosecond != 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        match self.zone {
+            TimeZone::Local => Ok(()),
+            TimeZone::Utc => write!(f, "Z"),
+            TimeZone::Offset(minutes) => {
+                let sign = if minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.unsigned_abs();
+                write!(f, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = ValidationError;
+
+    /// Parses `YYYY-MM-DDTHH:MM:SS`, with optional fractional seconds and an
+    /// optional `Z` or `±HH:MM` suffix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ValidationError::InvalidTimestamp(s.to_string());
+        let bytes = s.as_bytes();
+        if bytes.len() < 19 || !s.is_char_boundary(19) {
+            return Err(invalid());
+        }
+        let separators = [(4, b'-'), (7, b'-'), (10, b'T'), (13, b':'), (16, b':')];
+        if separators.iter().any(|&(at, c)| bytes[at] != c) {
+            return Err(invalid());
+        }
+        let field = |range: std::ops::Range<usize>| -> Result<u16, ValidationError> {
+            let digits = &s[range];
+            if !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            digits.parse().map_err(|_| invalid())
+        };
+        let mut timestamp = Timestamp::new(
+            field(0..4)?,
+            field(5..7)? as u8,
+            field(8..10)? as u8,
+            field(11..13)? as u8,
+            field(14..16)? as u8,
+            field(17..19)? as u8,
+        )
+        .map_err(|_| invalid())?;
+
+        let mut rest = &s[19..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+            if len == 0 {
+                return Err(invalid());
+            }
+            // Digits past nanoseconds are dropped
+            let digits = &fraction[..len.min(9)];
+            let nanosecond: u32 = digits.parse().map_err(|_| invalid())?;
+            timestamp.nanosecond = nanosecond * 10u32.pow(9 - digits.len() as u32);
+            rest = &fraction[len..];
+        }
+
+        match rest.as_bytes() {
+            [] => Ok(timestamp),
+            [b'Z'] => Ok(timestamp.utc()),
+            [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2]
+                if [h1, h2, m1, m2].iter().all(|b| b.is_ascii_digit()) =>
+            {
+                let hours = i16::from((h1 - b'0') * 10 + (h2 - b'0'));
+                let minutes = i16::from((m1 - b'0') * 10 + (m2 - b'0'));
+                if minutes >= 60 {
+                    return Err(invalid());
+                }
+                let offset = hours * 60 + minutes;
+                let offset = if *sign == b'-' { -offset } else { offset };
+                timestamp.with_offset(offset).map_err(|_| invalid())
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days from 1970-01-01 to the given date in the proleptic Gregorian
+/// calendar.
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The date `days` after 1970-01-01, the inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u8;
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> Timestamp {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn formats_each_time_zone() {
+        let local = Timestamp::new(2024, 3, 9, 8, 5, 0).unwrap();
+
+        assert_eq!(local.to_string(), "2024-03-09T08:05:00");
+        assert_eq!(local.utc().to_string(), "2024-03-09T08:05:00Z");
+        assert_eq!(local.with_offset(330).unwrap().to_string(), "2024-03-09T08:05:00+05:30");
+        assert_eq!(local.with_offset(-480).unwrap().to_string(), "2024-03-09T08:05:00-08:00");
+    }
+
+    #[test]
+    fn parses_what_it_formats() {
+        for s in [
+            "2024-03-19T00:00:00",
+            "2024-03-19T23:59:59Z",
+            "2024-02-29T12:00:00+14:00",
+            "1999-12-31T06:30:15-03:30",
+            "2019-07-08T10:46:50.6467052",
+        ] {
+            assert_eq!(at(s).to_string(), s);
+        }
+        assert_eq!(at("2024-03-19T00:00:00.500+00:00").to_string(), "2024-03-19T00:00:00.5+00:00");
+    }
+
+    #[test]
+    fn rejects_impossible_dates_and_malformed_text() {
+        assert_eq!(
+            Timestamp::new(2023, 2, 29, 0, 0, 0),
+            Err(ValidationError::InvalidTimestamp("2023-02-29T00:00:00".to_string()))
+        );
+        assert!(Timestamp::new(2024, 2, 29, 0, 0, 0).is_ok());
+        assert!(Timestamp::new(1900, 2, 29, 0, 0, 0).is_err());
+        assert!(Timestamp::new(2024, 4, 31, 0, 0, 0).is_err());
+        assert!(Timestamp::new(2024, 1, 1, 24, 0, 0).is_err());
+        assert!(Timestamp::new(2024, 1, 1, 0, 0, 0).unwrap().with_offset(15 * 60).is_err());
+
+        for s in [
+            "",
+            "2024-03-19",
+            "2024-03-19 00:00:00",
+            "2024-3-19T00:00:00",
+            "2024-03-19T00:00:00+1:00",
+            "2024-03-19T00:00:00+01:60",
+            "2024-03-19T00:00:00.",
+            "2024-03-19T00:00:00Zjunk",
+            "+024-03-19T00:00:00",
+        ] {
+            assert!(s.parse::<Timestamp>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn compares_instants_across_offsets() {
+        assert_eq!(at("2024-01-01T10:00:00Z").compare(&at("2024-01-01T11:00:00+01:00")), Some(Ordering::Equal));
+        assert_eq!(at("2024-01-01T10:00:00Z").compare(&at("2024-01-01T10:30:00+01:00")), Some(Ordering::Greater));
+        assert_eq!(at("2024-12-31T23:59:59").compare(&at("2025-01-01T00:00:00")), Some(Ordering::Less));
+        assert_eq!(at("2024-01-01T10:00:00").compare(&at("2024-01-01T10:00:00Z")), None);
+    }
+
+    #[test]
+    fn converts_days_to_dates_and_back() {
+        for (year, month, day) in [(1970, 1, 1), (1601, 1, 1), (2000, 2, 29), (2024, 12, 31), (9999, 12, 31)] {
+            assert_eq!(civil_from_days(days_from_civil(year, month, day)), (year, month, day));
+        }
+        let now = Timestamp::now_utc();
+        assert_eq!(now.zone(), TimeZone::Utc);
+        assert!(now.compare(&at("2024-01-01T00:00:00Z")).is_some_and(Ordering::is_gt));
+    }
+}
@@ -0,0 +1,405 @@
+use super::{DaysOfMonth, DaysOfWeek, Months, TaskDuration, Timestamp, WeeksOfMonth};
+
+/// A condition that starts a task (`ITrigger`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trigger {
+    /// Identifier of the trigger within its task, e.g. `Daily1`. Task
+    /// builders fill in missing ids; see [`TaskDefinition::assign_ids`].
+    ///
+    /// [`TaskDefinition::assign_ids`]: super::TaskDefinition::assign_ids
+    pub id: Option<String>,
+    /// Date and time at which the trigger is activated. Time and calendar
+    /// triggers count their schedule from it.
+    pub start_boundary: Option<Timestamp>,
+    /// Date and time at which the trigger is deactivated; must be after
+    /// the start boundary.
+    pub end_boundary: Option<Timestamp>,
+    /// How often the task is run again after the trigger fires.
+    pub repetition: Option<Repetition>,
+    /// How long the task started by this trigger may run before it is
+    /// stopped. `None` means no limit beyond the task's own.
+    pub execution_time_limit: Option<TaskDuration>,
+    /// Fixed wait between the event and the start of the task. Only logon,
+    /// boot, registration, session state change and event triggers accept
+    /// a delay.
+    pub delay: Option<TaskDuration>,
+    /// Upper bound of a random wait added to each scheduled start. Only
+    /// time and calendar triggers accept a random delay.
+    pub random_delay: Option<TaskDuration>,
+    /// Whether the trigger fires at all.
+    pub enabled: bool,
+    /// What fires the trigger.
+    pub kind: TriggerKind,
+}
+
+impl Trigger {
+    /// Creates an enabled trigger with no id and no boundaries.
+    pub fn new(kind: TriggerKind) -> Self {
+        Self {
+            id: None,
+            start_boundary: None,
+            end_boundary: None,
+            repetition: None,
+            execution_time_limit: None,
+            delay: None,
+            random_delay: None,
+            enabled: true,
+            kind,
+        }
+    }
+
+    /// Creates a trigger that fires when `user_id` logs on, or when any
+    /// user logs on if `user_id` is `None`.
+    pub fn logon(user_id: Option<&str>) -> Self {
+        Self::new(TriggerKind::Logon(LogonTrigger {
+            user_id: user_id.map(str::to_string),
+        }))
+    }
+
+    /// Creates a trigger that fires when the system starts.
+    pub fn boot() -> Self {
+        Self::new(TriggerKind::Boot)
+    }
+
+    /// Creates a trigger that fires when the system becomes idle, as set
+    /// out in the task's idle settings.
+    pub fn idle() -> Self {
+        Self::new(TriggerKind::Idle)
+    }
+
+    /// Creates a trigger that fires when the task is registered or
+    /// updated.
+    pub fn registration() -> Self {
+        Self::new(TriggerKind::Registration)
+    }
+
+    /// Creates a trigger that fires on `state_change` in the session of
+    /// `user_id`, or in any session if `user_id` is `None`.
+    pub fn session_state_change(state_change: SessionStateChange, user_id: Option<&str>) -> Self {
+        Self::new(TriggerKind::SessionStateChange(SessionStateChangeTrigger {
+            state_change,
+            user_id: user_id.map(str::to_string),
+        }))
+    }
+
+    /// Creates a trigger that fires when an event matching `subscription`
+    /// is logged. The subscription is a `QueryList` document, such as one
+    /// built with [`EventQuery`](crate::EventQuery).
+    pub fn event(subscription: &str) -> Self {
+        Self::new(TriggerKind::Event(EventTrigger {
+            subscription: subscription.to_string(),
+            ..EventTrigger::default()
+        }))
+    }
+
+    /// Creates a trigger that fires once, at `start`.
+    pub fn once(start: Timestamp) -> Self {
+        Self::new(TriggerKind::Time).starts_at(start)
+    }
+
+    /// Creates a trigger that fires every `days_interval` days from `start`.
+    pub fn daily(start: Timestamp, days_interval: u16) -> Self {
+        Self::new(TriggerKind::Daily(DailyTrigger { days_interval })).starts_at(start)
+    }
+
+    /// Creates a trigger that fires on `days_of_week` every `weeks_interval`
+    /// weeks from `start`.
+    pub fn weekly(start: Timestamp, days_of_week: DaysOfWeek, weeks_interval: u16) -> Self {
+        Self::new(TriggerKind::Weekly(WeeklyTrigger { days_of_week, weeks_interval }))
+            .starts_at(start)
+    }
+
+    /// Creates a trigger that fires on `days_of_month` of `months` from
+    /// `start`.
+    pub fn monthly(start: Timestamp, days_of_month: DaysOfMonth, months: Months) -> Self {
+        Self::new(TriggerKind::Monthly(MonthlyTrigger {
+            days_of_month,
+            months,
+            run_on_last_day_of_month: false,
+        }))
+        .starts_at(start)
+    }
+
+    /// Creates a trigger that fires on `days_of_week` in `weeks_of_month` of
+    /// `months` from `start`, e.g. the first Monday of every month.
+    pub fn monthly_day_of_week(
+        start: Timestamp,
+        weeks_of_month: WeeksOfMonth,
+        days_of_week: DaysOfWeek,
+        months: Months,
+    ) -> Self {
+        Self::new(TriggerKind::MonthlyDayOfWeek(MonthlyDayOfWeekTrigger {
+            days_of_week,
+            weeks_of_month,
+            months,
+            run_on_last_week_of_month: false,
+        }))
+        .starts_at(start)
+    }
+
+    /// Sets the date and time at which the trigger is activated.
+    pub fn starts_at(mut self, start: Timestamp) -> Self {
+        self.start_boundary = Some(start);
+        self
+    }
+
+    /// Sets the date and time at which the trigger is deactivated.
+    pub fn ends_at(mut self, end: Timestamp) -> Self {
+        self.end_boundary = Some(end);
+        self
+    }
+
+    /// Whether the trigger follows a schedule, and so needs a start
+    /// boundary to count from.
+    pub fn is_scheduled(&self) -> bool {
+        matches!(
+            self.kind,
+            TriggerKind::Time
+                | TriggerKind::Daily(_)
+                | TriggerKind::Weekly(_)
+                | TriggerKind::Monthly(_)
+                | TriggerKind::MonthlyDayOfWeek(_)
+        )
+    }
+
+    /// Whether the trigger's kind accepts a [`delay`](Trigger::delay).
+    pub fn accepts_delay(&self) -> bool {
+        matches!(
+            self.kind,
+            TriggerKind::Logon(_)
+                | TriggerKind::Boot
+                | TriggerKind::Registration
+                | TriggerKind::SessionStateChange(_)
+                | TriggerKind::Event(_)
+        )
+    }
+}
+
+/// How a task is run again after its trigger fires (`IRepetitionPattern`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repetition {
+    /// Time between runs, from 1 minute to 31 days.
+    pub interval: TaskDuration,
+    /// How long to keep repeating, at least the interval. `None` repeats
+    /// indefinitely.
+    pub duration: Option<TaskDuration>,
+    /// Whether a running instance is stopped when the duration ends.
+    pub stop_at_duration_end: bool,
+}
+
+impl Repetition {
+    /// Repeats every `interval` for `duration`, or indefinitely if
+    /// `duration` is `None`.
+    pub fn new(interval: TaskDuration, duration: Option<TaskDuration>) -> Self {
+        Self { interval, duration, stop_at_duration_end: false }
+    }
+}
+
+/// The event that fires a [`Trigger`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TriggerKind {
+    /// Fires when a user logs on (`TASK_TRIGGER_LOGON`).
+    Logon(LogonTrigger),
+    /// Fires when the system starts (`TASK_TRIGGER_BOOT`).
+    Boot,
+    /// Fires when the system becomes idle (`TASK_TRIGGER_IDLE`).
+    Idle,
+    /// Fires when the task is registered or updated
+    /// (`TASK_TRIGGER_REGISTRATION`).
+    Registration,
+    /// Fires when a user session connects, disconnects, locks or unlocks
+    /// (`TASK_TRIGGER_SESSION_STATE_CHANGE`).
+    SessionStateChange(SessionStateChangeTrigger),
+    /// Fires when a matching event is logged (`TASK_TRIGGER_EVENT`).
+    Event(EventTrigger),
+    /// Fires once, at the start boundary (`TASK_TRIGGER_TIME`).
+    Time,
+    /// Fires every few days (`TASK_TRIGGER_DAILY`).
+    Daily(DailyTrigger),
+    /// Fires on given days every few weeks (`TASK_TRIGGER_WEEKLY`).
+    Weekly(WeeklyTrigger),
+    /// Fires on given days of given months (`TASK_TRIGGER_MONTHLY`).
+    Monthly(MonthlyTrigger),
+    /// Fires on given days of given weeks of given months
+    /// (`TASK_TRIGGER_MONTHLYDOW`).
+    MonthlyDayOfWeek(MonthlyDayOfWeekTrigger),
+}
+
+impl TriggerKind {
+    /// Short name of the kind, used as the prefix of generated trigger ids.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TriggerKind::Logon(_) => "Logon",
+            TriggerKind::Boot => "Boot",
+            TriggerKind::Idle => "Idle",
+            TriggerKind::Registration => "Registration",
+            TriggerKind::SessionStateChange(_) => "SessionStateChange",
+            TriggerKind::Event(_) => "Event",
+            TriggerKind::Time => "Time",
+            TriggerKind::Daily(_) => "Daily",
+            TriggerKind::Weekly(_) => "Weekly",
+            TriggerKind::Monthly(_) => "Monthly",
+            TriggerKind::MonthlyDayOfWeek(_) => "MonthlyDayOfWeek",
+        }
+    }
+}
+
+/// Settings specific to a logon trigger (`ILogonTrigger`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogonTrigger {
+    /// User whose logon fires the trigger. `None` means any user.
+    pub user_id: Option<String>,
+}
+
+/// Settings specific to a session state change trigger
+/// (`ISessionStateChangeTrigger`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionStateChangeTrigger {
+    /// The change that fires the trigger.
+    pub state_change: SessionStateChange,
+    /// User whose session is watched. `None` means any user.
+    pub user_id: Option<String>,
+}
+
+/// Settings specific to an event trigger (`IEventTrigger`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventTrigger {
+    /// `QueryList` document selecting the events that fire the trigger.
+    pub subscription: String,
+    /// Values taken from the matching event, as pairs of a name and an
+    /// XPath into the event. Actions refer to them as `$(name)`.
+    pub value_queries: Vec<(String, String)>,
+    /// Fire only once this many matching events, from 1 to 32, are logged
+    /// within `period_of_occurrence`. `None` fires on every event.
+    pub number_of_occurrences: Option<u8>,
+    /// Window in which `number_of_occurrences` events must be logged.
+    pub period_of_occurrence: Option<TaskDuration>,
+    /// Name of a value query whose value groups the events that are
+    /// counted, so that only events with the same value add up.
+    pub matching_element: Option<String>,
+}
+
+impl EventTrigger {
+    /// Whether the trigger counts events rather than firing on each one.
+    pub fn counts_occurrences(&self) -> bool {
+        self.number_of_occurrences.is_some() || self.period_of_occurrence.is_some() || self.matching_element.is_some()
+    }
+}
+
+/// A change in a user session (`TASK_SESSION_STATE_CHANGE_TYPE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SessionStateChange {
+    /// A user connects to the local console.
+    ConsoleConnect,
+    /// A user disconnects from the local console.
+    ConsoleDisconnect,
+    /// A user connects through Remote Desktop.
+    RemoteConnect,
+    /// A user disconnects from Remote Desktop.
+    RemoteDisconnect,
+    /// The workstation is locked.
+    SessionLock,
+    /// The workstation is unlocked.
+    SessionUnlock,
+}
+
+impl SessionStateChange {
+    /// Value of the `StateChange` element in Task XML.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SessionStateChange::ConsoleConnect => "ConsoleConnect",
+            SessionStateChange::ConsoleDisconnect => "ConsoleDisconnect",
+            SessionStateChange::RemoteConnect => "RemoteConnect",
+            SessionStateChange::RemoteDisconnect => "RemoteDisconnect",
+            SessionStateChange::SessionLock => "SessionLock",
+            SessionStateChange::SessionUnlock => "SessionUnlock",
+        }
+    }
+
+    /// Parses the value of a `StateChange` element.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "ConsoleConnect" => Some(SessionStateChange::ConsoleConnect),
+            "ConsoleDisconnect" => Some(SessionStateChange::ConsoleDisconnect),
+            "RemoteConnect" => Some(SessionStateChange::RemoteConnect),
+            "RemoteDisconnect" => Some(SessionStateChange::RemoteDisconnect),
+            "SessionLock" => Some(SessionStateChange::SessionLock),
+            "SessionUnlock" => Some(SessionStateChange::SessionUnlock),
+            _ => None,
+        }
+    }
+}
+
+/// Settings specific to a daily trigger (`IDailyTrigger`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyTrigger {
+    /// Days between runs, from 1 to 365. `1` runs every day.
+    pub days_interval: u16,
+}
+
+impl Default for DailyTrigger {
+    fn default() -> Self {
+        Self { days_interval: 1 }
+    }
+}
+
+/// Settings specific to a weekly trigger (`IWeeklyTrigger`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeeklyTrigger {
+    /// Days of the week the task runs on.
+    pub days_of_week: DaysOfWeek,
+    /// Weeks between runs, from 1 to 52. `1` runs every week.
+    pub weeks_interval: u16,
+}
+
+impl Default for WeeklyTrigger {
+    fn default() -> Self {
+        Self { days_of_week: DaysOfWeek::empty(), weeks_interval: 1 }
+    }
+}
+
+/// Settings specific to a monthly trigger (`IMonthlyTrigger`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonthlyTrigger {
+    /// Days of the month the task runs on.
+    pub days_of_month: DaysOfMonth,
+    /// Months the task runs in.
+    pub months: Months,
+    /// Whether the task also runs on the last day of each month.
+    pub run_on_last_day_of_month: bool,
+}
+
+impl Default for MonthlyTrigger {
+    fn default() -> Self {
+        Self {
+            days_of_month: DaysOfMonth::default(),
+            months: Months::ALL,
+            run_on_last_day_of_month: false,
+        }
+    }
+}
+
+/// Settings specific to a monthly day-of-week trigger
+/// (`IMonthlyDOWTrigger`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonthlyDayOfWeekTrigger {
+    /// Days of the week the task runs on.
+    pub days_of_week: DaysOfWeek,
+    /// Weeks of the month the task runs in.
+    pub weeks_of_month: WeeksOfMonth,
+    /// Months the task runs in.
+    pub months: Months,
+    /// Whether the task also runs in the last week of each month.
+    pub run_on_last_week_of_month: bool,
+}
+
+impl Default for MonthlyDayOfWeekTrigger {
+    fn default() -> Self {
+        Self {
+            days_of_week: DaysOfWeek::empty(),
+            weeks_of_month: WeeksOfMonth::empty(),
+            months: Months::ALL,
+            run_on_last_week_of_month: false,
+        }
+    }
+}
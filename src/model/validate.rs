@@ -0,0 +1,467 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::{
+    Action,
+    ActionKind,
+    DurationField,
+    LogonType,
+    Principal,
+    ServiceAccount,
+    Task,
+    TaskDefinition,
+    TaskDuration,
+    TaskSettings,
+    Trigger,
+    TriggerKind,
+    MAX_ACTIONS,
+    MAX_TRIGGERS,
+};
+
+/// Characters Task Scheduler does not accept in task or folder names,
+/// besides control characters.
+const RESERVED_NAME_CHARS: &[char] = &['\\', '/', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Whether `name` has a character Task Scheduler does not accept. Control
+/// characters are rejected too: the other backends write names into
+/// line-based files, where a line break would split them.
+fn has_reserved_char(name: &str) -> bool {
+    name.contains(|c: char| RESERVED_NAME_CHARS.contains(&c) || c.is_control())
+}
+
+/// A task, or part of one, that Task Scheduler would reject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The task name is empty.
+    EmptyTaskName,
+    /// The task name contains a reserved character.
+    InvalidTaskName(String),
+    /// The folder path is not of the form `\` or `\A\B`.
+    InvalidFolder(String),
+    /// The task has no actions; Task Scheduler requires at least one.
+    NoActions,
+    /// The task has more than [`MAX_TRIGGERS`] triggers.
+    TooManyTriggers(usize),
+    /// The task has more than [`MAX_ACTIONS`] actions.
+    TooManyActions(usize),
+    /// Two triggers, or two actions, share an id.
+    DuplicateId(String),
+    /// An executable action has an empty path.
+    EmptyExecPath,
+    /// A COM handler action's class id is not a GUID in braces.
+    InvalidClassId(String),
+    /// A network profile id is not a GUID in braces.
+    InvalidNetworkId(String),
+    /// A principal combines settings Task Scheduler does not accept
+    /// together; the field says which.
+    IncompatiblePrincipal(&'static str),
+    /// The principal's logon type stores a password, but none was given
+    /// when registering the task.
+    MissingPassword(LogonType),
+    /// An email or message box action lacks a required field; the field is
+    /// its element name, e.g. `Server`.
+    MissingActionField(&'static str),
+    /// A day of the month outside 1 to 31.
+    InvalidDayOfMonth(u8),
+    /// A time or calendar trigger has no start boundary to count from.
+    MissingStartBoundary,
+    /// A daily trigger's interval is outside 1 to 365 days.
+    InvalidDaysInterval(u16),
+    /// A weekly trigger's interval is outside 1 to 52 weeks.
+    InvalidWeeksInterval(u16),
+    /// A date and time that is malformed or does not exist.
+    InvalidTimestamp(String),
+    /// A trigger's end boundary is not after its start boundary.
+    EndNotAfterStart { start: String, end: String },
+    /// A duration that is not of the form `PnDTnHnMnS`.
+    InvalidDuration(String),
+    /// A duration outside the range Task Scheduler accepts for its field.
+    DurationOutOfRange { field: DurationField, value: TaskDuration },
+    /// A trigger has a setting its kind does not accept; the field is the
+    /// setting's element name, e.g. `RandomDelay`.
+    UnsupportedTriggerSetting(&'static str),
+    /// A repetition stops before its first repeat.
+    RepetitionDurationTooShort,
+    /// An event trigger has no subscription.
+    EmptySubscription,
+    /// An event query value contains both `'` and `"`, which no string
+    /// literal in the XPath subset of the event log can hold.
+    UnquotableXPathValue(String),
+    /// A weekly or monthly trigger never fires; the field names what is
+    /// missing, e.g. `days of the week`.
+    EmptySchedule(&'static str),
+    /// A priority outside 0 to 10.
+    InvalidPriority(u8),
+    /// A restart count outside 1 to 999.
+    InvalidRestartCount(u16),
+    /// The task is deleted after it expires, but no trigger has an end
+    /// boundary, so it never does.
+    MissingEndBoundary,
+    /// A maintenance deadline is not longer than the maintenance period.
+    MaintenanceDeadlineTooShort,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::EmptyTaskName => write!(f, "task name is empty"),
+            ValidationError::InvalidTaskName(name) => {
+                write!(f, "task name {:?} contains a reserved character", name)
+            }
+            ValidationError::InvalidFolder(folder) => {
+                write!(f, "folder {:?} is not a valid task folder path", folder)
+            }
+            ValidationError::NoActions => write!(f, "task has no actions"),
+            ValidationError::TooManyTriggers(count) => {
+                write!(f, "task has {} triggers; at most {} are allowed", count, MAX_TRIGGERS)
+            }
+            ValidationError::TooManyActions(count) => {
+                write!(f, "task has {} actions; at most {} are allowed", count, MAX_ACTIONS)
+            }
+            ValidationError::DuplicateId(id) => write!(f, "id {:?} is used more than once", id),
+            ValidationError::EmptyExecPath => write!(f, "executable action has an empty path"),
+            ValidationError::InvalidClassId(class_id) => {
+                write!(f, "class id {:?} is not a GUID in braces", class_id)
+            }
+            ValidationError::InvalidNetworkId(id) => {
+                write!(f, "network id {:?} is not a GUID in braces", id)
+            }
+            ValidationError::MissingActionField(field) => write!(f, "action has no {}", field),
+            ValidationError::IncompatiblePrincipal(reason) => write!(f, "invalid principal: {}", reason),
+            ValidationError::MissingPassword(logon_type) => {
+                write!(f, "the {:?} logon type needs the account's password to register the task", logon_type)
+            }
+            ValidationError::InvalidDayOfMonth(day) => {
+                write!(f, "day of the month {} is not between 1 and 31", day)
+            }
+            ValidationError::MissingStartBoundary => {
+                write!(f, "time and calendar triggers need a start boundary")
+            }
+            ValidationError::InvalidDaysInterval(days) => {
+                write!(f, "days interval {} is not between 1 and 365", days)
+            }
+            ValidationError::InvalidWeeksInterval(weeks) => {
+                write!(f, "weeks interval {} is not between 1 and 52", weeks)
+            }
+            ValidationError::InvalidTimestamp(timestamp) => {
+                write!(f, "{:?} is not a valid date and time", timestamp)
+            }
+            ValidationError::EndNotAfterStart { start, end } => {
+                write!(f, "end boundary {} is not after start boundary {}", end, start)
+            }
+            ValidationError::InvalidDuration(duration) => {
+                write!(f, "{:?} is not an ISO 8601 duration in weeks, days, hours, minutes and seconds", duration)
+            }
+            ValidationError::DurationOutOfRange { field, value } => {
+                let (min, max) = field.range();
+                match max {
+                    Some(max) => write!(f, "{} {} is not between {} and {}", field.name(), value, min, max),
+                    None => write!(f, "{} {} is shorter than {}", field.name(), value, min),
+                }
+            }
+            ValidationError::UnsupportedTriggerSetting(setting) => {
+                write!(f, "trigger does not accept a {}", setting)
+            }
+            ValidationError::RepetitionDurationTooShort => {
+                write!(f, "repetition duration is shorter than its interval")
+            }
+            ValidationError::EmptySubscription => write!(f, "event trigger has no subscription"),
+            ValidationError::UnquotableXPathValue(value) => {
+                write!(f, "event query value {:?} contains both kinds of quote", value)
+            }
+            ValidationError::EmptySchedule(what) => write!(f, "trigger has no {}", what),
+            ValidationError::InvalidPriority(priority) => {
+                write!(f, "priority {} is not between 0 and 10", priority)
+            }
+            ValidationError::InvalidRestartCount(count) => {
+                write!(f, "restart count {} is not between 1 and 999", count)
+            }
+            ValidationError::MissingEndBoundary => {
+                write!(f, "deleting an expired task needs a trigger with an end boundary")
+            }
+            ValidationError::MaintenanceDeadlineTooShort => {
+                write!(f, "maintenance deadline is not longer than its period")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Checks that `name` can be used as a task name.
+pub fn validate_task_name(name: &str) -> Result<(), ValidationError> {
+    if name.is_empty() {
+        return Err(ValidationError::EmptyTaskName);
+    }
+    if has_reserved_char(name) {
+        return Err(ValidationError::InvalidTaskName(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Checks that `folder` is a task folder path such as `\` or `\Vendor\App`.
+pub fn validate_folder(folder: &str) -> Result<(), ValidationError> {
+    if folder == "\\" {
+        return Ok(());
+    }
+    let valid = folder
+        .strip_prefix('\\')
+        .map(|rest| {
+            rest.split('\\')
+                .all(|part| !part.is_empty() && !has_reserved_char(part))
+        })
+        .unwrap_or(false);
+    if !valid {
+        return Err(ValidationError::InvalidFolder(folder.to_string()));
+    }
+    Ok(())
+}
+
+impl Trigger {
+    /// Checks the trigger's schedule for anything Task Scheduler would
+    /// reject.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.is_scheduled() && self.start_boundary.is_none() {
+            return Err(ValidationError::MissingStartBoundary);
+        }
+        if let (Some(start), Some(end)) = (&self.start_boundary, &self.end_boundary)
+            && end.compare(start).is_some_and(Ordering::is_le)
+        {
+            return Err(ValidationError::EndNotAfterStart {
+                start: start.to_string(),
+                end: end.to_string(),
+            });
+        }
+        if self.delay.is_some() && !self.accepts_delay() {
+            return Err(ValidationError::UnsupportedTriggerSetting("Delay"));
+        }
+        if self.random_delay.is_some() && !self.is_scheduled() {
+            return Err(ValidationError::UnsupportedTriggerSetting("RandomDelay"));
+        }
+        if let Some(repetition) = &self.repetition {
+            repetition.interval.check(DurationField::RepetitionInterval)?;
+            if let Some(duration) = repetition.duration {
+                duration.check(DurationField::RepetitionDuration)?;
+                if duration < repetition.interval {
+                    return Err(ValidationError::RepetitionDurationTooShort);
+                }
+            }
+        }
+        let durations = [
+            (self.execution_time_limit, DurationField::ExecutionTimeLimit),
+            (self.delay, DurationField::Delay),
+            (self.random_delay, DurationField::RandomDelay),
+        ];
+        for (value, field) in durations {
+            if let Some(value) = value {
+                value.check(field)?;
+            }
+        }
+        match &self.kind {
+            TriggerKind::Logon(_)
+            | TriggerKind::Boot
+            | TriggerKind::Idle
+            | TriggerKind::Registration
+            | TriggerKind::SessionStateChange(_)
+            | TriggerKind::Time => {}
+            TriggerKind::Event(event) => {
+                if event.subscription.trim().is_empty() {
+                    return Err(ValidationError::EmptySubscription);
+                }
+            }
+            TriggerKind::Daily(daily) => {
+                if !(1..=365).contains(&daily.days_interval) {
+                    return Err(ValidationError::InvalidDaysInterval(daily.days_interval));
+                }
+            }
+            TriggerKind::Weekly(weekly) => {
+                if !(1..=52).contains(&weekly.weeks_interval) {
+                    return Err(ValidationError::InvalidWeeksInterval(weekly.weeks_interval));
+                }
+                if weekly.days_of_week.is_empty() {
+                    return Err(ValidationError::EmptySchedule("days of the week"));
+                }
+            }
+            TriggerKind::Monthly(monthly) => {
+                if monthly.days_of_month.is_empty() && !monthly.run_on_last_day_of_month {
+                    return Err(ValidationError::EmptySchedule("days of the month"));
+                }
+                if monthly.months.is_empty() {
+                    return Err(ValidationError::EmptySchedule("months"));
+                }
+            }
+            TriggerKind::MonthlyDayOfWeek(monthly) => {
+                if monthly.weeks_of_month.is_empty() && !monthly.run_on_last_week_of_month {
+                    return Err(ValidationError::EmptySchedule("weeks of the month"));
+                }
+                if monthly.days_of_week.is_empty() {
+                    return Err(ValidationError::EmptySchedule("days of the week"));
+                }
+                if monthly.months.is_empty() {
+                    return Err(ValidationError::EmptySchedule("months"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TaskDefinition {
+    /// Checks the definition for anything Task Scheduler would reject.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.actions.is_empty() {
+            return Err(ValidationError::NoActions);
+        }
+        if self.actions.len() > MAX_ACTIONS {
+            return Err(ValidationError::TooManyActions(self.actions.len()));
+        }
+        if self.triggers.len() > MAX_TRIGGERS {
+            return Err(ValidationError::TooManyTriggers(self.triggers.len()));
+        }
+        self.principal.validate()?;
+        self.settings.validate()?;
+        if self.settings.delete_expired_task_after.is_some()
+            && self.triggers.iter().all(|t| t.end_boundary.is_none())
+        {
+            return Err(ValidationError::MissingEndBoundary);
+        }
+        check_unique_ids(self.triggers.iter().filter_map(|t| t.id.as_deref()))?;
+        check_unique_ids(self.actions.iter().filter_map(|a| a.id.as_deref()))?;
+        for trigger in &self.triggers {
+            trigger.validate()?;
+        }
+        for action in &self.actions {
+            action.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl TaskSettings {
+    /// Checks the priority, restart policy, durations, network id and
+    /// maintenance schedule for values Task Scheduler would reject.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        if self.priority > 10 {
+            return Err(ValidationError::InvalidPriority(self.priority));
+        }
+        if let Some(restart) = &self.restart_on_failure {
+            if !(1..=999).contains(&restart.count) {
+                return Err(ValidationError::InvalidRestartCount(restart.count));
+            }
+            restart.interval.check(DurationField::RestartInterval)?;
+        }
+        self.idle_settings.idle_duration.check(DurationField::IdleDuration)?;
+        self.idle_settings.wait_timeout.check(DurationField::IdleWaitTimeout)?;
+        self.execution_time_limit.check(DurationField::ExecutionTimeLimit)?;
+        if let Some(delete_after) = self.delete_expired_task_after {
+            delete_after.check(DurationField::DeleteExpiredTaskAfter)?;
+        }
+        if let Some(id) = self.network_settings.as_ref().and_then(|n| n.id.as_ref())
+            && !is_braced_guid(id)
+        {
+            return Err(ValidationError::InvalidNetworkId(id.clone()));
+        }
+        if let Some(maintenance) = &self.maintenance_settings {
+            maintenance.period.check(DurationField::MaintenancePeriod)?;
+            if maintenance.deadline.is_some_and(|deadline| deadline <= maintenance.period) {
+                return Err(ValidationError::MaintenanceDeadlineTooShort);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Principal {
+    /// Checks that the user, group and logon type fit together.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let incompatible = |reason| Err(ValidationError::IncompatiblePrincipal(reason));
+        let service_account = self.user_id.as_deref().and_then(ServiceAccount::from_user_id);
+
+        if self.user_id.is_some() && self.group_id.is_some() {
+            return incompatible("it names both a user and a group");
+        }
+        match (self.effective_logon_type(), &self.user_id, &self.group_id) {
+            (LogonType::Group, _, None) => incompatible("a group logon needs a group id"),
+            (logon_type, _, Some(_)) if logon_type != LogonType::Group => {
+                incompatible("a group must use the group logon type")
+            }
+            (LogonType::ServiceAccount, _, _) if service_account.is_none() => {
+                incompatible("the service account logon type needs a built-in service account")
+            }
+            (logon_type, _, _) if service_account.is_some() && logon_type != LogonType::ServiceAccount => {
+                incompatible("built-in service accounts must use the service account logon type")
+            }
+            (LogonType::Password | LogonType::S4U | LogonType::InteractiveTokenOrPassword, None, _) => {
+                incompatible("password and S4U logons need a user id")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Action {
+    /// Checks the action for missing or malformed settings.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        match &self.kind {
+            ActionKind::Exec(exec) => {
+                if exec.path.is_empty() {
+                    return Err(ValidationError::EmptyExecPath);
+                }
+            }
+            ActionKind::ComHandler(handler) => {
+                if !is_braced_guid(&handler.class_id) {
+                    return Err(ValidationError::InvalidClassId(handler.class_id.clone()));
+                }
+            }
+            ActionKind::SendEmail(email) => {
+                if email.server.is_empty() {
+                    return Err(ValidationError::MissingActionField("Server"));
+                }
+                if email.from.is_empty() {
+                    return Err(ValidationError::MissingActionField("From"));
+                }
+            }
+            ActionKind::ShowMessage(message) => {
+                if message.title.is_empty() {
+                    return Err(ValidationError::MissingActionField("Title"));
+                }
+                if message.body.is_empty() {
+                    return Err(ValidationError::MissingActionField("Body"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `value` has the form `{XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}`.
+fn is_braced_guid(value: &str) -> bool {
+    let Some(inner) = value.strip_prefix('{').and_then(|v| v.strip_suffix('}')) else {
+        return false;
+    };
+    let groups: Vec<&str> = inner.split('-').collect();
+    groups.len() == 5
+        && groups.iter().zip([8, 4, 4, 4, 12]).all(|(group, len)| {
+            group.len() == len && group.bytes().all(|b| b.is_ascii_hexdigit())
+        })
+}
+
+fn check_unique_ids<'a>(ids: impl Iterator<Item = &'a str>) -> Result<(), ValidationError> {
+    let mut seen = Vec::new();
+    for id in ids {
+        if seen.contains(&id) {
+            return Err(ValidationError::DuplicateId(id.to_string()));
+        }
+        seen.push(id);
+    }
+    Ok(())
+}
+
+impl Task {
+    /// Checks the name, folder and definition of the task.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_task_name(&self.name)?;
+        validate_folder(&self.folder)?;
+        self.definition.validate()
+    }
+}
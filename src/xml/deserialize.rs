@@ -0,0 +1,854 @@
+use std::ops::BitOrAssign;
+use std::str::FromStr;
+
+use crate::model::{
+    Action,
+    ActionKind,
+    ComHandlerAction,
+    DailyTrigger,
+    DaysOfMonth,
+    DaysOfWeek,
+    EmailAction,
+    EventTrigger,
+    ExecAction,
+    IdleSettings,
+    LogonTrigger,
+    LogonType,
+    MonthlyDayOfWeekTrigger,
+    MonthlyTrigger,
+    Months,
+    MultipleInstancesPolicy,
+    NetworkSettings,
+    MaintenanceSettings,
+    Principal,
+    ProcessTokenSidType,
+    RegistrationInfo,
+    Repetition,
+    RestartOnFailure,
+    RunLevel,
+    SessionStateChange,
+    SessionStateChangeTrigger,
+    ShowMessageAction,
+    TaskDefinition,
+    TaskDuration,
+    TaskSettings,
+    Timestamp,
+    Trigger,
+    TriggerKind,
+    WeeklyTrigger,
+    WeeksOfMonth,
+};
+
+use super::reader::Element;
+use super::{SchemaVersion, XmlError, XmlErrorKind, TASK_NAMESPACE};
+
+pub(super) fn task_from_element(root: &Element) -> Result<TaskDefinition, XmlError> {
+    if root.name != "Task" {
+        return Err(root.error(XmlErrorKind::NotATask(format!(
+            "root element is <{}>",
+            root.name
+        ))));
+    }
+    check_attributes(root, &["version", "xmlns"])?;
+    if root.attribute("xmlns") != Some(TASK_NAMESPACE) {
+        return Err(root.error(XmlErrorKind::NotATask(format!(
+            "<Task> must be in the {} namespace",
+            TASK_NAMESPACE
+        ))));
+    }
+    let version = match root.attribute("version") {
+        Some(version) => SchemaVersion::parse(version)
+            .ok_or_else(|| root.error(XmlErrorKind::UnsupportedVersion(version.to_string())))?,
+        None => SchemaVersion::default(),
+    };
+
+    let mut definition = TaskDefinition::new();
+    let mut principal_id = None;
+    let mut actions = None;
+    let mut seen = Seen::new(root);
+
+    for child in children(root)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "RegistrationInfo" => definition.registration_info = registration_info(child)?,
+            "Triggers" => {
+                check_attributes(child, &[])?;
+                for trigger in children(child)? {
+                    definition.triggers.push(trigger_from_element(trigger, child)?);
+                }
+            }
+            "Principals" => {
+                let (id, principal) = principals(child, version)?;
+                principal_id = id;
+                definition.principal = principal;
+            }
+            "Settings" => definition.settings = settings(child, version)?,
+            "Actions" => actions = Some(child),
+            _ => return Err(unexpected(child, root)),
+        }
+    }
+
+    let actions = actions.ok_or_else(|| missing("Actions", root))?;
+    check_attributes(actions, &["Context"])?;
+    if let (Some(context), Some(id)) = (actions.attribute("Context"), &principal_id)
+        && context != id
+    {
+        return Err(actions.error(XmlErrorKind::InvalidValue {
+            element: "Actions".to_string(),
+            value: context.to_string(),
+            expected: "the id of the task's principal",
+        }));
+    }
+    for action in children(actions)? {
+        definition.actions.push(action_from_element(action, actions)?);
+    }
+
+    Ok(definition)
+}
+
+fn registration_info(element: &Element) -> Result<RegistrationInfo, XmlError> {
+    check_attributes(element, &[])?;
+    let mut info = RegistrationInfo::default();
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        let field = match child.name.as_str() {
+            "Date" => &mut info.date,
+            "Author" => &mut info.author,
+            "Version" => &mut info.version,
+            "Description" => &mut info.description,
+            "URI" => &mut info.uri,
+            "Source" => &mut info.source,
+            "Documentation" => &mut info.documentation,
+            "SecurityDescriptor" => &mut info.security_descriptor,
+            _ => return Err(unexpected(child, element)),
+        };
+        *field = Some(text(child)?);
+    }
+
+    Ok(info)
+}
+
+fn trigger_from_element(element: &Element, parent: &Element) -> Result<Trigger, XmlError> {
+    let kind = match element.name.as_str() {
+        "LogonTrigger" => TriggerKind::Logon(LogonTrigger::default()),
+        "BootTrigger" => TriggerKind::Boot,
+        "IdleTrigger" => TriggerKind::Idle,
+        "RegistrationTrigger" => TriggerKind::Registration,
+        "EventTrigger" => TriggerKind::Event(EventTrigger::default()),
+        // The state change is required and filled in below
+        "SessionStateChangeTrigger" => TriggerKind::SessionStateChange(SessionStateChangeTrigger {
+            state_change: SessionStateChange::ConsoleConnect,
+            user_id: None,
+        }),
+        "TimeTrigger" => TriggerKind::Time,
+        "CalendarTrigger" => calendar_schedule(element)?,
+        _ => return Err(unexpected(element, parent)),
+    };
+    check_attributes(element, &["id"])?;
+
+    let mut trigger = Trigger::new(kind);
+    trigger.id = element.attribute("id").map(str::to_string);
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match (child.name.as_str(), &mut trigger.kind) {
+            ("Repetition", _) => trigger.repetition = Some(repetition(child)?),
+            ("StartBoundary", _) => trigger.start_boundary = Some(timestamp(child)?),
+            ("EndBoundary", _) => trigger.end_boundary = Some(timestamp(child)?),
+            ("ExecutionTimeLimit", _) => trigger.execution_time_limit = Some(duration(child)?),
+            ("Enabled", _) => trigger.enabled = boolean(child)?,
+            ("UserId", TriggerKind::Logon(logon)) => logon.user_id = Some(text(child)?),
+            ("UserId", TriggerKind::SessionStateChange(session)) => session.user_id = Some(text(child)?),
+            ("StateChange", TriggerKind::SessionStateChange(session)) => {
+                let value = text(child)?;
+                session.state_change = SessionStateChange::parse(&value)
+                    .ok_or_else(|| invalid(child, &value, "a session state change such as SessionLock"))?;
+            }
+            (
+                "Delay",
+                TriggerKind::Logon(_)
+                | TriggerKind::Boot
+                | TriggerKind::Registration
+                | TriggerKind::SessionStateChange(_)
+                | TriggerKind::Event(_),
+            ) => trigger.delay = Some(duration(child)?),
+            ("Subscription", TriggerKind::Event(event)) => event.subscription = text(child)?,
+            ("ValueQueries", TriggerKind::Event(event)) => event.value_queries = value_queries(child)?,
+            ("NumberOfOccurrences", TriggerKind::Event(event)) => {
+                event.number_of_occurrences = Some(number(child, "a number of occurrences from 1 to 32")?)
+            }
+            ("PeriodOfOccurrence", TriggerKind::Event(event)) => event.period_of_occurrence = Some(duration(child)?),
+            ("MatchingElement", TriggerKind::Event(event)) => event.matching_element = Some(text(child)?),
+            (
+                "RandomDelay",
+                TriggerKind::Time
+                | TriggerKind::Daily(_)
+                | TriggerKind::Weekly(_)
+                | TriggerKind::Monthly(_)
+                | TriggerKind::MonthlyDayOfWeek(_),
+            ) => trigger.random_delay = Some(duration(child)?),
+            // Already read by `calendar_schedule`
+            ("ScheduleByDay" | "ScheduleByWeek" | "ScheduleByMonth" | "ScheduleByMonthDayOfWeek", _)
+                if element.name == "CalendarTrigger" => {}
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    if let TriggerKind::SessionStateChange(_) = trigger.kind
+        && !seen.contains("StateChange")
+    {
+        return Err(missing("StateChange", element));
+    }
+    if let TriggerKind::Event(_) = trigger.kind
+        && !seen.contains("Subscription")
+    {
+        return Err(missing("Subscription", element));
+    }
+
+    Ok(trigger)
+}
+
+/// Reads `ValueQueries` as pairs of a name and an XPath.
+fn value_queries(element: &Element) -> Result<Vec<(String, String)>, XmlError> {
+    check_attributes(element, &[])?;
+    let mut queries = Vec::new();
+
+    for child in children(element)? {
+        if child.name != "Value" {
+            return Err(unexpected(child, element));
+        }
+        check_attributes(child, &["name"])?;
+        let name = child.attribute("name").ok_or_else(|| {
+            child.error(XmlErrorKind::InvalidValue {
+                element: "Value".to_string(),
+                value: String::new(),
+                expected: "a name attribute",
+            })
+        })?;
+        if let Some(grandchild) = child.children.first() {
+            return Err(unexpected(grandchild, child));
+        }
+        queries.push((name.to_string(), child.text.trim().to_string()));
+    }
+
+    Ok(queries)
+}
+
+fn repetition(element: &Element) -> Result<Repetition, XmlError> {
+    check_attributes(element, &[])?;
+    let mut interval = None;
+    let mut repetition = Repetition::new(TaskDuration::ZERO, None);
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "Interval" => interval = Some(duration(child)?),
+            "Duration" => repetition.duration = Some(duration(child)?),
+            "StopAtDurationEnd" => repetition.stop_at_duration_end = boolean(child)?,
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    repetition.interval = interval.ok_or_else(|| missing("Interval", element))?;
+    Ok(repetition)
+}
+
+/// Reads the schedule of a `CalendarTrigger`, which is held in exactly one
+/// of its `ScheduleBy*` children.
+fn calendar_schedule(element: &Element) -> Result<TriggerKind, XmlError> {
+    let mut kind = None;
+
+    for child in children(element)? {
+        let schedule = match child.name.as_str() {
+            "ScheduleByDay" => TriggerKind::Daily(schedule_by_day(child)?),
+            "ScheduleByWeek" => TriggerKind::Weekly(schedule_by_week(child)?),
+            "ScheduleByMonth" => TriggerKind::Monthly(schedule_by_month(child)?),
+            "ScheduleByMonthDayOfWeek" => {
+                TriggerKind::MonthlyDayOfWeek(schedule_by_month_day_of_week(child)?)
+            }
+            _ => continue,
+        };
+        if kind.is_some() {
+            return Err(unexpected(child, element));
+        }
+        kind = Some(schedule);
+    }
+
+    kind.ok_or_else(|| missing("ScheduleByDay", element))
+}
+
+fn schedule_by_day(element: &Element) -> Result<DailyTrigger, XmlError> {
+    check_attributes(element, &[])?;
+    let mut daily = DailyTrigger::default();
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "DaysInterval" => daily.days_interval = number(child, "a number of days")?,
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    Ok(daily)
+}
+
+fn schedule_by_week(element: &Element) -> Result<WeeklyTrigger, XmlError> {
+    check_attributes(element, &[])?;
+    let mut weekly = WeeklyTrigger::default();
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "DaysOfWeek" => weekly.days_of_week = flags(child, DaysOfWeek::NAMED)?,
+            "WeeksInterval" => weekly.weeks_interval = number(child, "a number of weeks")?,
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    Ok(weekly)
+}
+
+fn schedule_by_month(element: &Element) -> Result<MonthlyTrigger, XmlError> {
+    check_attributes(element, &[])?;
+    let mut monthly = MonthlyTrigger::default();
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "DaysOfMonth" => {
+                check_attributes(child, &[])?;
+                let mut days = Vec::new();
+                for day in children(child)? {
+                    if day.name != "Day" {
+                        return Err(unexpected(day, child));
+                    }
+                    match text(day)?.as_str() {
+                        "Last" => monthly.run_on_last_day_of_month = true,
+                        value => match value.parse() {
+                            Ok(n @ 1..=31) => days.push(n),
+                            _ => return Err(invalid(day, value, "a day from 1 to 31 or Last")),
+                        },
+                    }
+                }
+                monthly.days_of_month = DaysOfMonth::from_days(&days)
+                    .expect("days are checked as they are read");
+            }
+            "Months" => monthly.months = flags(child, Months::NAMED)?,
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    Ok(monthly)
+}
+
+fn schedule_by_month_day_of_week(element: &Element) -> Result<MonthlyDayOfWeekTrigger, XmlError> {
+    check_attributes(element, &[])?;
+    let mut monthly = MonthlyDayOfWeekTrigger::default();
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "Weeks" => {
+                check_attributes(child, &[])?;
+                for week in children(child)? {
+                    if week.name != "Week" {
+                        return Err(unexpected(week, child));
+                    }
+                    let value = text(week)?;
+                    if value == "Last" {
+                        monthly.run_on_last_week_of_month = true;
+                        continue;
+                    }
+                    match WeeksOfMonth::NAMED.iter().find(|(_, name)| *name == value) {
+                        Some(&(flag, _)) => monthly.weeks_of_month |= flag,
+                        None => return Err(invalid(week, &value, "a week from 1 to 4 or Last")),
+                    }
+                }
+            }
+            "DaysOfWeek" => monthly.days_of_week = flags(child, DaysOfWeek::NAMED)?,
+            "Months" => monthly.months = flags(child, Months::NAMED)?,
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    Ok(monthly)
+}
+
+/// Reads the `Principals` element, returning the principal and its id.
+fn principals(element: &Element, version: SchemaVersion) -> Result<(Option<String>, Principal), XmlError> {
+    check_attributes(element, &[])?;
+    let mut result = (None, Principal::default());
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        if child.name != "Principal" {
+            return Err(unexpected(child, element));
+        }
+        seen.check(child)?;
+        check_attributes(child, &["id"])?;
+        result.0 = child.attribute("id").map(str::to_string);
+
+        let mut seen = Seen::new(child);
+        for field in children(child)? {
+            seen.check(field)?;
+            match field.name.as_str() {
+                "UserId" => result.1.user_id = Some(text(field)?),
+                "GroupId" => result.1.group_id = Some(text(field)?),
+                "DisplayName" => result.1.display_name = Some(text(field)?),
+                "LogonType" => {
+                    let value = text(field)?;
+                    let logon_type = LogonType::parse(&value)
+                        .ok_or_else(|| invalid(field, &value, "S4U, Password, InteractiveToken or InteractiveTokenOrPassword"))?;
+                    result.1.logon_type = Some(logon_type);
+                }
+                "RunLevel" => {
+                    let value = text(field)?;
+                    let run_level = RunLevel::parse(&value)
+                        .ok_or_else(|| invalid(field, &value, "LeastPrivilege or HighestAvailable"))?;
+                    result.1.run_level = Some(run_level);
+                }
+                "ProcessTokenSidType" if version >= SchemaVersion::V1_3 => {
+                    let value = text(field)?;
+                    let sid_type = ProcessTokenSidType::parse(&value)
+                        .ok_or_else(|| invalid(field, &value, "None, Unrestricted or Default"))?;
+                    result.1.process_token_sid_type = Some(sid_type);
+                }
+                "RequiredPrivileges" if version >= SchemaVersion::V1_3 => {
+                    result.1.required_privileges = required_privileges(field)?;
+                }
+                _ => return Err(unexpected(field, child)),
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Reads `RequiredPrivileges` as a list of privilege names.
+fn required_privileges(element: &Element) -> Result<Vec<String>, XmlError> {
+    check_attributes(element, &[])?;
+    children(element)?
+        .iter()
+        .map(|child| match child.name.as_str() {
+            "Privilege" => text(child),
+            _ => Err(unexpected(child, element)),
+        })
+        .collect()
+}
+
+fn settings(element: &Element, version: SchemaVersion) -> Result<TaskSettings, XmlError> {
+    check_attributes(element, &[])?;
+    let mut settings = TaskSettings { compatibility: version.compatibility(), ..TaskSettings::default() };
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "AllowStartOnDemand" => settings.allow_demand_start = boolean(child)?,
+            "AllowHardTerminate" => settings.allow_hard_terminate = boolean(child)?,
+            "StartWhenAvailable" => settings.start_when_available = boolean(child)?,
+            "StopIfGoingOnBatteries" => settings.stop_if_going_on_batteries = boolean(child)?,
+            "DisallowStartIfOnBatteries" => settings.disallow_start_if_on_batteries = boolean(child)?,
+            "MultipleInstancesPolicy" => {
+                let value = text(child)?;
+                settings.multiple_instances = MultipleInstancesPolicy::parse(&value)
+                    .ok_or_else(|| invalid(child, &value, "Parallel, Queue, IgnoreNew or StopExisting"))?;
+            }
+            "Priority" => settings.priority = number(child, "a priority from 0 to 10")?,
+            "RestartOnFailure" => settings.restart_on_failure = Some(restart_on_failure(child)?),
+            "RunOnlyIfNetworkAvailable" => settings.run_only_if_network_available = boolean(child)?,
+            "RunOnlyIfIdle" => settings.run_only_if_idle = boolean(child)?,
+            "IdleSettings" => settings.idle_settings = idle_settings(child)?,
+            "NetworkSettings" => settings.network_settings = Some(network_settings(child)?),
+            "WakeToRun" => settings.wake_to_run = boolean(child)?,
+            "Hidden" => settings.hidden = boolean(child)?,
+            "Enabled" => settings.enabled = boolean(child)?,
+            "DeleteExpiredTaskAfter" => settings.delete_expired_task_after = Some(duration(child)?),
+            "ExecutionTimeLimit" => settings.execution_time_limit = duration(child)?,
+            "UseUnifiedSchedulingEngine" if version >= SchemaVersion::V1_3 => {
+                settings.use_unified_scheduling_engine = boolean(child)?
+            }
+            "DisallowStartOnRemoteAppSession" if version >= SchemaVersion::V1_3 => {
+                settings.disallow_start_on_remote_app_session = boolean(child)?
+            }
+            "Volatile" if version >= SchemaVersion::V1_4 => settings.volatile = boolean(child)?,
+            "MaintenanceSettings" if version >= SchemaVersion::V1_4 => {
+                settings.maintenance_settings = Some(maintenance_settings(child)?)
+            }
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    Ok(settings)
+}
+
+fn restart_on_failure(element: &Element) -> Result<RestartOnFailure, XmlError> {
+    check_attributes(element, &[])?;
+    let mut interval = None;
+    let mut count = None;
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "Interval" => interval = Some(duration(child)?),
+            "Count" => count = Some(number(child, "a restart count from 1 to 999")?),
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    Ok(RestartOnFailure {
+        count: count.ok_or_else(|| missing("Count", element))?,
+        interval: interval.ok_or_else(|| missing("Interval", element))?,
+    })
+}
+
+fn maintenance_settings(element: &Element) -> Result<MaintenanceSettings, XmlError> {
+    check_attributes(element, &[])?;
+    let mut period = None;
+    let mut deadline = None;
+    let mut exclusive = false;
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "Period" => period = Some(duration(child)?),
+            "Deadline" => deadline = Some(duration(child)?),
+            "Exclusive" => exclusive = boolean(child)?,
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    Ok(MaintenanceSettings {
+        period: period.ok_or_else(|| missing("Period", element))?,
+        deadline,
+        exclusive,
+    })
+}
+
+fn network_settings(element: &Element) -> Result<NetworkSettings, XmlError> {
+    check_attributes(element, &[])?;
+    let mut network = NetworkSettings::default();
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "Name" => network.name = Some(text(child)?),
+            "Id" => network.id = Some(text(child)?),
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    Ok(network)
+}
+
+fn idle_settings(element: &Element) -> Result<IdleSettings, XmlError> {
+    check_attributes(element, &[])?;
+    let mut idle = IdleSettings::default();
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "Duration" => idle.idle_duration = duration(child)?,
+            "WaitTimeout" => idle.wait_timeout = duration(child)?,
+            "StopOnIdleEnd" => idle.stop_on_idle_end = boolean(child)?,
+            "RestartOnIdle" => idle.restart_on_idle = boolean(child)?,
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    Ok(idle)
+}
+
+fn action_from_element(element: &Element, parent: &Element) -> Result<Action, XmlError> {
+    check_attributes(element, &["id"])?;
+    let kind = match element.name.as_str() {
+        "Exec" => ActionKind::Exec(exec_action(element)?),
+        "ComHandler" => ActionKind::ComHandler(com_handler_action(element)?),
+        "SendEmail" => ActionKind::SendEmail(email_action(element)?),
+        "ShowMessage" => ActionKind::ShowMessage(show_message_action(element)?),
+        _ => return Err(unexpected(element, parent)),
+    };
+
+    let mut action = Action::new(kind);
+    action.id = element.attribute("id").map(str::to_string);
+    Ok(action)
+}
+
+fn exec_action(element: &Element) -> Result<ExecAction, XmlError> {
+    let mut command = None;
+    let mut arguments = None;
+    let mut working_directory = None;
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "Command" => command = Some(text(child)?),
+            "Arguments" => arguments = Some(text(child)?),
+            "WorkingDirectory" => working_directory = Some(text(child)?),
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    Ok(ExecAction {
+        path: command.ok_or_else(|| missing("Command", element))?,
+        arguments,
+        working_directory,
+    })
+}
+
+fn com_handler_action(element: &Element) -> Result<ComHandlerAction, XmlError> {
+    let mut class_id = None;
+    let mut data = None;
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "ClassId" => class_id = Some(text(child)?),
+            "Data" => data = Some(text(child)?),
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    Ok(ComHandlerAction {
+        class_id: class_id.ok_or_else(|| missing("ClassId", element))?,
+        data,
+    })
+}
+
+fn email_action(element: &Element) -> Result<EmailAction, XmlError> {
+    let mut email = EmailAction::default();
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "Server" => email.server = text(child)?,
+            "Subject" => email.subject = Some(text(child)?),
+            "To" => email.to = Some(text(child)?),
+            "Cc" => email.cc = Some(text(child)?),
+            "Bcc" => email.bcc = Some(text(child)?),
+            "ReplyTo" => email.reply_to = Some(text(child)?),
+            "From" => email.from = text(child)?,
+            "HeaderFields" => email.header_fields = header_fields(child)?,
+            "Body" => email.body = Some(text(child)?),
+            "Attachments" => email.attachments = attachments(child)?,
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+    for required in ["Server", "From"] {
+        if !seen.contains(required) {
+            return Err(missing(required, element));
+        }
+    }
+
+    Ok(email)
+}
+
+/// Reads `HeaderFields` as pairs of a name and a value.
+fn header_fields(element: &Element) -> Result<Vec<(String, String)>, XmlError> {
+    check_attributes(element, &[])?;
+    let mut fields = Vec::new();
+
+    for child in children(element)? {
+        if child.name != "HeaderField" {
+            return Err(unexpected(child, element));
+        }
+        check_attributes(child, &[])?;
+        let mut name = None;
+        let mut value = None;
+        let mut seen = Seen::new(child);
+        for grandchild in children(child)? {
+            seen.check(grandchild)?;
+            match grandchild.name.as_str() {
+                "Name" => name = Some(text(grandchild)?),
+                "Value" => value = Some(text(grandchild)?),
+                _ => return Err(unexpected(grandchild, child)),
+            }
+        }
+        fields.push((
+            name.ok_or_else(|| missing("Name", child))?,
+            value.ok_or_else(|| missing("Value", child))?,
+        ));
+    }
+
+    Ok(fields)
+}
+
+/// Reads `Attachments` as a list of file paths.
+fn attachments(element: &Element) -> Result<Vec<String>, XmlError> {
+    check_attributes(element, &[])?;
+    children(element)?
+        .iter()
+        .map(|child| match child.name.as_str() {
+            "File" => text(child),
+            _ => Err(unexpected(child, element)),
+        })
+        .collect()
+}
+
+fn show_message_action(element: &Element) -> Result<ShowMessageAction, XmlError> {
+    let mut title = None;
+    let mut body = None;
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        match child.name.as_str() {
+            "Title" => title = Some(text(child)?),
+            "Body" => body = Some(text(child)?),
+            _ => return Err(unexpected(child, element)),
+        }
+    }
+
+    Ok(ShowMessageAction {
+        title: title.ok_or_else(|| missing("Title", element))?,
+        body: body.ok_or_else(|| missing("Body", element))?,
+    })
+}
+
+/// Tracks which children of an element have been read, to reject
+/// duplicates.
+struct Seen<'a> {
+    parent: &'a Element,
+    names: Vec<&'a str>,
+}
+
+impl<'a> Seen<'a> {
+    fn new(parent: &'a Element) -> Self {
+        Self { parent, names: Vec::new() }
+    }
+
+    fn check(&mut self, child: &'a Element) -> Result<(), XmlError> {
+        if self.names.contains(&child.name.as_str()) {
+            return Err(child.error(XmlErrorKind::DuplicateElement {
+                element: child.name.clone(),
+                parent: self.parent.name.clone(),
+            }));
+        }
+        self.names.push(&child.name);
+        Ok(())
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.names.contains(&name)
+    }
+}
+
+/// The child elements of a container, which must not hold text of its own.
+fn children(element: &Element) -> Result<&[Element], XmlError> {
+    if !element.text.trim().is_empty() {
+        return Err(element.error(XmlErrorKind::UnexpectedText {
+            element: element.name.clone(),
+        }));
+    }
+    Ok(&element.children)
+}
+
+/// The text of a leaf element, with surrounding whitespace removed.
+fn text(element: &Element) -> Result<String, XmlError> {
+    check_attributes(element, &[])?;
+    if let Some(child) = element.children.first() {
+        return Err(unexpected(child, element));
+    }
+    Ok(element.text.trim().to_string())
+}
+
+/// The value of an `xs:boolean` element.
+fn boolean(element: &Element) -> Result<bool, XmlError> {
+    match text(element)?.as_str() {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        value => Err(invalid(element, value, "true or false")),
+    }
+}
+
+/// The value of an `xs:dateTime` element.
+fn timestamp(element: &Element) -> Result<Timestamp, XmlError> {
+    let value = text(element)?;
+    value.parse().map_err(|_| invalid(element, &value, "an ISO 8601 date and time"))
+}
+
+/// The value of an `xs:duration` element.
+fn duration(element: &Element) -> Result<TaskDuration, XmlError> {
+    let value = text(element)?;
+    value.parse().map_err(|_| invalid(element, &value, "an ISO 8601 duration"))
+}
+
+/// The value of a numeric element.
+fn number<T: FromStr>(element: &Element, expected: &'static str) -> Result<T, XmlError> {
+    let value = text(element)?;
+    value.parse().map_err(|_| invalid(element, &value, expected))
+}
+
+/// A set written as one empty child element per member, such as
+/// `<DaysOfWeek><Monday /><Friday /></DaysOfWeek>`.
+fn flags<T>(element: &Element, named: &[(T, &str)]) -> Result<T, XmlError>
+where
+    T: Copy + Default + BitOrAssign,
+{
+    check_attributes(element, &[])?;
+    let mut set = T::default();
+    let mut seen = Seen::new(element);
+
+    for child in children(element)? {
+        seen.check(child)?;
+        let &(flag, _) = named
+            .iter()
+            .find(|(_, name)| *name == child.name)
+            .ok_or_else(|| unexpected(child, element))?;
+        if !text(child)?.is_empty() {
+            return Err(child.error(XmlErrorKind::UnexpectedText {
+                element: child.name.clone(),
+            }));
+        }
+        set |= flag;
+    }
+
+    Ok(set)
+}
+
+fn check_attributes(element: &Element, allowed: &[&str]) -> Result<(), XmlError> {
+    match element.attributes.iter().find(|(key, _)| !allowed.contains(&key.as_str())) {
+        Some((key, _)) => Err(element.error(XmlErrorKind::UnexpectedAttribute {
+            attribute: key.clone(),
+            element: element.name.clone(),
+        })),
+        None => Ok(()),
+    }
+}
+
+fn unexpected(element: &Element, parent: &Element) -> XmlError {
+    element.error(XmlErrorKind::UnexpectedElement {
+        element: element.name.clone(),
+        parent: parent.name.clone(),
+    })
+}
+
+fn missing(name: &str, parent: &Element) -> XmlError {
+    parent.error(XmlErrorKind::MissingElement {
+        element: name.to_string(),
+        parent: parent.name.clone(),
+    })
+}
+
+fn invalid(element: &Element, value: &str, expected: &'static str) -> XmlError {
+    element.error(XmlErrorKind::InvalidValue {
+        element: element.name.clone(),
+        value: value.to_string(),
+        expected,
+    })
+}
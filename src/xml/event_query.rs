@@ -0,0 +1,237 @@
+use std::ops::RangeInclusive;
+
+use crate::model::{TaskDuration, Trigger, TriggerKind, ValidationError};
+
+use super::writer::escape_into;
+
+/// Severity of an event, as filtered on by the `Level` of its `System`
+/// data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventLevel {
+    Critical,
+    Error,
+    Warning,
+    /// Also matches events logged with level 0 (`LogAlways`), as Event
+    /// Viewer does.
+    Information,
+    Verbose,
+}
+
+impl EventLevel {
+    fn values(self) -> &'static [u8] {
+        match self {
+            EventLevel::Critical => &[1],
+            EventLevel::Error => &[2],
+            EventLevel::Warning => &[3],
+            EventLevel::Information => &[4, 0],
+            EventLevel::Verbose => &[5],
+        }
+    }
+}
+
+/// Builder for the event log subscription of an event trigger: a
+/// `QueryList` selecting events from one or more channels by provider,
+/// event ID, level and age.
+///
+/// The filters combine like Event Viewer's "Filter Current Log" dialog:
+/// an event matches if it passes every filter that is set, and a filter
+/// matches any of the values given to it.
+///
+/// ```
+/// use schtask::{EventLevel, EventQuery};
+///
+/// let query = EventQuery::new("Security").event_id(4625).level(EventLevel::Information);
+///
+/// assert_eq!(
+///     query.xpath().unwrap(),
+///     "*[System[(EventID=4625) and (Level=4 or Level=0)]]"
+/// );
+/// ```
+///
+/// Provider names and event data names are written as XPath string
+/// literals. The event log accepts no functions besides `position()`,
+/// `band()` and `timediff()`, so a value containing both `'` and `"`
+/// cannot be written and fails with
+/// [`ValidationError::UnquotableXPathValue`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventQuery {
+    channels: Vec<String>,
+    providers: Vec<String>,
+    event_ids: Vec<RangeInclusive<u32>>,
+    levels: Vec<EventLevel>,
+    within: Option<TaskDuration>,
+    value_queries: Vec<(String, ValueQuery)>,
+}
+
+/// Where a value query's XPath comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ValueQuery {
+    XPath(String),
+    /// The name of an event data item, quoted when the query is built.
+    EventData(String),
+}
+
+impl EventQuery {
+    /// Starts a query over the events in `channel`, e.g. `System` or
+    /// `Microsoft-Windows-TaskScheduler/Operational`.
+    pub fn new(channel: &str) -> Self {
+        Self {
+            channels: vec![channel.to_string()],
+            providers: Vec::new(),
+            event_ids: Vec::new(),
+            levels: Vec::new(),
+            within: None,
+            value_queries: Vec::new(),
+        }
+    }
+
+    /// Also selects events from `channel`.
+    pub fn channel(mut self, channel: &str) -> Self {
+        self.channels.push(channel.to_string());
+        self
+    }
+
+    /// Matches events logged by `provider`.
+    pub fn provider(mut self, provider: &str) -> Self {
+        self.providers.push(provider.to_string());
+        self
+    }
+
+    /// Matches events with the given ID.
+    pub fn event_id(mut self, id: u32) -> Self {
+        self.event_ids.push(id..=id);
+        self
+    }
+
+    /// Matches events with any of the given IDs.
+    pub fn event_ids(mut self, ids: impl IntoIterator<Item = u32>) -> Self {
+        self.event_ids.extend(ids.into_iter().map(|id| id..=id));
+        self
+    }
+
+    /// Matches events with an ID in `ids`.
+    pub fn event_id_range(mut self, ids: RangeInclusive<u32>) -> Self {
+        self.event_ids.push(ids);
+        self
+    }
+
+    /// Matches events of the given severity.
+    pub fn level(mut self, level: EventLevel) -> Self {
+        self.levels.push(level);
+        self
+    }
+
+    /// Matches events logged no longer than `age` ago.
+    pub fn within(mut self, age: TaskDuration) -> Self {
+        self.within = Some(age);
+        self
+    }
+
+    /// Passes the value `xpath` selects in the matching event to the task's
+    /// actions as `$(name)`.
+    pub fn value_query(mut self, name: &str, xpath: &str) -> Self {
+        self.value_queries.push((name.to_string(), ValueQuery::XPath(xpath.to_string())));
+        self
+    }
+
+    /// Passes the event data item `data` of the matching event to the
+    /// task's actions as `$(name)`.
+    pub fn event_data_value(mut self, name: &str, data: &str) -> Self {
+        self.value_queries.push((name.to_string(), ValueQuery::EventData(data.to_string())));
+        self
+    }
+
+    /// The XPath expression selecting the events, e.g.
+    /// `*[System[(EventID=7036)]]`, or `*` if no filter is set.
+    pub fn xpath(&self) -> Result<String, ValidationError> {
+        let mut conditions = Vec::new();
+
+        if !self.providers.is_empty() {
+            let names = self
+                .providers
+                .iter()
+                .map(|provider| Ok(format!("@Name={}", literal(provider)?)))
+                .collect::<Result<Vec<_>, ValidationError>>()?;
+            conditions.push(format!("Provider[{}]", names.join(" or ")));
+        }
+        if !self.event_ids.is_empty() {
+            let ids: Vec<String> = self
+                .event_ids
+                .iter()
+                .map(|ids| match (ids.start(), ids.end()) {
+                    (start, end) if start == end => format!("EventID={}", start),
+                    (start, end) => format!("(EventID>={} and EventID<={})", start, end),
+                })
+                .collect();
+            conditions.push(format!("({})", ids.join(" or ")));
+        }
+        if !self.levels.is_empty() {
+            let levels: Vec<String> = self
+                .levels
+                .iter()
+                .flat_map(|level| level.values())
+                .map(|value| format!("Level={}", value))
+                .collect();
+            conditions.push(format!("({})", levels.join(" or ")));
+        }
+        if let Some(age) = self.within {
+            conditions.push(format!(
+                "TimeCreated[timediff(@SystemTime)<={}]",
+                age.as_duration().as_millis()
+            ));
+        }
+
+        if conditions.is_empty() {
+            Ok("*".to_string())
+        } else {
+            Ok(format!("*[System[{}]]", conditions.join(" and ")))
+        }
+    }
+
+    /// The `QueryList` document to use as an event trigger's
+    /// subscription, with one `Select` per channel.
+    pub fn to_query_list(&self) -> Result<String, ValidationError> {
+        let xpath = self.xpath()?;
+        let mut out = String::from("<QueryList><Query Id=\"0\" Path=\"");
+        escape_into(&mut out, &self.channels[0], true);
+        out.push_str("\">");
+        for channel in &self.channels {
+            out.push_str("<Select Path=\"");
+            escape_into(&mut out, channel, true);
+            out.push_str("\">");
+            escape_into(&mut out, &xpath, false);
+            out.push_str("</Select>");
+        }
+        out.push_str("</Query></QueryList>");
+        Ok(out)
+    }
+
+    /// An event trigger with this query as its subscription and the value
+    /// queries added to it.
+    pub fn to_trigger(&self) -> Result<Trigger, ValidationError> {
+        let mut value_queries = Vec::new();
+        for (name, query) in &self.value_queries {
+            let xpath = match query {
+                ValueQuery::XPath(xpath) => xpath.clone(),
+                ValueQuery::EventData(data) => format!("Event/EventData/Data[@Name={}]", literal(data)?),
+            };
+            value_queries.push((name.clone(), xpath));
+        }
+        let mut trigger = Trigger::event(&self.to_query_list()?);
+        if let TriggerKind::Event(event) = &mut trigger.kind {
+            event.value_queries = value_queries;
+        }
+        Ok(trigger)
+    }
+}
+
+/// Quotes `value` as an XPath string literal. XPath 1.0 has no escapes,
+/// so values containing `'` are quoted with `"` instead; values
+/// containing both would need `concat()`, which the event log rejects.
+fn literal(value: &str) -> Result<String, ValidationError> {
+    match (value.contains('\''), value.contains('"')) {
+        (false, _) => Ok(format!("'{}'", value)),
+        (true, false) => Ok(format!("\"{}\"", value)),
+        (true, true) => Err(ValidationError::UnquotableXPathValue(value.to_string())),
+    }
+}
@@ -0,0 +1,217 @@
+//! Task Scheduler XML, the format used by `schtasks /create /xml`,
+//! `ITaskFolder::RegisterTask` and the Task Scheduler MMC export.
+
+mod deserialize;
+mod event_query;
+mod reader;
+mod serialize;
+mod writer;
+
+use std::fmt;
+
+pub use event_query::{EventLevel, EventQuery};
+
+use crate::model::{Compatibility, TaskDefinition};
+
+/// Namespace of the Task Scheduler schema.
+pub const TASK_NAMESPACE: &str = "http://schemas.microsoft.com/windows/2004/02/mit/task";
+
+/// Version of the Task Scheduler schema a document is written against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SchemaVersion {
+    /// Windows Vista and Windows Server 2008.
+    #[default]
+    V1_2,
+    /// Windows 7 and Windows Server 2008 R2.
+    V1_3,
+    /// Windows 8 and Windows Server 2012.
+    V1_4,
+}
+
+impl SchemaVersion {
+    /// The value of the `version` attribute on the `Task` element.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SchemaVersion::V1_2 => "1.2",
+            SchemaVersion::V1_3 => "1.3",
+            SchemaVersion::V1_4 => "1.4",
+        }
+    }
+
+    /// Parses the value of the `version` attribute.
+    pub fn parse(value: &str) -> Option<SchemaVersion> {
+        match value {
+            "1.2" => Some(SchemaVersion::V1_2),
+            "1.3" => Some(SchemaVersion::V1_3),
+            "1.4" => Some(SchemaVersion::V1_4),
+            _ => None,
+        }
+    }
+
+    /// The Task Scheduler version that introduced this schema, which a
+    /// task read from it is written for.
+    pub fn compatibility(self) -> Compatibility {
+        match self {
+            SchemaVersion::V1_2 => Compatibility::V2,
+            SchemaVersion::V1_3 => Compatibility::V2_1,
+            SchemaVersion::V1_4 => Compatibility::V2_2,
+        }
+    }
+}
+
+/// Something in a task definition that Task Scheduler will not accept when
+/// the task is registered, or that a schema version cannot express.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaWarning {
+    /// A deprecated email or message box action, which Windows 8 and later
+    /// (schema 1.4 and later) reject.
+    DeprecatedAction { id: Option<String>, kind: &'static str, version: SchemaVersion },
+    /// A setting or principal property the schema version has no element
+    /// for, so it is left out of the XML; the setting is its element name.
+    UnsupportedSetting { setting: &'static str, version: SchemaVersion },
+}
+
+impl fmt::Display for SchemaWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaWarning::DeprecatedAction { id, kind, version } => {
+                write!(f, "{} action", kind)?;
+                if let Some(id) = id {
+                    write!(f, " {:?}", id)?;
+                }
+                write!(f, " is not supported by task schema {}", version.as_str())
+            }
+            SchemaWarning::UnsupportedSetting { setting, version } => {
+                write!(f, "setting {} is not supported by task schema {}", setting, version.as_str())
+            }
+        }
+    }
+}
+
+/// A Task XML document that could not be read, with the 1-based line and
+/// column where the problem was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: XmlErrorKind,
+}
+
+/// What is wrong with a Task XML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmlErrorKind {
+    /// The bytes are not valid UTF-8 or UTF-16.
+    Encoding(String),
+    /// The document is not well-formed XML.
+    Syntax(String),
+    /// The root element is not a `Task` in the Task Scheduler namespace.
+    NotATask(String),
+    /// The `version` attribute names a schema this crate does not read.
+    UnsupportedVersion(String),
+    /// An element that is not allowed (or not supported) where it appears.
+    UnexpectedElement { element: String, parent: String },
+    /// An attribute that is not allowed on its element.
+    UnexpectedAttribute { attribute: String, element: String },
+    /// An element that may appear only once appears again.
+    DuplicateElement { element: String, parent: String },
+    /// A required element is missing.
+    MissingElement { element: String, parent: String },
+    /// An element has a value of the wrong form.
+    InvalidValue { element: String, value: String, expected: &'static str },
+    /// An element that only holds other elements contains text.
+    UnexpectedText { element: String },
+}
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            XmlErrorKind::Encoding(message) => write!(f, "{}", message),
+            XmlErrorKind::Syntax(message) => write!(f, "malformed XML: {}", message),
+            XmlErrorKind::NotATask(message) => write!(f, "not a task document: {}", message),
+            XmlErrorKind::UnsupportedVersion(version) => {
+                write!(f, "unsupported task schema version {:?}", version)
+            }
+            XmlErrorKind::UnexpectedElement { element, parent } => {
+                write!(f, "unexpected element <{}> in <{}>", element, parent)
+            }
+            XmlErrorKind::UnexpectedAttribute { attribute, element } => {
+                write!(f, "unexpected attribute `{}` on <{}>", attribute, element)
+            }
+            XmlErrorKind::DuplicateElement { element, parent } => {
+                write!(f, "element <{}> appears more than once in <{}>", element, parent)
+            }
+            XmlErrorKind::MissingElement { element, parent } => {
+                write!(f, "missing element <{}> in <{}>", element, parent)
+            }
+            XmlErrorKind::InvalidValue { element, value, expected } => {
+                write!(f, "invalid value {:?} in <{}>, expected {}", value, element, expected)
+            }
+            XmlErrorKind::UnexpectedText { element } => {
+                write!(f, "unexpected text in <{}>", element)
+            }
+        }
+    }
+}
+
+impl std::error::Error for XmlError {}
+
+impl TaskDefinition {
+    /// Reads a Task XML document.
+    pub fn from_xml(text: &str) -> Result<TaskDefinition, XmlError> {
+        deserialize::task_from_element(&reader::parse(text)?)
+    }
+
+    /// Reads a Task XML document from a file's bytes, which may be UTF-8 or
+    /// UTF-16 (as exported by the Task Scheduler MMC) with or without a
+    /// byte order mark.
+    pub fn from_xml_bytes(bytes: &[u8]) -> Result<TaskDefinition, XmlError> {
+        Self::from_xml(&reader::decode(bytes)?)
+    }
+
+    /// Lists what in the definition Task Scheduler would reject, or what
+    /// would be left out, in a document of schema `version`. The document is still written as
+    /// asked, so that older exports round-trip unchanged.
+    pub fn schema_warnings(&self, version: SchemaVersion) -> Vec<SchemaWarning> {
+        let mut warnings = Vec::new();
+        if version >= SchemaVersion::V1_4 {
+            for action in self.actions.iter().filter(|a| a.kind.is_deprecated()) {
+                warnings.push(SchemaWarning::DeprecatedAction {
+                    id: action.id.clone(),
+                    kind: action.kind.name(),
+                    version,
+                });
+            }
+        }
+        let settings = &self.settings;
+        let principal = &self.principal;
+        let unsupported = [
+            (SchemaVersion::V1_3, settings.use_unified_scheduling_engine, "UseUnifiedSchedulingEngine"),
+            (SchemaVersion::V1_3, settings.disallow_start_on_remote_app_session, "DisallowStartOnRemoteAppSession"),
+            (SchemaVersion::V1_3, principal.process_token_sid_type.is_some(), "ProcessTokenSidType"),
+            (SchemaVersion::V1_3, !principal.required_privileges.is_empty(), "RequiredPrivileges"),
+            (SchemaVersion::V1_4, settings.maintenance_settings.is_some(), "MaintenanceSettings"),
+            (SchemaVersion::V1_4, settings.volatile, "Volatile"),
+        ];
+        for (introduced, used, setting) in unsupported {
+            if version < introduced && used {
+                warnings.push(SchemaWarning::UnsupportedSetting { setting, version });
+            }
+        }
+        warnings
+    }
+
+    /// Serializes the definition as a UTF-8 Task XML document.
+    pub fn to_xml(&self, version: SchemaVersion) -> String {
+        serialize::task_to_xml(self, version, "UTF-8")
+    }
+
+    /// Serializes the definition as a UTF-16 (little endian, with BOM) Task
+    /// XML document, the encoding `schtasks` and the MMC export use.
+    pub fn to_xml_utf16(&self, version: SchemaVersion) -> Vec<u8> {
+        let xml = serialize::task_to_xml(self, version, "UTF-16");
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(xml.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+}
@@ -0,0 +1,339 @@
+use super::{XmlError, XmlErrorKind};
+
+/// An element of a parsed XML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    /// Character data directly inside the element, entities decoded.
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Element {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Builds an error located at this element.
+    pub fn error(&self, kind: XmlErrorKind) -> XmlError {
+        XmlError { line: self.line, column: self.column, kind }
+    }
+}
+
+/// Decodes a document from bytes, honouring a UTF-8 or UTF-16 byte order
+/// mark. Documents without a BOM are UTF-16 if they start with `<` followed
+/// by a zero byte and UTF-8 otherwise.
+pub(super) fn decode(bytes: &[u8]) -> Result<String, XmlError> {
+    let encoding_error = |message: &str| XmlError {
+        line: 1,
+        column: 1,
+        kind: XmlErrorKind::Encoding(message.to_string()),
+    };
+
+    let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+        if !bytes.len().is_multiple_of(2) {
+            return Err(encoding_error("UTF-16 document has an odd number of bytes"));
+        }
+        let units: Vec<u16> = bytes.chunks(2).map(|b| from([b[0], b[1]])).collect();
+        String::from_utf16(&units).map_err(|_| encoding_error("invalid UTF-16"))
+    };
+
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => {
+            String::from_utf8(rest.to_vec()).map_err(|_| encoding_error("invalid UTF-8"))
+        }
+        [b'<', 0, ..] => utf16(bytes, u16::from_le_bytes),
+        [0, b'<', ..] => utf16(bytes, u16::from_be_bytes),
+        _ => String::from_utf8(bytes.to_vec()).map_err(|_| encoding_error("invalid UTF-8")),
+    }
+}
+
+/// Parses a document and returns its root element.
+///
+/// Supports the subset of XML 1.0 Task Scheduler documents use: elements,
+/// attributes, character and entity references, CDATA sections, comments
+/// and processing instructions. Document type declarations are rejected.
+/// Line breaks are normalized first: `\r\n` and a lone `\r` are read as
+/// `\n` (XML 1.0 §2.11), so text from Windows exports has no `\r` left.
+pub(super) fn parse(text: &str) -> Result<Element, XmlError> {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut parser = Parser { src: &text, pos: 0 };
+    if parser.rest().starts_with('\u{FEFF}') {
+        parser.pos += '\u{FEFF}'.len_utf8();
+    }
+
+    parser.skip_misc()?;
+    if parser.rest().starts_with("<!DOCTYPE") {
+        return Err(parser.syntax("document type declarations are not supported"));
+    }
+    if !parser.rest().starts_with('<') {
+        return Err(parser.syntax("expected the root element"));
+    }
+    let root = parser.element()?;
+    parser.skip_misc()?;
+    if !parser.rest().is_empty() {
+        return Err(parser.syntax("unexpected content after the root element"));
+    }
+    Ok(root)
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    /// Line and column (both 1-based) of the current position.
+    fn location(&self) -> (usize, usize) {
+        let before = &self.src[..self.pos];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+        (line, column)
+    }
+
+    fn syntax(&self, message: &str) -> XmlError {
+        let (line, column) = self.location();
+        XmlError { line, column, kind: XmlErrorKind::Syntax(message.to_string()) }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start_matches([' ', '\t', '\r', '\n']);
+        self.pos = self.src.len() - trimmed.len();
+    }
+
+    /// Skips past `terminator`, failing with `what` if it never appears.
+    fn skip_past(&mut self, terminator: &str, what: &str) -> Result<&'a str, XmlError> {
+        match self.rest().find(terminator) {
+            Some(end) => {
+                let skipped = &self.rest()[..end];
+                self.pos += end + terminator.len();
+                Ok(skipped)
+            }
+            None => Err(self.syntax(&format!("unterminated {}", what))),
+        }
+    }
+
+    /// Skips whitespace, comments and processing instructions (including
+    /// the XML declaration).
+    fn skip_misc(&mut self) -> Result<(), XmlError> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<?") {
+                self.skip_past("?>", "processing instruction")?;
+            } else if self.rest().starts_with("<!--") {
+                self.skip_past("-->", "comment")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, XmlError> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.syntax("expected a name"));
+        }
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), XmlError> {
+        if !self.rest().starts_with(token) {
+            return Err(self.syntax(&format!("expected `{}`", token)));
+        }
+        self.pos += token.len();
+        Ok(())
+    }
+
+    fn element(&mut self) -> Result<Element, XmlError> {
+        let (line, column) = self.location();
+        self.expect("<")?;
+        let name = self.name()?;
+        let mut element = Element {
+            name,
+            attributes: Vec::new(),
+            children: Vec::new(),
+            text: String::new(),
+            line,
+            column,
+        };
+
+        // Attributes, up to the end of the start tag
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let key = self.name()?;
+            if element.attribute(&key).is_some() {
+                return Err(self.syntax(&format!("duplicate attribute `{}`", key)));
+            }
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(q @ ('"' | '\'')) => q,
+                _ => return Err(self.syntax("expected a quoted attribute value")),
+            };
+            self.pos += 1;
+            let raw = self.skip_past(&quote.to_string(), "attribute value")?;
+            let value = self.unescape(raw)?;
+            element.attributes.push((key, value));
+        }
+
+        // Content, up to the matching end tag
+        loop {
+            let rest = self.rest();
+            if rest.starts_with("</") {
+                let tag_start = self.pos;
+                self.pos += 2;
+                let end_name = self.name()?;
+                if end_name != element.name {
+                    self.pos = tag_start;
+                    return Err(self.syntax(&format!(
+                        "expected `</{}>` but found `</{}>`",
+                        element.name, end_name
+                    )));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(element);
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->", "comment")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.pos += "<![CDATA[".len();
+                let data = self.skip_past("]]>", "CDATA section")?;
+                element.text.push_str(data);
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>", "processing instruction")?;
+            } else if rest.starts_with('<') {
+                element.children.push(self.element()?);
+            } else if rest.is_empty() {
+                return Err(self.syntax(&format!("missing `</{}>`", element.name)));
+            } else {
+                let len = rest.find('<').unwrap_or(rest.len());
+                let text = self.unescape(&rest[..len])?;
+                element.text.push_str(&text);
+                self.pos += len;
+            }
+        }
+    }
+
+    /// Decodes entity and character references in `raw`.
+    fn unescape(&self, raw: &str) -> Result<String, XmlError> {
+        let mut out = String::with_capacity(raw.len());
+        let mut rest = raw;
+        while let Some(amp) = rest.find('&') {
+            out.push_str(&rest[..amp]);
+            rest = &rest[amp + 1..];
+            let end = rest
+                .find(';')
+                .ok_or_else(|| self.syntax("unterminated entity reference"))?;
+            let entity = &rest[..end];
+            let decoded = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => {
+                    let code = if let Some(hex) = entity.strip_prefix("#x") {
+                        u32::from_str_radix(hex, 16).ok()
+                    } else if let Some(dec) = entity.strip_prefix('#') {
+                        dec.parse().ok()
+                    } else {
+                        None
+                    };
+                    code.and_then(char::from_u32)
+                }
+            };
+            match decoded {
+                Some(c) => out.push(c),
+                None => return Err(self.syntax(&format!("unknown entity `&{};`", entity))),
+            }
+            rest = &rest[end + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_elements_attributes_and_text() {
+        let root = parse(
+            "<?xml version=\"1.0\"?>\n<!-- exported -->\n<a x='1' y=\"&lt;2&gt;\">\n  <b>one &amp; <![CDATA[<two>]]></b>\n  <c/>\n</a>\n",
+        )
+        .unwrap();
+
+        assert_eq!(root.name, "a");
+        assert_eq!(root.attribute("x"), Some("1"));
+        assert_eq!(root.attribute("y"), Some("<2>"));
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.children[0].text, "one & <two>");
+        assert_eq!((root.children[0].line, root.children[0].column), (4, 3));
+        assert_eq!(root.children[1].name, "c");
+    }
+
+    #[test]
+    fn decodes_character_references() {
+        let root = parse("<a>&#65;&#x42;</a>").unwrap();
+        assert_eq!(root.text, "AB");
+    }
+
+    #[test]
+    fn normalizes_line_breaks() {
+        let root = parse("<a>\r\n  <b>one\r\ntwo\rthree&#13;</b>\r\n</a>").unwrap();
+        assert_eq!(root.children[0].text, "one\ntwo\nthree\r");
+        assert_eq!(root.children[0].line, 2);
+    }
+
+    #[test]
+    fn reports_mismatched_end_tags_with_location() {
+        let error = parse("<a>\n  <b></c>\n</a>").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 6));
+        assert_eq!(
+            error.kind,
+            XmlErrorKind::Syntax("expected `</b>` but found `</c>`".to_string())
+        );
+    }
+
+    #[test]
+    fn decodes_byte_order_marks() {
+        let utf16le: Vec<u8> = [0xFF, 0xFE].into_iter()
+            .chain("<a/>".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        let utf16be: Vec<u8> = [0xFE, 0xFF].into_iter()
+            .chain("<a/>".encode_utf16().flat_map(u16::to_be_bytes))
+            .collect();
+
+        assert_eq!(decode(&utf16le).unwrap(), "<a/>");
+        assert_eq!(decode(&utf16be).unwrap(), "<a/>");
+        assert_eq!(decode(b"\xEF\xBB\xBF<a/>").unwrap(), "<a/>");
+        assert_eq!(decode(b"<a/>").unwrap(), "<a/>");
+        assert!(decode(&utf16le[..5]).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+use crate::model::{
+    Action,
+    ActionKind,
+    DaysOfWeek,
+    LogonType,
+    Months,
+    Principal,
+    ProcessTokenSidType,
+    RegistrationInfo,
+    RunLevel,
+    TaskDefinition,
+    TaskSettings,
+    Trigger,
+    TriggerKind,
+    WeeksOfMonth,
+};
+
+use super::writer::XmlWriter;
+use super::{SchemaVersion, TASK_NAMESPACE};
+
+/// Id of the single principal a task definition has; actions run under it.
+pub(super) const PRINCIPAL_ID: &str = "Author";
+
+pub(super) fn task_to_xml(definition: &TaskDefinition, version: SchemaVersion, encoding: &str) -> String {
+    let mut w = XmlWriter::new(encoding);
+    w.start("Task", &[("version", Some(version.as_str())), ("xmlns", Some(TASK_NAMESPACE))]);
+
+    write_registration_info(&mut w, &definition.registration_info);
+
+    w.start("Triggers", &[]);
+    for trigger in &definition.triggers {
+        write_trigger(&mut w, trigger);
+    }
+    w.end();
+
+    write_principal(&mut w, &definition.principal, version);
+    write_settings(&mut w, &definition.settings, version);
+
+    w.start("Actions", &[("Context", Some(PRINCIPAL_ID))]);
+    for action in &definition.actions {
+        write_action(&mut w, action);
+    }
+    w.end();
+
+    w.end();
+    w.finish()
+}
+
+fn write_registration_info(w: &mut XmlWriter, info: &RegistrationInfo) {
+    w.start("RegistrationInfo", &[]);
+    w.optional("Date", info.date.as_deref());
+    w.optional("Author", info.author.as_deref());
+    w.optional("Version", info.version.as_deref());
+    w.optional("Description", info.description.as_deref());
+    w.optional("URI", info.uri.as_deref());
+    w.optional("SecurityDescriptor", info.security_descriptor.as_deref());
+    w.optional("Source", info.source.as_deref());
+    w.optional("Documentation", info.documentation.as_deref());
+    w.end();
+}
+
+fn write_trigger(w: &mut XmlWriter, trigger: &Trigger) {
+    let name = match trigger.kind {
+        TriggerKind::Logon(_) => "LogonTrigger",
+        TriggerKind::Boot => "BootTrigger",
+        TriggerKind::Idle => "IdleTrigger",
+        TriggerKind::Registration => "RegistrationTrigger",
+        TriggerKind::SessionStateChange(_) => "SessionStateChangeTrigger",
+        TriggerKind::Event(_) => "EventTrigger",
+        TriggerKind::Time => "TimeTrigger",
+        TriggerKind::Daily(_)
+        | TriggerKind::Weekly(_)
+        | TriggerKind::Monthly(_)
+        | TriggerKind::MonthlyDayOfWeek(_) => "CalendarTrigger",
+    };
+
+    w.start(name, &[("id", trigger.id.as_deref())]);
+    if let Some(repetition) = &trigger.repetition {
+        w.start("Repetition", &[]);
+        w.element("Interval", &repetition.interval.to_string());
+        w.optional("Duration", repetition.duration.map(|d| d.to_string()).as_deref());
+        w.bool("StopAtDurationEnd", repetition.stop_at_duration_end);
+        w.end();
+    }
+    w.optional("StartBoundary", trigger.start_boundary.map(|t| t.to_string()).as_deref());
+    w.optional("EndBoundary", trigger.end_boundary.map(|t| t.to_string()).as_deref());
+    w.optional("ExecutionTimeLimit", trigger.execution_time_limit.map(|d| d.to_string()).as_deref());
+    w.bool("Enabled", trigger.enabled);
+    w.optional("RandomDelay", trigger.random_delay.map(|d| d.to_string()).as_deref());
+
+    match &trigger.kind {
+        TriggerKind::Logon(logon) => {
+            w.optional("UserId", logon.user_id.as_deref());
+            w.optional("Delay", trigger.delay.map(|d| d.to_string()).as_deref());
+        }
+        TriggerKind::Boot | TriggerKind::Registration => {
+            w.optional("Delay", trigger.delay.map(|d| d.to_string()).as_deref());
+        }
+        TriggerKind::SessionStateChange(session) => {
+            w.optional("Delay", trigger.delay.map(|d| d.to_string()).as_deref());
+            w.optional("UserId", session.user_id.as_deref());
+            w.element("StateChange", session.state_change.as_str());
+        }
+        TriggerKind::Event(event) => {
+            w.element("Subscription", &event.subscription);
+            w.optional("Delay", trigger.delay.map(|d| d.to_string()).as_deref());
+            w.optional("PeriodOfOccurrence", event.period_of_occurrence.map(|d| d.to_string()).as_deref());
+            w.optional("NumberOfOccurrences", event.number_of_occurrences.map(|n| n.to_string()).as_deref());
+            w.optional("MatchingElement", event.matching_element.as_deref());
+            if !event.value_queries.is_empty() {
+                w.start("ValueQueries", &[]);
+                for (name, xpath) in &event.value_queries {
+                    w.element_with("Value", &[("name", Some(name))], xpath);
+                }
+                w.end();
+            }
+        }
+        TriggerKind::Idle | TriggerKind::Time => {}
+        TriggerKind::Daily(daily) => {
+            w.start("ScheduleByDay", &[]);
+            w.element("DaysInterval", &daily.days_interval.to_string());
+            w.end();
+        }
+        TriggerKind::Weekly(weekly) => {
+            w.start("ScheduleByWeek", &[]);
+            write_days_of_week(w, weekly.days_of_week);
+            w.element("WeeksInterval", &weekly.weeks_interval.to_string());
+            w.end();
+        }
+        TriggerKind::Monthly(monthly) => {
+            w.start("ScheduleByMonth", &[]);
+            if !monthly.days_of_month.is_empty() || monthly.run_on_last_day_of_month {
+                w.start("DaysOfMonth", &[]);
+                for day in monthly.days_of_month.days() {
+                    w.element("Day", &day.to_string());
+                }
+                if monthly.run_on_last_day_of_month {
+                    w.element("Day", "Last");
+                }
+                w.end();
+            }
+            write_months(w, monthly.months);
+            w.end();
+        }
+        TriggerKind::MonthlyDayOfWeek(monthly) => {
+            w.start("ScheduleByMonthDayOfWeek", &[]);
+            w.start("Weeks", &[]);
+            for &(week, name) in WeeksOfMonth::NAMED {
+                if monthly.weeks_of_month.contains(week) {
+                    w.element("Week", name);
+                }
+            }
+            if monthly.run_on_last_week_of_month {
+                w.element("Week", "Last");
+            }
+            w.end();
+            write_days_of_week(w, monthly.days_of_week);
+            write_months(w, monthly.months);
+            w.end();
+        }
+    }
+    w.end();
+}
+
+/// Writes a set of days as `<DaysOfWeek><Monday />...</DaysOfWeek>`.
+fn write_days_of_week(w: &mut XmlWriter, days: DaysOfWeek) {
+    w.start("DaysOfWeek", &[]);
+    for &(day, name) in DaysOfWeek::NAMED {
+        if days.contains(day) {
+            w.start(name, &[]);
+            w.end();
+        }
+    }
+    w.end();
+}
+
+/// Writes a set of months as `<Months><January />...</Months>`.
+fn write_months(w: &mut XmlWriter, months: Months) {
+    w.start("Months", &[]);
+    for &(month, name) in Months::NAMED {
+        if months.contains(month) {
+            w.start(name, &[]);
+            w.end();
+        }
+    }
+    w.end();
+}
+
+fn write_principal(w: &mut XmlWriter, principal: &Principal, version: SchemaVersion) {
+    w.start("Principals", &[]);
+    w.start("Principal", &[("id", Some(PRINCIPAL_ID))]);
+    w.optional("UserId", principal.user_id.as_deref());
+    w.optional("LogonType", principal.logon_type.and_then(LogonType::as_xml));
+    w.optional("GroupId", principal.group_id.as_deref());
+    w.optional("DisplayName", principal.display_name.as_deref());
+    w.optional("RunLevel", principal.run_level.map(RunLevel::as_str));
+    // Schema 1.2 has no such elements; see TaskDefinition::schema_warnings
+    if version >= SchemaVersion::V1_3 {
+        w.optional("ProcessTokenSidType", principal.process_token_sid_type.map(ProcessTokenSidType::as_str));
+        if !principal.required_privileges.is_empty() {
+            w.start("RequiredPrivileges", &[]);
+            for privilege in &principal.required_privileges {
+                w.element("Privilege", privilege);
+            }
+            w.end();
+        }
+    }
+    w.end();
+    w.end();
+}
+
+/// Writes every setting, in the order the Task Scheduler export uses.
+fn write_settings(w: &mut XmlWriter, settings: &TaskSettings, version: SchemaVersion) {
+    w.start("Settings", &[]);
+    w.element("MultipleInstancesPolicy", settings.multiple_instances.as_str());
+    w.bool("DisallowStartIfOnBatteries", settings.disallow_start_if_on_batteries);
+    w.bool("StopIfGoingOnBatteries", settings.stop_if_going_on_batteries);
+    w.bool("AllowHardTerminate", settings.allow_hard_terminate);
+    w.bool("StartWhenAvailable", settings.start_when_available);
+    w.bool("RunOnlyIfNetworkAvailable", settings.run_only_if_network_available);
+
+    let idle = &settings.idle_settings;
+    w.start("IdleSettings", &[]);
+    w.element("Duration", &idle.idle_duration.to_string());
+    w.element("WaitTimeout", &idle.wait_timeout.to_string());
+    w.bool("StopOnIdleEnd", idle.stop_on_idle_end);
+    w.bool("RestartOnIdle", idle.restart_on_idle);
+    w.end();
+
+    if let Some(network) = &settings.network_settings {
+        w.start("NetworkSettings", &[]);
+        w.optional("Name", network.name.as_deref());
+        w.optional("Id", network.id.as_deref());
+        w.end();
+    }
+
+    w.bool("AllowStartOnDemand", settings.allow_demand_start);
+    w.bool("Enabled", settings.enabled);
+    w.bool("Hidden", settings.hidden);
+    w.bool("RunOnlyIfIdle", settings.run_only_if_idle);
+    // Schema 1.2 has no such element; see TaskDefinition::schema_warnings
+    if version >= SchemaVersion::V1_3 {
+        if settings.disallow_start_on_remote_app_session {
+            w.bool("DisallowStartOnRemoteAppSession", true);
+        }
+        w.bool("UseUnifiedSchedulingEngine", settings.use_unified_scheduling_engine);
+    }
+    if version >= SchemaVersion::V1_4 {
+        if let Some(maintenance) = &settings.maintenance_settings {
+            w.start("MaintenanceSettings", &[]);
+            w.element("Period", &maintenance.period.to_string());
+            w.optional("Deadline", maintenance.deadline.map(|d| d.to_string()).as_deref());
+            w.bool("Exclusive", maintenance.exclusive);
+            w.end();
+        }
+        if settings.volatile {
+            w.bool("Volatile", true);
+        }
+    }
+    w.bool("WakeToRun", settings.wake_to_run);
+    w.element("ExecutionTimeLimit", &settings.execution_time_limit.to_string());
+    w.element("Priority", &settings.priority.to_string());
+    if let Some(restart) = &settings.restart_on_failure {
+        w.start("RestartOnFailure", &[]);
+        w.element("Interval", &restart.interval.to_string());
+        w.element("Count", &restart.count.to_string());
+        w.end();
+    }
+    if let Some(delete_after) = settings.delete_expired_task_after {
+        w.element("DeleteExpiredTaskAfter", &delete_after.to_string());
+    }
+    w.end();
+}
+
+fn write_action(w: &mut XmlWriter, action: &Action) {
+    w.start(action.kind.name(), &[("id", action.id.as_deref())]);
+    match &action.kind {
+        ActionKind::Exec(exec) => {
+            w.element("Command", &exec.path);
+            w.optional("Arguments", exec.arguments.as_deref());
+            w.optional("WorkingDirectory", exec.working_directory.as_deref());
+        }
+        ActionKind::ComHandler(handler) => {
+            w.element("ClassId", &handler.class_id);
+            w.optional("Data", handler.data.as_deref());
+        }
+        ActionKind::SendEmail(email) => {
+            w.element("Server", &email.server);
+            w.optional("Subject", email.subject.as_deref());
+            w.optional("To", email.to.as_deref());
+            w.optional("Cc", email.cc.as_deref());
+            w.optional("Bcc", email.bcc.as_deref());
+            w.optional("ReplyTo", email.reply_to.as_deref());
+            w.element("From", &email.from);
+            if !email.header_fields.is_empty() {
+                w.start("HeaderFields", &[]);
+                for (name, value) in &email.header_fields {
+                    w.start("HeaderField", &[]);
+                    w.element("Name", name);
+                    w.element("Value", value);
+                    w.end();
+                }
+                w.end();
+            }
+            w.optional("Body", email.body.as_deref());
+            if !email.attachments.is_empty() {
+                w.start("Attachments", &[]);
+                for file in &email.attachments {
+                    w.element("File", file);
+                }
+                w.end();
+            }
+        }
+        ActionKind::ShowMessage(message) => {
+            w.element("Title", &message.title);
+            w.element("Body", &message.body);
+        }
+    }
+    w.end();
+}
@@ -0,0 +1,111 @@
+/// Minimal indenting XML writer.
+pub(super) struct XmlWriter {
+    out: String,
+    open: Vec<&'static str>,
+    /// Whether the innermost open element has no content yet.
+    empty: bool,
+}
+
+impl XmlWriter {
+    /// Starts a document with an XML declaration for `encoding`.
+    pub fn new(encoding: &str) -> Self {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"");
+        out.push_str(encoding);
+        out.push_str("\"?>\n");
+        Self { out, open: Vec::new(), empty: false }
+    }
+
+    /// Opens `name` with the given attributes; attributes whose value is
+    /// `None` are left out.
+    pub fn start(&mut self, name: &'static str, attributes: &[(&str, Option<&str>)]) {
+        self.start_tag(name, attributes);
+        self.out.push_str(">\n");
+        self.open.push(name);
+        self.empty = true;
+    }
+
+    /// Closes the most recently opened element, as `<name />` if nothing
+    /// was written inside it.
+    pub fn end(&mut self) {
+        let name = self.open.pop().expect("end() without matching start()");
+        if self.empty {
+            self.empty = false;
+            self.out.truncate(self.out.len() - ">\n".len());
+            self.out.push_str(" />\n");
+            return;
+        }
+        self.indent();
+        self.out.push_str("</");
+        self.out.push_str(name);
+        self.out.push_str(">\n");
+    }
+
+    /// Writes `<name>value</name>`.
+    pub fn element(&mut self, name: &'static str, value: &str) {
+        self.element_with(name, &[], value);
+    }
+
+    /// Writes `<name attributes>value</name>`.
+    pub fn element_with(&mut self, name: &'static str, attributes: &[(&str, Option<&str>)], value: &str) {
+        self.start_tag(name, attributes);
+        self.out.push('>');
+        escape_into(&mut self.out, value, false);
+        self.out.push_str("</");
+        self.out.push_str(name);
+        self.out.push_str(">\n");
+    }
+
+    /// Writes `<name>value</name>` if `value` is set.
+    pub fn optional(&mut self, name: &'static str, value: Option<&str>) {
+        if let Some(value) = value {
+            self.element(name, value);
+        }
+    }
+
+    /// Writes a boolean element as `true` or `false`.
+    pub fn bool(&mut self, name: &'static str, value: bool) {
+        self.element(name, if value { "true" } else { "false" });
+    }
+
+    pub fn finish(self) -> String {
+        debug_assert!(self.open.is_empty(), "unclosed elements: {:?}", self.open);
+        self.out
+    }
+
+    fn start_tag(&mut self, name: &str, attributes: &[(&str, Option<&str>)]) {
+        self.empty = false;
+        self.indent();
+        self.out.push('<');
+        self.out.push_str(name);
+        for (key, value) in attributes {
+            if let Some(value) = value {
+                self.out.push(' ');
+                self.out.push_str(key);
+                self.out.push_str("=\"");
+                escape_into(&mut self.out, value, true);
+                self.out.push('"');
+            }
+        }
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.open.len() {
+            self.out.push_str("  ");
+        }
+    }
+}
+
+/// Appends `value` with XML special characters escaped.
+pub(super) fn escape_into(out: &mut String, value: &str, attribute: bool) {
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if attribute => out.push_str("&quot;"),
+            '\'' if attribute => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+}
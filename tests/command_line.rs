@@ -0,0 +1,63 @@
+//! Round-trip tests for Windows command-line quoting.
+
+use schtask::model::{join_args, split_args};
+use schtask::{Action, ActionKind};
+
+/// Characters that take part in quoting, plus one that does not.
+const ALPHABET: [char; 6] = ['a', ' ', '\t', '"', '\\', 'é'];
+
+/// Every string over [`ALPHABET`] of at most `max_len` characters.
+fn strings(max_len: usize) -> Vec<String> {
+    let mut all = vec![String::new()];
+    let mut previous = vec![String::new()];
+    for _ in 0..max_len {
+        let next: Vec<String> = previous
+            .iter()
+            .flat_map(|prefix| ALPHABET.iter().map(move |&c| format!("{}{}", prefix, c)))
+            .collect();
+        all.extend(next.iter().cloned());
+        previous = next;
+    }
+    all
+}
+
+#[test]
+fn round_trips_every_single_argument() {
+    for arg in strings(6) {
+        let line = join_args(&[&arg]);
+        assert_eq!(split_args(&line), [arg.as_str()], "{:?} quoted as {:?}", arg, line);
+    }
+}
+
+#[test]
+fn round_trips_every_pair_and_triple_of_arguments() {
+    let short = strings(3);
+    for first in &short {
+        for second in &short {
+            let args = [first.as_str(), second.as_str()];
+            let line = join_args(&args);
+            assert_eq!(split_args(&line), args, "{:?} quoted as {:?}", args, line);
+        }
+    }
+
+    let shorter = strings(2);
+    for first in &shorter {
+        for second in &shorter {
+            for third in &shorter {
+                let args = [first.as_str(), second.as_str(), third.as_str()];
+                let line = join_args(&args);
+                assert_eq!(split_args(&line), args, "{:?} quoted as {:?}", args, line);
+            }
+        }
+    }
+}
+
+#[test]
+fn builds_exec_actions_from_argument_vectors() {
+    let action = Action::exec_args("C:\\Tools\\copy.exe", &["C:\\My Files\\", "D:\\backup", "--note=\"nightly\""]);
+    let ActionKind::Exec(exec) = &action.kind else { panic!("not an exec action") };
+
+    assert_eq!(exec.arguments.as_deref(), Some(r#""C:\My Files\\" D:\backup --note=\"nightly\""#));
+    assert_eq!(split_args(exec.arguments.as_deref().unwrap()), ["C:\\My Files\\", "D:\\backup", "--note=\"nightly\""]);
+    assert_eq!(Action::exec_args::<&str>("C:\\app.exe", &[]), Action::exec("C:\\app.exe", None));
+}
@@ -0,0 +1,73 @@
+//! Golden tests for the event log subscription builder.
+
+use schtask::{EventLevel, EventQuery, TaskDuration, TriggerKind, ValidationError};
+
+fn golden(name: &str) -> String {
+    let path = format!("{}/tests/golden/queries/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let text = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("cannot read {}: {}", path, e));
+    text.trim_end().to_string()
+}
+
+#[test]
+fn whole_channel() {
+    let query = EventQuery::new("Application");
+
+    assert_eq!(query.xpath().unwrap(), "*");
+    assert_eq!(query.to_query_list().unwrap(), golden("whole_channel.xml"));
+}
+
+#[test]
+fn failed_logons() {
+    let query = EventQuery::new("Security")
+        .provider("Microsoft-Windows-Security-Auditing")
+        .event_ids([4625, 4771])
+        .within(TaskDuration::from_hours(1));
+
+    assert_eq!(query.to_query_list().unwrap(), golden("failed_logons.xml"));
+}
+
+#[test]
+fn service_errors_across_channels() {
+    let query = EventQuery::new("System")
+        .channel("Application")
+        .provider("Service Control Manager")
+        .provider("Dr. Watson's Tool")
+        .event_id(7031)
+        .event_id_range(7022..=7024)
+        .level(EventLevel::Critical)
+        .level(EventLevel::Error)
+        .level(EventLevel::Information);
+
+    assert_eq!(query.to_query_list().unwrap(), golden("service_errors.xml"));
+}
+
+#[test]
+fn rejects_values_with_both_kinds_of_quote() {
+    let unquotable = || ValidationError::UnquotableXPathValue("O'Neil \"Tools\"".to_string());
+
+    let query = EventQuery::new("Application").provider("O'Neil \"Tools\"");
+    assert_eq!(query.xpath(), Err(unquotable()));
+    assert_eq!(query.to_query_list(), Err(unquotable()));
+
+    let query = EventQuery::new("Application").event_data_value("tool", "O'Neil \"Tools\"");
+    assert_eq!(query.to_trigger().map(|_| ()), Err(unquotable()));
+}
+
+#[test]
+fn passes_event_data_to_the_trigger() {
+    let query = EventQuery::new("System")
+        .provider("Service Control Manager")
+        .event_id(7031)
+        .event_data_value("service", "param1")
+        .value_query("time", "Event/System/TimeCreated/@SystemTime");
+
+    let TriggerKind::Event(event) = query.to_trigger().unwrap().kind else { panic!("expected an event trigger") };
+    assert_eq!(event.subscription, query.to_query_list().unwrap());
+    assert_eq!(
+        event.value_queries,
+        [
+            ("service".to_string(), "Event/EventData/Data[@Name='param1']".to_string()),
+            ("time".to_string(), "Event/System/TimeCreated/@SystemTime".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,295 @@
+//! Golden tests for the Task Scheduler XML serializer.
+
+use schtask::{
+    Action,
+    ActionKind,
+    DaysOfMonth,
+    DaysOfWeek,
+    EmailAction,
+    EventQuery,
+    ExecAction,
+    IdleSettings,
+    LogonType,
+    Months,
+    MultipleInstancesPolicy,
+    NetworkSettings,
+    Principal,
+    Repetition,
+    RestartOnFailure,
+    RunLevel,
+    SchemaVersion,
+    SchemaWarning,
+    SessionStateChange,
+    TaskBuilder,
+    TaskDuration,
+    TaskSettings,
+    Timestamp,
+    Trigger,
+    TriggerKind,
+    WeeksOfMonth,
+};
+
+fn golden(name: &str) -> String {
+    let path = format!("{}/tests/golden/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("cannot read {}: {}", path, e))
+}
+
+fn at(s: &str) -> Timestamp {
+    s.parse().unwrap()
+}
+
+#[test]
+fn logon_task() {
+    let mut trigger = Trigger::logon(Some("CORP\\alice"));
+    trigger.id = Some("Trigger1".to_string());
+    trigger.start_boundary = Some(at("2024-03-19T00:00:00"));
+
+    let task = TaskBuilder::new("Notepad")
+        .author("Author Name")
+        .settings(TaskSettings { start_when_available: true, ..TaskSettings::default() })
+        .trigger(trigger)
+        .action(Action::exec("C:\\Windows\\System32\\notepad.exe", Some("C:\\notes.txt")))
+        .build()
+        .unwrap();
+
+    assert_eq!(task.definition.to_xml(SchemaVersion::V1_2), golden("logon_task.xml"));
+}
+
+#[test]
+fn calendar_triggers() {
+    let mut monthly = Trigger::monthly(
+        at("2024-01-01T03:00:00"),
+        DaysOfMonth::from_days(&[1, 15]).unwrap(),
+        Months::JANUARY | Months::JULY,
+    );
+    if let TriggerKind::Monthly(schedule) = &mut monthly.kind {
+        schedule.run_on_last_day_of_month = true;
+    }
+    let mut last_friday = Trigger::monthly_day_of_week(
+        at("2024-01-01T18:00:00"),
+        WeeksOfMonth::FIRST,
+        DaysOfWeek::FRIDAY,
+        Months::ALL,
+    );
+    if let TriggerKind::MonthlyDayOfWeek(schedule) = &mut last_friday.kind {
+        schedule.run_on_last_week_of_month = true;
+    }
+
+    let task = TaskBuilder::new("Reports")
+        .trigger(Trigger::once(at("2024-06-01T09:30:00")))
+        .trigger(Trigger::daily(at("2024-01-01T08:00:00"), 2))
+        .trigger(Trigger::weekly(at("2024-01-01T12:00:00"), DaysOfWeek::MONDAY | DaysOfWeek::THURSDAY, 1))
+        .trigger(monthly)
+        .trigger(last_friday)
+        .action(Action::exec("C:\\Tools\\report.exe", None))
+        .build()
+        .unwrap();
+
+    assert_eq!(task.definition.to_xml(SchemaVersion::V1_2), golden("calendar_triggers.xml"));
+}
+
+#[test]
+fn repetition_and_delays() {
+    let mut logon = Trigger::logon(None);
+    logon.repetition = Some(Repetition::new(TaskDuration::from_mins(15), Some(TaskDuration::from_hours(8))));
+    logon.execution_time_limit = Some(TaskDuration::from_mins(30));
+    logon.delay = Some(TaskDuration::from_secs(30));
+
+    let mut daily = Trigger::daily(at("2024-01-01T02:00:00"), 1);
+    daily.repetition = Some(Repetition {
+        interval: TaskDuration::from_hours(1),
+        duration: None,
+        stop_at_duration_end: true,
+    });
+    daily.random_delay = Some(TaskDuration::from_hours(26));
+
+    let task = TaskBuilder::new("Poll")
+        .trigger(logon)
+        .trigger(daily)
+        .action(Action::exec("C:\\Tools\\poll.exe", None))
+        .build()
+        .unwrap();
+
+    assert_eq!(task.definition.to_xml(SchemaVersion::V1_2), golden("repetition.xml"));
+}
+
+#[test]
+fn system_and_session_triggers() {
+    let mut boot = Trigger::boot();
+    boot.delay = Some(TaskDuration::from_mins(2));
+    let mut unlock = Trigger::session_state_change(SessionStateChange::SessionUnlock, Some("CORP\\alice"));
+    unlock.delay = Some(TaskDuration::from_secs(10));
+
+    let task = TaskBuilder::new("Watch")
+        .trigger(boot)
+        .trigger(Trigger::idle())
+        .trigger(Trigger::registration())
+        .trigger(unlock)
+        .trigger(Trigger::session_state_change(SessionStateChange::RemoteConnect, None))
+        .action(Action::exec("C:\\Tools\\watch.exe", None))
+        .build()
+        .unwrap();
+
+    assert_eq!(task.definition.to_xml(SchemaVersion::V1_2), golden("system_triggers.xml"));
+}
+
+#[test]
+fn event_trigger() {
+    let query = EventQuery::new("System").provider("Service Control Manager").event_id(7031);
+    let mut trigger = Trigger::event(&query.to_query_list().unwrap());
+    trigger.delay = Some(TaskDuration::from_mins(1));
+    if let TriggerKind::Event(event) = &mut trigger.kind {
+        event.value_queries.push(("service".to_string(), "Event/EventData/Data[@Name='param1']".to_string()));
+    }
+
+    let action = Action::new(ActionKind::Exec(ExecAction {
+        path: "%ProgramFiles%\\Tools\\restart.cmd".to_string(),
+        arguments: Some("$(service)".to_string()),
+        working_directory: Some("%TEMP%".to_string()),
+    }));
+
+    let task = TaskBuilder::new("Remediate")
+        .trigger(trigger)
+        .action(action)
+        .build()
+        .unwrap();
+
+    assert_eq!(task.definition.to_xml(SchemaVersion::V1_2), golden("event_trigger.xml"));
+}
+
+#[test]
+fn escapes_text_and_attributes() {
+    let mut action = Action::exec("C:\\Tools\\run.cmd", Some("\"a & b\" <c>"));
+    action.id = Some("Run \"it\"".to_string());
+
+    let task = TaskBuilder::new("Escaping")
+        .description("Uses <, > & quotes")
+        .action(action)
+        .build()
+        .unwrap();
+
+    assert_eq!(task.definition.to_xml(SchemaVersion::V1_4), golden("escaping.xml"));
+}
+
+#[test]
+fn com_handler_and_legacy_actions() {
+    let email = EmailAction {
+        server: "smtp.example.com".to_string(),
+        subject: Some("Backup finished".to_string()),
+        to: Some("ops@example.com".to_string()),
+        from: "backup@example.com".to_string(),
+        header_fields: vec![("X-Priority".to_string(), "1".to_string())],
+        body: Some("See the attached log.".to_string()),
+        attachments: vec!["C:\\Logs\\backup.log".to_string()],
+        ..EmailAction::default()
+    };
+
+    let task = TaskBuilder::new("Notify")
+        .action(Action::com_handler("{E3A19126-8E23-4E8A-9A9E-6EF3E26A6E39}", Some("<Sync mode=\"full\" />")))
+        .action(Action::new(ActionKind::SendEmail(email)))
+        .action(Action::show_message("Backup", "The backup has finished."))
+        .build()
+        .unwrap();
+
+    assert_eq!(task.definition.to_xml(SchemaVersion::V1_2), golden("legacy_actions.xml"));
+    assert_eq!(task.definition.schema_warnings(SchemaVersion::V1_3), []);
+
+    let warnings = task.definition.schema_warnings(SchemaVersion::V1_4);
+    let messages: Vec<String> = warnings.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        [
+            "SendEmail action \"SendEmail1\" is not supported by task schema 1.4",
+            "ShowMessage action \"ShowMessage1\" is not supported by task schema 1.4",
+        ]
+    );
+    assert_eq!(
+        warnings[0],
+        SchemaWarning::DeprecatedAction {
+            id: Some("SendEmail1".to_string()),
+            kind: "SendEmail",
+            version: SchemaVersion::V1_4,
+        }
+    );
+}
+
+#[test]
+fn s4u_principal() {
+    let principal = Principal {
+        display_name: Some("Backup service".to_string()),
+        run_level: Some(RunLevel::Highest),
+        ..Principal::user("CORP\\svc-backup", LogonType::S4U)
+    };
+
+    let task = TaskBuilder::new("Backup")
+        .principal(principal)
+        .trigger(Trigger::boot())
+        .action(Action::exec("C:\\Tools\\backup.exe", None))
+        .build()
+        .unwrap();
+
+    assert_eq!(task.definition.to_xml(SchemaVersion::V1_2), golden("s4u_principal.xml"));
+}
+
+#[test]
+fn task_settings() {
+    let settings = TaskSettings {
+        allow_demand_start: false,
+        disallow_start_if_on_batteries: false,
+        stop_if_going_on_batteries: false,
+        multiple_instances: MultipleInstancesPolicy::Queue,
+        priority: 4,
+        restart_on_failure: Some(RestartOnFailure { count: 3, interval: TaskDuration::from_mins(5) }),
+        run_only_if_network_available: true,
+        run_only_if_idle: true,
+        idle_settings: IdleSettings {
+            idle_duration: TaskDuration::from_mins(20),
+            wait_timeout: TaskDuration::from_hours(2),
+            stop_on_idle_end: false,
+            restart_on_idle: true,
+        },
+        network_settings: Some(NetworkSettings {
+            name: Some("CorpNet".to_string()),
+            id: Some("{6D5DD0A9-3F6A-4D2E-9C43-1B0E5C7A8F21}".to_string()),
+        }),
+        wake_to_run: true,
+        hidden: true,
+        delete_expired_task_after: Some(TaskDuration::from_days(30)),
+        execution_time_limit: TaskDuration::from_hours(2),
+        use_unified_scheduling_engine: true,
+        ..TaskSettings::default()
+    };
+    let mut daily = Trigger::daily(at("2024-01-01T03:00:00"), 1);
+    daily.end_boundary = Some(at("2024-12-31T00:00:00"));
+
+    let task = TaskBuilder::new("Cleanup")
+        .settings(settings)
+        .trigger(daily)
+        .action(Action::exec("C:\\Tools\\cleanup.exe", None))
+        .build()
+        .unwrap();
+
+    assert_eq!(task.definition.to_xml(SchemaVersion::V1_3), golden("task_settings.xml"));
+    assert_eq!(task.definition.schema_warnings(SchemaVersion::V1_3), []);
+    assert_eq!(
+        task.definition.schema_warnings(SchemaVersion::V1_2),
+        [SchemaWarning::UnsupportedSetting { setting: "UseUnifiedSchedulingEngine", version: SchemaVersion::V1_2 }]
+    );
+    assert!(!task.definition.to_xml(SchemaVersion::V1_2).contains("UseUnifiedSchedulingEngine"));
+}
+
+#[test]
+fn utf16_output_has_bom_and_declares_utf16() {
+    let task = TaskBuilder::new("Utf16")
+        .action(Action::exec("C:\\app.exe", None))
+        .build()
+        .unwrap();
+
+    let bytes = task.definition.to_xml_utf16(SchemaVersion::V1_2);
+    assert_eq!(&bytes[..2], &[0xFF, 0xFE]);
+
+    let units: Vec<u16> = bytes[2..].chunks(2).map(|b| u16::from_le_bytes([b[0], b[1]])).collect();
+    let text = String::from_utf16(&units).unwrap();
+    assert!(text.starts_with("<?xml version=\"1.0\" encoding=\"UTF-16\"?>\n<Task version=\"1.2\""));
+    assert_eq!(text.replace("UTF-16", "UTF-8"), task.definition.to_xml(SchemaVersion::V1_2));
+}
@@ -0,0 +1,202 @@
+//! Tests for reading Task Scheduler XML into the task model.
+
+use schtask::{
+    Action,
+    LogonTrigger,
+    LogonType,
+    MaintenanceSettings,
+    Principal,
+    ProcessTokenSidType,
+    RunLevel,
+    SchemaVersion,
+    TaskDefinition,
+    TaskDuration,
+    TriggerKind,
+    XmlErrorKind,
+};
+
+fn golden(name: &str) -> String {
+    let path = format!("{}/tests/golden/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("cannot read {}: {}", path, e))
+}
+
+#[test]
+fn round_trips_golden_files() {
+    let files = [
+        ("logon_task.xml", SchemaVersion::V1_2),
+        ("escaping.xml", SchemaVersion::V1_4),
+        ("calendar_triggers.xml", SchemaVersion::V1_2),
+        ("repetition.xml", SchemaVersion::V1_2),
+        ("system_triggers.xml", SchemaVersion::V1_2),
+        ("event_trigger.xml", SchemaVersion::V1_2),
+        ("legacy_actions.xml", SchemaVersion::V1_2),
+        ("s4u_principal.xml", SchemaVersion::V1_2),
+        ("task_settings.xml", SchemaVersion::V1_3),
+        ("windows_export_rewritten.xml", SchemaVersion::V1_4),
+    ];
+    for (name, version) in files {
+        let xml = golden(name);
+        let definition = TaskDefinition::from_xml(&xml).unwrap();
+        assert_eq!(definition.to_xml(version), xml, "{}", name);
+    }
+}
+
+#[test]
+fn reads_utf16_exports_with_bom() {
+    let xml = "<?xml version=\"1.0\" encoding=\"UTF-16\"?>\r\n\
+        <Task version=\"1.2\" xmlns=\"http://schemas.microsoft.com/windows/2004/02/mit/task\">\r\n\
+        \x20 <Triggers>\r\n\
+        \x20   <LogonTrigger>\r\n\
+        \x20     <Enabled>false</Enabled>\r\n\
+        \x20   </LogonTrigger>\r\n\
+        \x20 </Triggers>\r\n\
+        \x20 <Principals>\r\n\
+        \x20   <Principal id=\"LocalSystem\">\r\n\
+        \x20     <UserId>S-1-5-18</UserId>\r\n\
+        \x20   </Principal>\r\n\
+        \x20 </Principals>\r\n\
+        \x20 <Actions Context=\"LocalSystem\">\r\n\
+        \x20   <Exec>\r\n\
+        \x20     <Command>%windir%\\system32\\cleanmgr.exe</Command>\r\n\
+        \x20   </Exec>\r\n\
+        \x20 </Actions>\r\n\
+        </Task>\r\n";
+    let bytes: Vec<u8> = [0xFF, 0xFE].into_iter()
+        .chain(xml.encode_utf16().flat_map(u16::to_le_bytes))
+        .collect();
+
+    let definition = TaskDefinition::from_xml_bytes(&bytes).unwrap();
+
+    assert_eq!(definition.principal.user_id.as_deref(), Some("S-1-5-18"));
+    assert_eq!(definition.principal.effective_logon_type(), LogonType::ServiceAccount);
+    assert_eq!(definition.triggers.len(), 1);
+    assert!(!definition.triggers[0].enabled);
+    assert_eq!(definition.triggers[0].kind, TriggerKind::Logon(LogonTrigger::default()));
+    assert_eq!(definition.actions, vec![Action::exec("%windir%\\system32\\cleanmgr.exe", None)]);
+}
+
+#[test]
+fn reports_unknown_elements_with_location() {
+    let xml = golden("logon_task.xml").replace(
+        "<UserId>CORP\\alice</UserId>",
+        "<UserId>CORP\\alice</UserId>\n      <Frobnicate>1</Frobnicate>",
+    );
+
+    let error = TaskDefinition::from_xml(&xml).unwrap_err();
+
+    assert_eq!((error.line, error.column), (11, 7));
+    assert_eq!(
+        error.kind,
+        XmlErrorKind::UnexpectedElement {
+            element: "Frobnicate".to_string(),
+            parent: "LogonTrigger".to_string(),
+        }
+    );
+    assert_eq!(error.to_string(), "line 11, column 7: unexpected element <Frobnicate> in <LogonTrigger>");
+}
+
+#[test]
+fn rejects_documents_that_are_not_tasks() {
+    let error = TaskDefinition::from_xml("<Task version=\"1.2\"><Actions/></Task>").unwrap_err();
+    assert!(matches!(error.kind, XmlErrorKind::NotATask(_)));
+
+    let error = TaskDefinition::from_xml(&golden("logon_task.xml").replace("version=\"1.2\"", "version=\"9.9\""))
+        .unwrap_err();
+    assert_eq!(error.kind, XmlErrorKind::UnsupportedVersion("9.9".to_string()));
+
+    let error = TaskDefinition::from_xml(&golden("logon_task.xml").replace("<Enabled>true</Enabled>", "<Enabled>yes</Enabled>"))
+        .unwrap_err();
+    assert_eq!(
+        error.kind,
+        XmlErrorKind::InvalidValue {
+            element: "Enabled".to_string(),
+            value: "yes".to_string(),
+            expected: "true or false",
+        }
+    );
+}
+
+#[test]
+fn requires_a_state_change_on_session_triggers() {
+    let xml = golden("system_triggers.xml").replace("      <StateChange>RemoteConnect</StateChange>\n", "");
+    let error = TaskDefinition::from_xml(&xml).unwrap_err();
+
+    assert_eq!(
+        error.kind,
+        XmlErrorKind::MissingElement {
+            element: "StateChange".to_string(),
+            parent: "SessionStateChangeTrigger".to_string(),
+        }
+    );
+}
+
+#[test]
+fn reads_group_principals() {
+    let xml = golden("logon_task.xml").replace(
+        "<Principal id=\"Author\" />",
+        "<Principal id=\"Author\">\n      <GroupId>S-1-5-32-545</GroupId>\n      <RunLevel>LeastPrivilege</RunLevel>\n    </Principal>",
+    );
+    let definition = TaskDefinition::from_xml(&xml).unwrap();
+
+    assert_eq!(
+        definition.principal,
+        Principal { run_level: Some(RunLevel::LeastPrivilege), group_id: Some("S-1-5-32-545".to_string()), ..Principal::default() }
+    );
+    assert_eq!(definition.principal.effective_logon_type(), LogonType::Group);
+    assert_eq!(definition.validate(), Ok(()));
+
+    let error = TaskDefinition::from_xml(&xml.replace("LeastPrivilege", "Elevated")).unwrap_err();
+    assert_eq!(
+        error.kind,
+        XmlErrorKind::InvalidValue {
+            element: "RunLevel".to_string(),
+            value: "Elevated".to_string(),
+            expected: "LeastPrivilege or HighestAvailable",
+        }
+    );
+}
+
+#[test]
+fn reads_windows_exports_with_schema_1_3_and_1_4_elements() {
+    let path = format!("{}/tests/golden/windows_export.xml", env!("CARGO_MANIFEST_DIR"));
+    let definition = TaskDefinition::from_xml_bytes(&std::fs::read(&path).unwrap()).unwrap();
+
+    assert_eq!(
+        definition.registration_info.security_descriptor.as_deref(),
+        Some("D:AI(A;;FA;;;BA)(A;;FA;;;SY)(A;;FRFX;;;LS)(A;;FR;;;AU)")
+    );
+    let TriggerKind::Event(event) = &definition.triggers[0].kind else {
+        panic!("expected an event trigger, got {:?}", definition.triggers[0].kind);
+    };
+    assert_eq!(event.number_of_occurrences, Some(3));
+    assert_eq!(event.period_of_occurrence, Some(TaskDuration::from_hours(1)));
+    assert_eq!(event.matching_element.as_deref(), Some("DriveName"));
+    assert_eq!(definition.principal.process_token_sid_type, Some(ProcessTokenSidType::Unrestricted));
+    assert_eq!(definition.principal.required_privileges, ["SeChangeNotifyPrivilege", "SeManageVolumePrivilege"]);
+    let settings = &definition.settings;
+    assert!(!settings.disallow_start_on_remote_app_session);
+    assert!(!settings.volatile);
+    assert_eq!(
+        settings.maintenance_settings,
+        Some(MaintenanceSettings {
+            period: TaskDuration::from_days(7),
+            deadline: Some(TaskDuration::from_days(14)),
+            exclusive: true,
+        })
+    );
+
+    let xml = definition.to_xml(SchemaVersion::V1_4);
+    assert_eq!(xml, golden("windows_export_rewritten.xml"));
+    assert_eq!(TaskDefinition::from_xml(&xml).unwrap(), definition);
+
+    let warnings: Vec<String> = definition.schema_warnings(SchemaVersion::V1_2).iter().map(ToString::to_string).collect();
+    assert_eq!(
+        warnings,
+        [
+            "setting UseUnifiedSchedulingEngine is not supported by task schema 1.2",
+            "setting ProcessTokenSidType is not supported by task schema 1.2",
+            "setting RequiredPrivileges is not supported by task schema 1.2",
+            "setting MaintenanceSettings is not supported by task schema 1.2",
+        ]
+    );
+}